@@ -0,0 +1,126 @@
+// Times the placement lookups of the GA with the time-independent checks cached in
+// ProblemIndex against rebuilding them before every lookup, on 500 tagged orders over 12 hours,
+// 40 drivers and 20 vehicles. Each round books every order greedily into a fresh plan, as for
+// one individual of a population. Run in release:
+//
+//     cargo run --release --example compatibility_benchmark -- [seed...]
+//
+// Seeds 3 and 4 are used when none are given.
+use chrono::{Duration as ChronoDuration, NaiveDate, NaiveDateTime};
+use constrained_scheduling_wasm::solver::{greedy_placement_rounds, Driver, Order, Vehicle};
+use rand::prelude::*;
+use std::time::Instant;
+
+const ORDERS: usize = 500;
+const DRIVERS: usize = 40;
+const VEHICLES: usize = 20;
+const ROUNDS: usize = 50;
+const TAGS: [&str; 4] = ["cold", "fragile", "hazmat", "oversize"];
+
+fn day_start() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 1, 1)
+        .unwrap()
+        .and_hms_opt(6, 0, 0)
+        .unwrap()
+}
+
+// Some of the tags, each kept with the given probability
+fn some_tags(rng: &mut StdRng, probability: f64) -> Option<Vec<String>> {
+    let tags: Vec<String> = TAGS
+        .iter()
+        .filter(|_| rng.gen_bool(probability))
+        .map(|t| t.to_string())
+        .collect();
+    (!tags.is_empty()).then_some(tags)
+}
+
+// Orders of 30 to 90 minutes starting anywhere in the 12 hours, most with a tag or two that
+// only part of the drivers and vehicles cover. Capacities grow along the fleet.
+fn instance(seed: u64) -> (Vec<Driver>, Vec<Vehicle>, Vec<Order>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let drivers = (0..DRIVERS)
+        .map(|i| Driver {
+            id: format!("d{}", i),
+            breaks: None,
+            recurring_breaks: None,
+            shifts: None,
+            preference: None,
+            skills: some_tags(&mut rng, 0.6),
+            max_orders: None,
+            allowed_vehicle_ids: None,
+            max_work_minutes: None,
+        })
+        .collect();
+    let vehicles = (0..VEHICLES)
+        .map(|i| Vehicle {
+            id: format!("v{}", i),
+            tags: some_tags(&mut rng, 0.6),
+            max_weight: 100.0 + 10.0 * i as f64,
+            max_volume: None,
+            volume_unlimited: None,
+            unavailable: None,
+            min_fill_pct: None,
+            min_break_minutes: None,
+            max_distance_km: None,
+        })
+        .collect();
+    let orders = (0..ORDERS)
+        .map(|i| {
+            let start_time = day_start() + ChronoDuration::minutes(rng.gen_range(0..12 * 60));
+            let minutes = rng.gen_range(30..90);
+            Order {
+                id: format!("o{}", i),
+                start_time,
+                end_time: start_time + ChronoDuration::minutes(minutes),
+                priority: Some(rng.gen_range(1..5) as f64),
+                tags: some_tags(&mut rng, 0.3),
+                weight: rng.gen_range(10.0..250.0),
+                volume: None,
+                duration_minutes: None,
+                earliest_start: None,
+                latest_end: None,
+                must_follow: None,
+                splittable: false,
+                required: None,
+                lat: None,
+                lon: None,
+                group_id: None,
+                same_vehicle_group: None,
+                service_minutes: None,
+                allowed_driver_ids: None,
+                allowed_vehicle_ids: None,
+                required_drivers: None,
+            }
+        })
+        .collect();
+    (drivers, vehicles, orders)
+}
+
+fn main() {
+    let mut seeds: Vec<u64> = std::env::args()
+        .skip(1)
+        .map(|arg| arg.parse().expect("seeds must be integers"))
+        .collect();
+    if seeds.is_empty() {
+        seeds = vec![3, 4];
+    }
+
+    for seed in seeds {
+        let (drivers, vehicles, orders) = instance(seed);
+        for precomputed in [false, true] {
+            let started = Instant::now();
+            let placed = greedy_placement_rounds(&drivers, &vehicles, &orders, ROUNDS, precomputed);
+            println!(
+                "seed {} {:<11} placed {:>3} wall {:>7.1} ms",
+                seed,
+                if precomputed {
+                    "precomputed"
+                } else {
+                    "per lookup"
+                },
+                placed,
+                started.elapsed().as_secs_f64() * 1000.0,
+            );
+        }
+    }
+}
@@ -0,0 +1,126 @@
+// Compares the crossover strategies with and without config.crossover_repair on 200 orders
+// packed into 4 hours, 12 drivers and 8 vehicles, so most children of a crossover collide. Runs
+// a fixed number of generations without a time budget, the numbers only depend on the seed.
+// Run in release:
+//
+//     cargo run --release --example crossover_benchmark -- [seed...]
+//
+// Seeds 3 and 4 are used when none are given.
+use chrono::{Duration as ChronoDuration, NaiveDate, NaiveDateTime};
+use constrained_scheduling_wasm::solver::{
+    solve, CrossoverStrategy, Driver, Order, SolverConfig, Vehicle,
+};
+use rand::prelude::*;
+
+const ORDERS: usize = 200;
+const DRIVERS: usize = 12;
+const VEHICLES: usize = 8;
+
+fn day_start() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 1, 1)
+        .unwrap()
+        .and_hms_opt(8, 0, 0)
+        .unwrap()
+}
+
+// Orders of 20 to 60 minutes starting anywhere in the 4 hours, far more than the fleet can
+// take. Capacities grow along the fleet.
+fn instance(seed: u64) -> (Vec<Driver>, Vec<Vehicle>, Vec<Order>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let drivers = (0..DRIVERS)
+        .map(|i| Driver {
+            id: format!("d{}", i),
+            breaks: None,
+            recurring_breaks: None,
+            shifts: None,
+            preference: None,
+            skills: None,
+            max_orders: None,
+            allowed_vehicle_ids: None,
+            max_work_minutes: None,
+        })
+        .collect();
+    let vehicles = (0..VEHICLES)
+        .map(|i| Vehicle {
+            id: format!("v{}", i),
+            tags: None,
+            max_weight: 100.0 + 10.0 * i as f64,
+            max_volume: None,
+            volume_unlimited: None,
+            unavailable: None,
+            min_fill_pct: None,
+            min_break_minutes: None,
+            max_distance_km: None,
+        })
+        .collect();
+    let orders = (0..ORDERS)
+        .map(|i| {
+            let start_time = day_start() + ChronoDuration::minutes(rng.gen_range(0..4 * 60));
+            let minutes = rng.gen_range(20..60);
+            Order {
+                id: format!("o{}", i),
+                start_time,
+                end_time: start_time + ChronoDuration::minutes(minutes),
+                priority: Some(rng.gen_range(1..5) as f64),
+                tags: None,
+                weight: rng.gen_range(10.0..90.0),
+                volume: None,
+                duration_minutes: None,
+                earliest_start: None,
+                latest_end: None,
+                must_follow: None,
+                splittable: false,
+                required: None,
+                lat: None,
+                lon: None,
+                group_id: None,
+                same_vehicle_group: None,
+                service_minutes: None,
+                allowed_driver_ids: None,
+                allowed_vehicle_ids: None,
+                required_drivers: None,
+            }
+        })
+        .collect();
+    (drivers, vehicles, orders)
+}
+
+fn main() {
+    let mut seeds: Vec<u64> = std::env::args()
+        .skip(1)
+        .map(|arg| arg.parse().expect("seeds must be integers"))
+        .collect();
+    if seeds.is_empty() {
+        seeds = vec![3, 4];
+    }
+
+    for seed in seeds {
+        let (drivers, vehicles, orders) = instance(seed);
+        for crossover in [
+            CrossoverStrategy::OnePoint,
+            CrossoverStrategy::TwoPoint,
+            CrossoverStrategy::Uniform,
+        ] {
+            for crossover_repair in [false, true] {
+                let config = SolverConfig {
+                    seed: Some(seed),
+                    generations: 100,
+                    max_generations_without_improvement: 100,
+                    ignore_time_budget: true,
+                    crossover,
+                    crossover_repair,
+                    ..SolverConfig::default()
+                };
+                let response = solve(&drivers, &vehicles, &orders, &config);
+                println!(
+                    "seed {} {:<8} repair {:<5} assigned {:>3} score {:>7.1}",
+                    seed,
+                    format!("{:?}", crossover),
+                    crossover_repair,
+                    response.assignments.len(),
+                    response.score,
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,118 @@
+// Compares the default initialization with config.two_phase on 2000 orders over 12 hours, 60
+// drivers and 30 vehicles, with a 30 s budget per solve. Run in release:
+//
+//     cargo run --release --example two_phase_benchmark -- [seed...]
+//
+// Seeds 3 and 4 are used when none are given.
+use chrono::{Duration as ChronoDuration, NaiveDate, NaiveDateTime};
+use constrained_scheduling_wasm::solver::{solve, Driver, Order, SolverConfig, Vehicle};
+use rand::prelude::*;
+
+const ORDERS: usize = 2000;
+const DRIVERS: usize = 60;
+const VEHICLES: usize = 30;
+
+fn day_start() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 1, 1)
+        .unwrap()
+        .and_hms_opt(6, 0, 0)
+        .unwrap()
+}
+
+// Orders of 30 to 90 minutes starting anywhere in the 12 hours, a third of them with a flexible
+// window. Every second driver prefers a vehicle, capacities grow along the fleet.
+fn instance(seed: u64) -> (Vec<Driver>, Vec<Vehicle>, Vec<Order>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let drivers = (0..DRIVERS)
+        .map(|i| Driver {
+            id: format!("d{}", i),
+            breaks: None,
+            recurring_breaks: None,
+            shifts: None,
+            preference: (i % 2 == 0).then(|| format!("v{}", i % VEHICLES)),
+            skills: None,
+            max_orders: None,
+            allowed_vehicle_ids: None,
+            max_work_minutes: None,
+        })
+        .collect();
+    let vehicles = (0..VEHICLES)
+        .map(|i| Vehicle {
+            id: format!("v{}", i),
+            tags: None,
+            max_weight: 100.0 + 10.0 * i as f64,
+            max_volume: None,
+            volume_unlimited: None,
+            unavailable: None,
+            min_fill_pct: None,
+            min_break_minutes: None,
+            max_distance_km: None,
+        })
+        .collect();
+    let orders = (0..ORDERS)
+        .map(|i| {
+            let start_time = day_start() + ChronoDuration::minutes(rng.gen_range(0..12 * 60));
+            let minutes = rng.gen_range(30..90);
+            let end_time = start_time + ChronoDuration::minutes(minutes);
+            let flexible = rng.gen_bool(1.0 / 3.0);
+            Order {
+                id: format!("o{}", i),
+                start_time,
+                end_time,
+                priority: Some(rng.gen_range(1..5) as f64),
+                tags: None,
+                weight: rng.gen_range(10.0..150.0),
+                volume: None,
+                duration_minutes: flexible.then_some(minutes),
+                earliest_start: None,
+                latest_end: flexible.then(|| end_time + ChronoDuration::minutes(120)),
+                must_follow: None,
+                splittable: false,
+                required: None,
+                lat: None,
+                lon: None,
+                group_id: None,
+                same_vehicle_group: None,
+                service_minutes: None,
+                allowed_driver_ids: None,
+                allowed_vehicle_ids: None,
+                required_drivers: None,
+            }
+        })
+        .collect();
+    (drivers, vehicles, orders)
+}
+
+fn main() {
+    let mut seeds: Vec<u64> = std::env::args()
+        .skip(1)
+        .map(|arg| arg.parse().expect("seeds must be integers"))
+        .collect();
+    if seeds.is_empty() {
+        seeds = vec![3, 4];
+    }
+
+    for seed in seeds {
+        let (drivers, vehicles, orders) = instance(seed);
+        for two_phase in [false, true] {
+            let config = SolverConfig {
+                seed: Some(seed),
+                max_duration_ms: 30_000.0,
+                two_phase,
+                ..SolverConfig::default()
+            };
+            let response = solve(&drivers, &vehicles, &orders, &config);
+            println!(
+                "seed {} {:<9} assigned {:>4} score {:>8.1} init {:>6.1} s wall {:>6.1} s \
+                 generations {}",
+                seed,
+                if two_phase { "two-phase" } else { "single" },
+                response.assignments.len(),
+                response.score,
+                response.metrics.initialization_ms / 1000.0,
+                response.metrics.wall_time_ms / 1000.0,
+                response.metrics.generations,
+            );
+        }
+    }
+}
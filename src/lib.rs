@@ -1,23 +1,428 @@
 // src/lib.rs
-mod solver;
-use chrono::Duration as ChronoDuration;
-use console_error_panic_hook;
-use js_sys::{Date, Function};
-use rand::prelude::*;
+pub mod solver;
+use js_sys::Function;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use serde_wasm_bindgen::{from_value, to_value};
 use solver::{
-    crossover, initialize_random_state, mutate, select_parent, Driver, Order, SchedulingResponse,
-    SolverState, Vehicle,
+    check_pinned, evaluate_schedule as evaluate_assignments, max_possible_score as score_ceiling,
+    merge_schedules as merge_responses, order_feasibility as probe_order_feasibility,
+    reassignment_options as list_reassignment_options, reject_unfinished,
+    resolve_after_removal as resolve_without_order, solve_batch as solve_problems, solve_best_of,
+    solve_with_observer, validate_assignment_structure as check_assignment_structure,
+    validate_inputs, Assignment, Driver, Order, PlannedAssignment, Problem, ProgressUpdate,
+    SchedulingResponse, SolveObserver, SolverConfig, SolverError, Vehicle,
 };
-use std::cmp::Ordering;
-use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+// TypeScript definitions for the payloads, emitted into the wasm-pack .d.ts.
+// Timestamps cross the boundary as milliseconds since the epoch, never as Date objects. Inputs
+// may also give them as RFC 3339 strings.
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &'static str = r#"
+export type Timestamp = number;
+// Accepted wherever a timestamp is read, responses always use Timestamp
+export type TimestampInput = Timestamp | string;
+
+export interface Break {
+    from: TimestampInput;
+    to: TimestampInput;
+}
+
+export interface DailyBreak {
+    start_minute_of_day: number;
+    duration_minutes: number;
+}
+
+export interface Driver {
+    id: string;
+    breaks?: Break[] | null;
+    recurring_breaks?: DailyBreak[] | null;
+    shifts?: [TimestampInput, TimestampInput][] | null;
+    preference?: string | null;
+    skills?: string[] | null;
+    max_orders?: number | null;
+    allowed_vehicle_ids?: string[] | null;
+    max_work_minutes?: number | null;
+}
+
+export interface Vehicle {
+    id: string;
+    tags?: string[] | null;
+    max_weight: number;
+    max_volume?: number | null;
+    volume_unlimited?: boolean | null;
+    unavailable?: Break[] | null;
+    min_fill_pct?: number | null;
+    min_break_minutes?: number | null;
+    max_distance_km?: number | null;
+}
+
+export interface Order {
+    id: string;
+    start_time: TimestampInput;
+    end_time: TimestampInput;
+    priority?: number | null;
+    tags?: string[] | null;
+    weight: number;
+    volume?: number | null;
+    duration_minutes?: number | null;
+    earliest_start?: TimestampInput | null;
+    latest_end?: TimestampInput | null;
+    must_follow?: string[] | null;
+    splittable?: boolean;
+    required?: boolean | null;
+    lat?: number | null;
+    lon?: number | null;
+    group_id?: string | null;
+    same_vehicle_group?: string | null;
+    service_minutes?: number | null;
+    allowed_driver_ids?: string[] | null;
+    allowed_vehicle_ids?: string[] | null;
+    required_drivers?: number | null;
+}
+
+export interface Assignment {
+    order_id: string;
+    driver_id: string;
+    vehicle_id: string;
+    start_time: Timestamp;
+    end_time: Timestamp;
+    weight?: number;
+}
+
+// An entry of a plan given to evaluate_schedule, a pin or a historical assignment. A missing time is
+// derived from the other and the order's duration, with neither the order starts at the beginning of
+// its window.
+export interface PlannedAssignment {
+    order_id: string;
+    driver_id: string;
+    vehicle_id: string;
+    start_time?: TimestampInput | null;
+    end_time?: TimestampInput | null;
+    weight?: number;
+}
+
+export interface AssignmentScore {
+    order_id: string;
+    base_priority: number;
+    preference_bonus: number;
+    earliness_bonus: number;
+    continuity_bonus: number;
+    unassigned_penalty: number;
+    total: number;
+}
+
+export interface VehicleUtilization {
+    vehicle_id: string;
+    total_weight: number;
+    total_volume: number;
+    weight_pct: number;
+    order_count: number;
+}
+
+export type Rejection =
+    | "DriverBreak"
+    | "OutsideShift"
+    | "MaxOrders"
+    | "VehicleUnavailable"
+    | "MissingSkill"
+    | "Precedence"
+    | "Overlap"
+    | "InsufficientBreak"
+    | "Weight"
+    | "Volume"
+    | "Tags"
+    | "VehicleNotAllowed"
+    | "WindowTooShort"
+    | "ContinuousWork"
+    | "FleetLimit"
+    | "VehicleHandoff"
+    | "DriverNotListed"
+    | "VehicleNotListed"
+    | "Range";
+
+export interface RejectionCount {
+    reason: Rejection;
+    count: number;
+}
+
+export type UnassignedCause = "Infeasible" | "CrowdedOut";
+
+export interface UnassignedReason {
+    order_id: string;
+    reason: UnassignedCause;
+}
+
+export interface SchedulingResponse {
+    assignments: Assignment[];
+    score: number;
+    unassigned_order_ids: string[];
+    unassigned_reasons: UnassignedReason[];
+    required_unassigned: string[];
+    relaxed_assignments: string[];
+    vehicle_utilization: VehicleUtilization[];
+    breakdown?: AssignmentScore[];
+    schedule_adjustment?: number;
+    run_scores?: number[];
+    pareto_front?: ParetoSolution[];
+    population?: SchedulingResponse[];
+    cancelled: boolean;
+    rejection_stats?: RejectionCount[];
+    seed_used: number;
+    warnings: ValidationWarning[];
+    metrics: SolverMetrics;
+}
+
+export interface ParetoObjectives {
+    priority: number;
+    balance: number;
+}
+
+export interface ParetoSolution {
+    objectives: ParetoObjectives;
+    response: SchedulingResponse;
+}
+
+export interface SolverMetrics {
+    generations: number;
+    restarts: number;
+    wall_time_ms: number;
+    initialization_ms: number;
+    evolution_ms: number;
+    local_search_ms: number;
+    feasibility_checks: number;
+}
+
+export interface ResolveResponse {
+    response: SchedulingResponse;
+    changed_order_ids: string[];
+}
+
+export interface ProgressUpdate {
+    generation: number;
+    best_score: number;
+    avg_score: number;
+    assigned_count: number;
+    progress_fraction: number;
+    message: string;
+    snapshot?: SchedulingResponse;
+}
+
+export type SelectionStrategy =
+    | { type: "Tournament"; size: number }
+    | { type: "RouletteWheel" };
+
+export type CrossoverStrategy = "OnePoint" | "TwoPoint" | "Uniform";
+
+export type InitOrder = "Shuffle" | "PriorityDesc" | "Hybrid";
+
+export type PreferenceMode = "Soft" | "HardWhenAvailable";
+
+export type PriorityTransform = "Raw" | "Log" | "Rank";
+
+export interface Relaxation {
+    break_slack_minutes?: number;
+    allow_capacity_overflow_pct?: number;
+}
+
+export interface ObjectiveWeights {
+    priority?: number;
+    balance?: number;
+}
+
+export interface SolverConfig {
+    generations?: number;
+    population_size?: number;
+    mutation_rate?: number;
+    max_duration_ms?: number;
+    max_generations_without_improvement?: number;
+    stall_generations_fraction?: number | null;
+    min_improvement_delta?: number;
+    max_restarts?: number;
+    restart_fraction?: number;
+    mandatory_break_minutes?: number;
+    touching_allowed?: boolean;
+    seed?: number | null;
+    selection?: SelectionStrategy;
+    tournament_win_prob?: number;
+    preference_bonus?: number;
+    explain?: boolean;
+    unassigned_penalty?: number;
+    objective_weights?: ObjectiveWeights;
+    crossover_repair?: boolean;
+    mutation_ramp_interval?: number;
+    mutation_ramp_factor?: number;
+    max_mutation_rate?: number;
+    greedy_init_fraction?: number;
+    init_order?: InitOrder;
+    dedupe?: boolean;
+    two_phase?: boolean;
+    two_phase_bucket_minutes?: number;
+    overtime_penalty?: number;
+    ignore_time_budget?: boolean;
+    local_search_iterations?: number;
+    local_search?: boolean;
+    local_search_budget_ms?: number;
+    compact?: boolean;
+    average_speed_kmh?: number;
+    elite_fraction?: number;
+    group_bonus?: number;
+    crossover?: CrossoverStrategy;
+    collect_rejection_stats?: boolean;
+    relaxation?: Relaxation;
+    max_continuous_minutes?: number;
+    long_break_minutes?: number;
+    priority_transform?: PriorityTransform;
+    priority_urgency_factor?: number;
+    reference_time?: TimestampInput | null;
+    snapshot_interval?: number;
+    islands?: number;
+    migration_interval?: number;
+    min_fill_penalty?: number;
+    earliness_bonus?: number;
+    max_vehicles_used?: number | null;
+    preferred_gap_minutes?: number;
+    gap_bonus?: number;
+    gap_bonus_cap?: number | null;
+    driver_count_penalty?: number;
+    max_assignment_imbalance?: number | null;
+    imbalance_penalty?: number;
+    vehicle_handoff_minutes?: number;
+    preference_mode?: PreferenceMode;
+    warm_start?: SchedulingResponse | null;
+    pinned?: PlannedAssignment[];
+    historical_assignments?: PlannedAssignment[];
+    continuity_bonus?: number;
+    return_pareto?: boolean;
+    return_population?: boolean;
+    pareto_front_size?: number;
+    weight_unit?: string | null;
+    volume_unit?: string | null;
+    strict_required?: boolean;
+}
+
+export type ValidationError =
+    | { kind: "EmptyDrivers" }
+    | { kind: "EmptyVehicles" }
+    | { kind: "EmptyOrders" }
+    | { kind: "DuplicateOrderId"; order_id: string }
+    | { kind: "InvalidTimeRange"; order_id: string }
+    | { kind: "InvalidDuration"; order_id: string }
+    | { kind: "InvalidWindow"; order_id: string }
+    | { kind: "NegativeServiceTime"; order_id: string }
+    | { kind: "NegativeWeight"; order_id: string }
+    | { kind: "UnknownTag"; order_id: string; tag: string }
+    | { kind: "InvalidBreak"; driver_id: string }
+    | { kind: "InvalidDailyBreak"; driver_id: string }
+    | { kind: "InvalidRequiredDrivers"; order_id: string }
+    | { kind: "InvalidMaxDistance"; vehicle_id: string };
+
+export type ValidationWarning =
+    | {
+          kind: "WeightExceedsFleet";
+          order_id: string;
+          weight: number;
+          capacity: number;
+          unit: string | null;
+      }
+    | {
+          kind: "VolumeExceedsFleet";
+          order_id: string;
+          volume: number;
+          capacity: number;
+          unit: string | null;
+      }
+    | { kind: "DuplicateOrder"; order_id: string; duplicate_of: string };
+
+// Thrown by every exported function. code is stable and meant for branching, message is
+// English text for logs.
+export type SolverError =
+    | { code: "DeserializationFailed"; message: string; details: { input: string } }
+    | { code: "InvalidConfig"; message: string; details: { problem_index: number | null } }
+    | {
+          code: "ValidationFailed";
+          message: string;
+          details: { problem_index: number | null; errors: ValidationError[] };
+      }
+    | { code: "InfeasiblePin"; message: string; details: { problem_index: number | null } }
+    | { code: "InvalidArgument"; message: string; details: null }
+    | { code: "SerializationFailed"; message: string; details: null }
+    | { code: "InfeasibleRequired"; message: string; details: { order_ids: string[] } }
+    | { code: "Cancelled"; message: string; details: { partial: SchedulingResponse } };
+
+export interface Problem {
+    drivers: Driver[];
+    vehicles: Vehicle[];
+    orders: Order[];
+    config?: SolverConfig | null;
+}
+
+export interface ResourcePair {
+    driver_id: string;
+    vehicle_id: string;
+}
+
+export interface OrderFeasibility {
+    order_id: string;
+    feasible_pairs: ResourcePair[];
+    infeasible: boolean;
+}
+
+export interface MergeResult {
+    response: SchedulingResponse;
+    conflicting_order_ids: string[];
+}
+
+export interface ScheduleEvaluation {
+    score: number;
+    violations: string[];
+}
+"#;
+
 // Define structs with serde and wasm_bindgen
 #[wasm_bindgen]
 extern "C" {
     // Import JavaScript Date object
     #[wasm_bindgen(typescript_type = "Date")]
     pub type JsDate;
+
+    // Typed views of the payloads declared above, so the exported signatures are not `any`
+    #[wasm_bindgen(typescript_type = "Driver[]")]
+    pub type JsDrivers;
+    #[wasm_bindgen(typescript_type = "Vehicle[]")]
+    pub type JsVehicles;
+    #[wasm_bindgen(typescript_type = "Order[]")]
+    pub type JsOrders;
+    #[wasm_bindgen(typescript_type = "Assignment[]")]
+    pub type JsAssignments;
+    #[wasm_bindgen(typescript_type = "PlannedAssignment[]")]
+    pub type JsPlannedAssignments;
+    #[wasm_bindgen(typescript_type = "SolverConfig | undefined | null")]
+    pub type JsSolverConfig;
+    #[wasm_bindgen(typescript_type = "(progress: ProgressUpdate) => void")]
+    pub type JsProgressCallback;
+    #[wasm_bindgen(typescript_type = "() => boolean")]
+    pub type JsCancelCallback;
+    #[wasm_bindgen(typescript_type = "SchedulingResponse")]
+    pub type JsSchedulingResponse;
+    #[wasm_bindgen(typescript_type = "ScheduleEvaluation")]
+    pub type JsScheduleEvaluation;
+    #[wasm_bindgen(typescript_type = "ResolveResponse")]
+    pub type JsResolveResponse;
+    #[wasm_bindgen(typescript_type = "MergeResult")]
+    pub type JsMergeResult;
+    #[wasm_bindgen(typescript_type = "string[]")]
+    pub type JsStrings;
+    #[wasm_bindgen(typescript_type = "Problem[]")]
+    pub type JsProblems;
+    #[wasm_bindgen(typescript_type = "SchedulingResponse[]")]
+    pub type JsSchedulingResponses;
+    #[wasm_bindgen(typescript_type = "OrderFeasibility[]")]
+    pub type JsOrderFeasibility;
+    #[wasm_bindgen(typescript_type = "ResourcePair[]")]
+    pub type JsResourcePairs;
 }
 
 #[wasm_bindgen(start)]
@@ -26,139 +431,396 @@ pub fn main_js() {
     console_error_panic_hook::set_once();
 }
 
-// Genetic Algorithm implementation with progress updates and termination criterion
+// Genetic Algorithm implementation with progress updates and termination criterion.
+// js_should_cancel is optional and polled once per generation, a truthy return stops the solve
+// and throws Cancelled with the best schedule found so far. Under config.strict_required a plan
+// leaving a required order out throws InfeasibleRequired instead of being returned.
 #[wasm_bindgen]
 pub fn get_schedule_recommendation(
-    js_drivers: JsValue,
-    js_vehicles: JsValue,
-    js_orders: JsValue,
-    js_update_function: &Function,
-) -> Result<JsValue, JsValue> {
-    // Deserialize input data from JsValue
-    let drivers: Vec<Driver> = from_value(js_drivers)
-        .map_err(|e| JsValue::from_str(&format!("Failed to deserialize drivers: {}", e)))?;
-    let vehicles: Vec<Vehicle> = from_value(js_vehicles)
-        .map_err(|e| JsValue::from_str(&format!("Failed to deserialize vehicles: {}", e)))?;
-    let orders: Vec<Order> = from_value(js_orders)
-        .map_err(|e| JsValue::from_str(&format!("Failed to deserialize orders: {}", e)))?;
-
-    // Initialize parameters
-    let generations = 1000; // Maximum number of generations
-    let population_size = 50;
-    let mutation_rate = 0.1;
-    let mandatory_break = ChronoDuration::minutes(30);
-    let start_time = Date::now(); // Milliseconds since epoch as f64
-    let max_duration = 10_000.0; // 10 seconds in milliseconds
-
-    // Map for order priorities
-    let order_priority_map: HashMap<String, u32> = orders
-        .iter()
-        .map(|order| (order.id.clone(), order.priority.unwrap_or(1)))
-        .collect();
-
-    // Initialize population
-    let mut population: Vec<SolverState> = Vec::new();
-    for _ in 0..population_size {
-        let state = initialize_random_state(
-            &drivers,
-            &vehicles,
-            &orders,
-            &order_priority_map,
-            mandatory_break,
-        );
-        population.push(state);
-    }
+    js_drivers: JsDrivers,
+    js_vehicles: JsVehicles,
+    js_orders: JsOrders,
+    js_update_function: &JsProgressCallback,
+    js_should_cancel: Option<JsCancelCallback>,
+) -> Result<JsSchedulingResponse, JsValue> {
+    run_genetic_algorithm(
+        js_drivers,
+        js_vehicles,
+        js_orders,
+        SolverConfig::default(),
+        js_update_function,
+        js_should_cancel,
+    )
+}
 
-    let mut rng = rand::thread_rng();
-    let mut generation = 0;
-
-    // Initialize variables for termination criterion
-    let mut best_score = population[0].score;
-    let mut generations_without_improvement = 0;
-    let max_generations_without_improvement = 50; // Adjust as needed
-
-    // Use generations variable to control the loop
-    while (Date::now() - start_time) < max_duration && generation < generations {
-        // Evaluate fitness
-        population.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
-        let current_best_score = population[0].score;
-
-        // Send progress update to JavaScript
-        let progress = format!(
-            "Generation {}: Best Score {:.2}",
-            generation + 1,
-            current_best_score
-        );
-        let _ = js_update_function.call1(&JsValue::NULL, &JsValue::from(progress));
-
-        // Check for improvement
-        if current_best_score > best_score {
-            best_score = current_best_score;
-            generations_without_improvement = 0;
-        } else {
-            generations_without_improvement += 1;
-        }
+// Same as get_schedule_recommendation, but with the GA parameters supplied by the caller
+#[wasm_bindgen]
+pub fn get_schedule_recommendation_with_config(
+    js_drivers: JsDrivers,
+    js_vehicles: JsVehicles,
+    js_orders: JsOrders,
+    js_config: JsSolverConfig,
+    js_update_function: &JsProgressCallback,
+    js_should_cancel: Option<JsCancelCallback>,
+) -> Result<JsSchedulingResponse, JsValue> {
+    let config: Option<SolverConfig> = deserialize(js_config, "config")?;
+    run_genetic_algorithm(
+        js_drivers,
+        js_vehicles,
+        js_orders,
+        config.unwrap_or_default(),
+        js_update_function,
+        js_should_cancel,
+    )
+}
 
-        // Terminate if no improvement over threshold
-        if generations_without_improvement >= max_generations_without_improvement {
-            let termination_message = format!(
-                "No improvement over {} generations, terminating.",
-                max_generations_without_improvement
-            );
-            let _ = js_update_function.call1(&JsValue::NULL, &JsValue::from(termination_message));
-            break;
+// Runs the GA `runs` times with different seeds and returns the best response, with every
+// run's score in run_scores. The inputs are parsed once. max_duration_ms applies per run, so a
+// call can take up to runs times the time budget. Cancellation and config.strict_required throw
+// as in get_schedule_recommendation.
+#[wasm_bindgen]
+pub fn get_schedule_recommendation_best_of(
+    runs: u32,
+    js_drivers: JsDrivers,
+    js_vehicles: JsVehicles,
+    js_orders: JsOrders,
+    js_config: JsSolverConfig,
+    js_update_function: &JsProgressCallback,
+    js_should_cancel: Option<JsCancelCallback>,
+) -> Result<JsSchedulingResponse, JsValue> {
+    if runs < 1 {
+        return Err(SolverError::InvalidArgument {
+            reason: "runs must be at least 1".to_string(),
+        }
+        .into());
+    }
+    let config: SolverConfig =
+        deserialize::<Option<SolverConfig>>(js_config, "config")?.unwrap_or_default();
+    validate_config(&config)?;
+    let (drivers, vehicles, orders) = parse_inputs(js_drivers, js_vehicles, js_orders)?;
+    check_pinned(&drivers, &vehicles, &orders, &config).map_err(|reason| {
+        SolverError::InfeasiblePin {
+            problem_index: None,
+            reason,
         }
+    })?;
 
-        // Selection (elitism)
-        let elite_count = (population_size as f64 * 0.1).ceil() as usize;
-        let mut new_population: Vec<SolverState> = population[..elite_count].to_vec();
-
-        // Crossover
-        while new_population.len() < population_size {
-            let parent1 = select_parent(&population);
-            let parent2 = select_parent(&population);
-            let mut child = crossover(
-                parent1,
-                parent2,
-                &drivers,
-                &vehicles,
-                &orders,
-                &order_priority_map,
-                mandatory_break,
-                &mut rng,
-            );
-
-            // Mutation
-            if rng.gen::<f64>() < mutation_rate {
-                mutate(
-                    &mut child,
-                    &drivers,
-                    &vehicles,
-                    &orders,
-                    &order_priority_map,
-                    mandatory_break,
-                    &mut rng,
-                );
-            }
+    let response = solve_best_of(
+        runs,
+        &drivers,
+        &vehicles,
+        &orders,
+        &config,
+        &mut JsProgressObserver::new(js_update_function, js_should_cancel.as_ref()),
+    );
+    let response = reject_unfinished(response, &config)?;
+    Ok(serialize(&response)?.unchecked_into())
+}
 
-            // Recalculate the child's score after mutation
-            child.score = child.calculate_score(&order_priority_map, &drivers);
-            new_population.push(child);
+// Solves many independent problems in one call to save the per-call boundary overhead. Every
+// problem is validated up front, invalid inputs fail the whole batch with the problem's index.
+// No progress is reported, each problem still runs to its own config's budget.
+#[wasm_bindgen]
+pub fn solve_batch(js_problems: JsProblems) -> Result<JsSchedulingResponses, JsValue> {
+    let problems: Vec<Problem> = deserialize(js_problems, "problems")?;
+    for (problem_index, problem) in problems.iter().enumerate() {
+        let problem_index = Some(problem_index);
+        if let Some(config) = &problem.config {
+            config
+                .validate()
+                .map_err(|reason| SolverError::InvalidConfig {
+                    problem_index,
+                    reason,
+                })?;
         }
+        let errors = validate_inputs(&problem.drivers, &problem.vehicles, &problem.orders);
+        if !errors.is_empty() {
+            return Err(SolverError::ValidationFailed {
+                problem_index,
+                errors,
+            }
+            .into());
+        }
+        if let Some(config) = &problem.config {
+            check_pinned(&problem.drivers, &problem.vehicles, &problem.orders, config).map_err(
+                |reason| SolverError::InfeasiblePin {
+                    problem_index,
+                    reason,
+                },
+            )?;
+        }
+    }
+
+    let responses = solve_problems(&problems);
+    Ok(serialize(&responses)?.unchecked_into())
+}
+
+// Ceiling for the score of any schedule of these orders, ignoring feasibility, so a UI can show
+// how close a solution got. js_config is optional, its weights and bonuses shape the bound.
+#[wasm_bindgen]
+pub fn max_possible_score(
+    js_orders: JsOrders,
+    js_drivers: JsDrivers,
+    js_config: JsSolverConfig,
+) -> Result<f64, JsValue> {
+    let orders: Vec<Order> = deserialize(js_orders, "orders")?;
+    let drivers: Vec<Driver> = deserialize(js_drivers, "drivers")?;
+    let config: SolverConfig =
+        deserialize::<Option<SolverConfig>>(js_config, "config")?.unwrap_or_default();
+    validate_config(&config)?;
+    Ok(score_ceiling(&orders, &drivers, &config))
+}
+
+// Cheap id-level checks of a plan, e.g. a warm start, before running evaluate_schedule on it.
+// Returns one message per problem found, empty when the structure is sound.
+#[wasm_bindgen]
+pub fn validate_assignment_structure(
+    js_assignments: JsAssignments,
+    js_drivers: JsDrivers,
+    js_vehicles: JsVehicles,
+    js_orders: JsOrders,
+) -> Result<JsStrings, JsValue> {
+    let assignments: Vec<Assignment> = deserialize(js_assignments, "assignments")?;
+    let drivers: Vec<Driver> = deserialize(js_drivers, "drivers")?;
+    let vehicles: Vec<Vehicle> = deserialize(js_vehicles, "vehicles")?;
+    let orders: Vec<Order> = deserialize(js_orders, "orders")?;
+    let messages = check_assignment_structure(&assignments, &drivers, &vehicles, &orders);
+    Ok(serialize(&messages)?.unchecked_into())
+}
+
+// Scores a given plan and lists its constraint violations without running the GA.
+// js_config is optional and only affects rules such as the mandatory break.
+#[wasm_bindgen]
+pub fn evaluate_schedule(
+    js_drivers: JsDrivers,
+    js_vehicles: JsVehicles,
+    js_orders: JsOrders,
+    js_assignments: JsPlannedAssignments,
+    js_config: JsSolverConfig,
+) -> Result<JsScheduleEvaluation, JsValue> {
+    let drivers: Vec<Driver> = deserialize(js_drivers, "drivers")?;
+    let vehicles: Vec<Vehicle> = deserialize(js_vehicles, "vehicles")?;
+    let orders: Vec<Order> = deserialize(js_orders, "orders")?;
+    let assignments: Vec<PlannedAssignment> = deserialize(js_assignments, "assignments")?;
+    let config: SolverConfig =
+        deserialize::<Option<SolverConfig>>(js_config, "config")?.unwrap_or_default();
+    validate_config(&config)?;
+
+    let evaluation = evaluate_assignments(&drivers, &vehicles, &orders, &assignments, &config);
+    Ok(serialize(&evaluation)?.unchecked_into())
+}
+
+// Lists the feasible (driver, vehicle) pairs of every order considered on its own, without
+// running the GA. Orders nothing can take are flagged as infeasible. js_config is optional.
+#[wasm_bindgen]
+pub fn order_feasibility(
+    js_drivers: JsDrivers,
+    js_vehicles: JsVehicles,
+    js_orders: JsOrders,
+    js_config: JsSolverConfig,
+) -> Result<JsOrderFeasibility, JsValue> {
+    let config: SolverConfig =
+        deserialize::<Option<SolverConfig>>(js_config, "config")?.unwrap_or_default();
+    validate_config(&config)?;
+    let (drivers, vehicles, orders) = parse_inputs(js_drivers, js_vehicles, js_orders)?;
+    let feasibility = probe_order_feasibility(&drivers, &vehicles, &orders, &config);
+    Ok(serialize(&feasibility)?.unchecked_into())
+}
+
+// Lists the other (driver, vehicle) pairs that could take one order of a plan while the rest of
+// the plan stays put, without running the GA. js_config is optional.
+#[wasm_bindgen]
+pub fn reassignment_options(
+    order_id: String,
+    js_current_assignments: JsAssignments,
+    js_drivers: JsDrivers,
+    js_vehicles: JsVehicles,
+    js_orders: JsOrders,
+    js_config: JsSolverConfig,
+) -> Result<JsResourcePairs, JsValue> {
+    let current_assignments: Vec<Assignment> =
+        deserialize(js_current_assignments, "current assignments")?;
+    let config: SolverConfig =
+        deserialize::<Option<SolverConfig>>(js_config, "config")?.unwrap_or_default();
+    validate_config(&config)?;
+    let (drivers, vehicles, orders) = parse_inputs(js_drivers, js_vehicles, js_orders)?;
+
+    let options = list_reassignment_options(
+        &order_id,
+        &current_assignments,
+        &drivers,
+        &vehicles,
+        &orders,
+        &config,
+    );
+    Ok(serialize(&options)?.unchecked_into())
+}
+
+// Updates an earlier response after one order was cancelled, using a short local search
+// instead of a full solve. js_config is optional.
+#[wasm_bindgen]
+pub fn resolve_after_removal(
+    js_previous_response: JsSchedulingResponse,
+    removed_order_id: String,
+    js_drivers: JsDrivers,
+    js_vehicles: JsVehicles,
+    js_orders: JsOrders,
+    js_config: JsSolverConfig,
+) -> Result<JsResolveResponse, JsValue> {
+    let previous_response: SchedulingResponse =
+        deserialize(js_previous_response, "previous response")?;
+    let config: SolverConfig =
+        deserialize::<Option<SolverConfig>>(js_config, "config")?.unwrap_or_default();
+    validate_config(&config)?;
+    let (drivers, vehicles, orders) = parse_inputs(js_drivers, js_vehicles, js_orders)?;
 
-        population = new_population;
-        generation += 1;
+    let resolved = resolve_without_order(
+        &previous_response,
+        &removed_order_id,
+        &drivers,
+        &vehicles,
+        &orders,
+        &config,
+    );
+    Ok(serialize(&resolved)?.unchecked_into())
+}
+
+// Combines two responses, e.g. from solving separate regions, into one plan. Assignments that
+// cannot coexist with those already placed are dropped and listed in conflicting_order_ids.
+#[wasm_bindgen]
+pub fn merge_schedules(
+    js_first: JsSchedulingResponse,
+    js_second: JsSchedulingResponse,
+    js_drivers: JsDrivers,
+    js_vehicles: JsVehicles,
+    js_orders: JsOrders,
+    js_config: JsSolverConfig,
+) -> Result<JsMergeResult, JsValue> {
+    let first: SchedulingResponse = deserialize(js_first, "first response")?;
+    let second: SchedulingResponse = deserialize(js_second, "second response")?;
+    let config: SolverConfig =
+        deserialize::<Option<SolverConfig>>(js_config, "config")?.unwrap_or_default();
+    validate_config(&config)?;
+    let (drivers, vehicles, orders) = parse_inputs(js_drivers, js_vehicles, js_orders)?;
+
+    let merged = merge_responses(&first, &second, &drivers, &vehicles, &orders, &config);
+    Ok(serialize(&merged)?.unchecked_into())
+}
+
+impl From<SolverError> for JsValue {
+    fn from(error: SolverError) -> JsValue {
+        to_value(&error).unwrap_or_else(|_| JsValue::from_str(&error.to_string()))
+    }
+}
+
+fn deserialize<T: DeserializeOwned>(
+    value: impl Into<JsValue>,
+    name: &str,
+) -> Result<T, SolverError> {
+    from_value(value.into()).map_err(|e| SolverError::DeserializationFailed {
+        input: name.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+fn serialize<T: Serialize>(value: &T) -> Result<JsValue, SolverError> {
+    to_value(value).map_err(|e| SolverError::SerializationFailed {
+        reason: e.to_string(),
+    })
+}
+
+fn validate_config(config: &SolverConfig) -> Result<(), SolverError> {
+    config
+        .validate()
+        .map_err(|reason| SolverError::InvalidConfig {
+            problem_index: None,
+            reason,
+        })
+}
+
+type SolverInputs = (Vec<Driver>, Vec<Vehicle>, Vec<Order>);
+
+// Deserializes the solver inputs and rejects malformed payloads before solving
+fn parse_inputs(
+    js_drivers: JsDrivers,
+    js_vehicles: JsVehicles,
+    js_orders: JsOrders,
+) -> Result<SolverInputs, SolverError> {
+    let drivers: Vec<Driver> = deserialize(js_drivers, "drivers")?;
+    let vehicles: Vec<Vehicle> = deserialize(js_vehicles, "vehicles")?;
+    let orders: Vec<Order> = deserialize(js_orders, "orders")?;
+
+    let errors = validate_inputs(&drivers, &vehicles, &orders);
+    if !errors.is_empty() {
+        return Err(SolverError::ValidationFailed {
+            problem_index: None,
+            errors,
+        });
     }
+    Ok((drivers, vehicles, orders))
+}
 
-    // Return the best solution
-    population.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
-    let best_assignments = &population[0].assignments;
+fn run_genetic_algorithm(
+    js_drivers: JsDrivers,
+    js_vehicles: JsVehicles,
+    js_orders: JsOrders,
+    config: SolverConfig,
+    js_update_function: &JsProgressCallback,
+    js_should_cancel: Option<JsCancelCallback>,
+) -> Result<JsSchedulingResponse, JsValue> {
+    validate_config(&config)?;
+
+    let (drivers, vehicles, orders) = parse_inputs(js_drivers, js_vehicles, js_orders)?;
+    check_pinned(&drivers, &vehicles, &orders, &config).map_err(|reason| {
+        SolverError::InfeasiblePin {
+            problem_index: None,
+            reason,
+        }
+    })?;
 
-    // Serialize the result to JsValue
-    let response = SchedulingResponse {
-        assignments: best_assignments.clone(),
-    };
-    let js_response = to_value(&response)?;
+    let response = solve_with_observer(
+        &drivers,
+        &vehicles,
+        &orders,
+        &config,
+        &mut JsProgressObserver::new(js_update_function, js_should_cancel.as_ref()),
+    );
+    let response = reject_unfinished(response, &config)?;
+    let js_response = serialize(&response)?.unchecked_into();
 
     Ok(js_response)
 }
+
+// Forwards solver progress to the JavaScript callback and polls the optional cancel callback
+struct JsProgressObserver<'a> {
+    js_update_function: &'a Function,
+    js_should_cancel: Option<&'a Function>,
+}
+
+impl<'a> JsProgressObserver<'a> {
+    fn new(
+        js_update_function: &'a JsProgressCallback,
+        js_should_cancel: Option<&'a JsCancelCallback>,
+    ) -> Self {
+        JsProgressObserver {
+            js_update_function: js_update_function.unchecked_ref(),
+            js_should_cancel: js_should_cancel.map(|f| f.unchecked_ref()),
+        }
+    }
+}
+
+impl SolveObserver for JsProgressObserver<'_> {
+    fn on_progress(&mut self, progress: &ProgressUpdate) {
+        if let Ok(js_progress) = to_value(progress) {
+            let _ = self.js_update_function.call1(&JsValue::NULL, &js_progress);
+        }
+    }
+
+    fn should_cancel(&mut self) -> bool {
+        self.js_should_cancel.is_some_and(|f| {
+            f.call0(&JsValue::NULL)
+                .map(|result| result.is_truthy())
+                .unwrap_or(false)
+        })
+    }
+}
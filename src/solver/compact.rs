@@ -0,0 +1,188 @@
+use super::{
+    assigned_piece, first_infeasible_assignment, Driver, Order, ProblemIndex, SolverConfig,
+    SolverState, Spacing, Vehicle,
+};
+use chrono::NaiveDateTime;
+use std::collections::HashMap;
+
+// Moves orders with a flexible window to the earliest start their driver and vehicle allow,
+// going through the plan in start order so each order can use the room freed before it. Drivers
+// and vehicles never change and every move passes the same break, overlap and handoff checks
+// as a new placement, so a plan without flexible windows is left as it is.
+pub fn compact_schedule(
+    state: &mut SolverState,
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    index: &ProblemIndex,
+    priority_map: &HashMap<String, f64>,
+    config: &SolverConfig,
+) {
+    if !config.compact {
+        return;
+    }
+    let spacing = Spacing::from_config(config);
+    let mut keys: Vec<(NaiveDateTime, String, String)> = state
+        .assignments
+        .iter()
+        .map(|a| (a.start_time, a.order_id.clone(), a.driver_id.clone()))
+        .collect();
+    keys.sort();
+
+    for (start_time, order_id, driver_id) in keys {
+        let Some(i) = state.assignments.iter().position(|a| {
+            a.start_time == start_time && a.order_id == order_id && a.driver_id == driver_id
+        }) else {
+            continue;
+        };
+        let order = index.orders[order_id.as_str()];
+        let assignment = &state.assignments[i];
+        // Relaxed and pinned assignments stay where they are, a crew only moves as a whole and
+        // an order without slack has nowhere to go
+        if assignment.relaxed
+            || assignment.pinned
+            || order.crew_size() > 1
+            || order.earliness(start_time).is_none()
+        {
+            continue;
+        }
+
+        // Taken off first so the order does not block its own earlier slots, an order that
+        // cannot move goes back to the slot it had
+        let removed = state.unassign(i);
+        let piece = assigned_piece(order, &removed);
+        let driver = index.drivers[removed.driver_id.as_str()];
+        let vehicle = index.vehicles[removed.vehicle_id.as_str()];
+        let slot = index
+            .can_assign(
+                &piece,
+                driver,
+                vehicle,
+                &state.driver_schedules[&driver.id],
+                &state.vehicle_schedules[&vehicle.id],
+                spacing,
+            )
+            .filter(|slot| {
+                slot.0 < start_time && follows_predecessors(state, order, driver, slot.0)
+            })
+            .unwrap_or((removed.start_time, removed.end_time));
+        state.assign_order(&piece, driver, vehicle, slot);
+    }
+
+    state.score = state.calculate_score(priority_map, drivers, vehicles, orders, index, config);
+    debug_assert_eq!(
+        first_infeasible_assignment(state, drivers, vehicles, index, config),
+        None
+    );
+}
+
+// Whether every must_follow predecessor of the order is finished by the driver before start
+fn follows_predecessors(
+    state: &SolverState,
+    order: &Order,
+    driver: &Driver,
+    start: NaiveDateTime,
+) -> bool {
+    order.must_follow.iter().flatten().all(|pred| {
+        state
+            .assignments
+            .iter()
+            .any(|p| &p.order_id == pred && p.driver_id == driver.id && p.end_time <= start)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fixtures::random_instance;
+    use super::super::{build_priority_map, ScheduleEntry};
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+
+    // Every order booked as late as its window allows, on the first pair that takes it there
+    fn latest_plan(
+        drivers: &[Driver],
+        vehicles: &[Vehicle],
+        orders: &[Order],
+        config: &SolverConfig,
+    ) -> SolverState {
+        let spacing = Spacing::from_config(config);
+        let mut state = SolverState::new(drivers, vehicles);
+        for order in orders {
+            let end = order.window().1;
+            let slot = (end - order.duration(), end);
+            let pair = drivers.iter().find_map(|driver| {
+                vehicles
+                    .iter()
+                    .find(|vehicle| state.slot_fits(order, driver, vehicle, slot, spacing))
+                    .map(|vehicle| (driver, vehicle))
+            });
+            if let Some((driver, vehicle)) = pair {
+                state.assign_order(order, driver, vehicle, slot);
+            }
+        }
+        state
+    }
+
+    fn assert_spaced(schedule: &[ScheduleEntry], min_gap: ChronoDuration) {
+        let mut entries: Vec<&ScheduleEntry> = schedule.iter().collect();
+        entries.sort_by_key(|e| e.start_time);
+        for pair in entries.windows(2) {
+            assert!(
+                pair[1].start_time - pair[0].end_time >= min_gap,
+                "{} and {} are too close",
+                pair[0].order_id,
+                pair[1].order_id
+            );
+        }
+    }
+
+    #[test]
+    fn compaction_never_creates_an_overlap_or_a_short_break() {
+        let config = SolverConfig {
+            compact: true,
+            ..SolverConfig::default()
+        };
+        let mut moved = 0;
+        for seed in 0..10 {
+            let (drivers, vehicles, orders) = random_instance(30, 4, 3, seed);
+            let index = ProblemIndex::new(&drivers, &vehicles, &orders);
+            let priority_map = build_priority_map(&orders, &config);
+            let mut state = latest_plan(&drivers, &vehicles, &orders, &config);
+            let before: HashMap<String, NaiveDateTime> = state
+                .assignments
+                .iter()
+                .map(|a| (a.order_id.clone(), a.start_time))
+                .collect();
+
+            compact_schedule(
+                &mut state,
+                &drivers,
+                &vehicles,
+                &orders,
+                &index,
+                &priority_map,
+                &config,
+            );
+            assert_eq!(state.assignments.len(), before.len());
+            assert_eq!(
+                first_infeasible_assignment(&state, &drivers, &vehicles, &index, &config),
+                None
+            );
+            for schedule in state.driver_schedules.values() {
+                assert_spaced(schedule, config.mandatory_break());
+            }
+            for vehicle in &vehicles {
+                let min_break = vehicle
+                    .min_break_minutes
+                    .map_or(config.mandatory_break(), ChronoDuration::minutes);
+                assert_spaced(&state.vehicle_schedules[&vehicle.id], min_break);
+            }
+            for a in &state.assignments {
+                assert!(a.start_time <= before[&a.order_id]);
+                moved += usize::from(a.start_time < before[&a.order_id]);
+            }
+        }
+        // Late bookings leave room, so the checks above ran on plans that changed
+        assert!(moved > 0);
+    }
+}
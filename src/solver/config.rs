@@ -0,0 +1,604 @@
+use super::timestamp::FlexibleTimestamp;
+use super::{PlannedAssignment, SchedulingResponse, MAX_SAFE_INTEGER};
+use chrono::{Duration as ChronoDuration, NaiveDateTime};
+use serde::Deserialize;
+use serde_with::serde_as;
+
+// How parents are picked from the population for crossover
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type")]
+pub enum SelectionStrategy {
+    Tournament { size: usize },
+    RouletteWheel,
+}
+
+impl Default for SelectionStrategy {
+    fn default() -> Self {
+        SelectionStrategy::Tournament { size: 3 }
+    }
+}
+
+// How a child's orders are split between its two parents, over the order list as given
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub enum CrossoverStrategy {
+    // Orders before a random cut come from the first parent, the rest from the second
+    #[default]
+    OnePoint,
+    // Orders between two random cuts come from the second parent
+    TwoPoint,
+    // Every order picks its parent with a coin flip
+    Uniform,
+}
+
+// Sequence in which initialize_random_state offers orders their placements, earlier orders
+// get the first pick of drivers and vehicles
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub enum InitOrder {
+    // Uniformly random
+    #[default]
+    Shuffle,
+    // Highest priority first, equal priorities in input order
+    PriorityDesc,
+    // Highest priority first, shuffled within each priority
+    Hybrid,
+}
+
+// How strongly a driver's preferred vehicle is enforced
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub enum PreferenceMode {
+    // Only earns preference_bonus
+    #[default]
+    Soft,
+    // A driver may not take another vehicle for an order their preferred one could serve
+    HardWhenAvailable,
+}
+
+// How order priorities are mapped before scoring, Log and Rank keep one huge priority from
+// drowning out every other order
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub enum PriorityTransform {
+    #[default]
+    Raw,
+    // ln(1 + priority), negative priorities count as 0
+    Log,
+    // 1 for the lowest distinct priority, 2 for the next and so on
+    Rank,
+}
+
+// How far initialize_random_state may bend soft limits for orders nothing else can take
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Relaxation {
+    // Minutes the mandatory break may be shortened by
+    pub break_slack_minutes: i64,
+    // Percent the vehicle weight and volume limits may be exceeded by
+    pub allow_capacity_overflow_pct: f64,
+}
+
+impl Relaxation {
+    pub fn is_enabled(&self) -> bool {
+        self.break_slack_minutes > 0 || self.allow_capacity_overflow_pct > 0.0
+    }
+}
+
+// Relative weight of each objective in the combined score
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct ObjectiveWeights {
+    // Summed order priorities and preference bonuses
+    pub priority: f64,
+    // Negative variance of the number of orders per vehicle
+    pub balance: f64,
+}
+
+impl Default for ObjectiveWeights {
+    fn default() -> Self {
+        ObjectiveWeights {
+            priority: 1.0,
+            balance: 0.0,
+        }
+    }
+}
+
+// Tunable genetic algorithm parameters, any field missing from the JS object falls back to the default
+#[serde_as]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct SolverConfig {
+    pub generations: u32,
+    pub population_size: usize,
+    pub mutation_rate: f64,
+    pub max_duration_ms: f64,
+    pub max_generations_without_improvement: u32,
+    // Raises the stall limit to this share of generations when that is larger, so long runs are
+    // not cut off by the fixed limit
+    pub stall_generations_fraction: Option<f64>,
+    // Score gain a generation needs over the last counted improvement to reset the stall counter
+    pub min_improvement_delta: f64,
+    // Times a stall reinitializes part of the population instead of ending the solve
+    pub max_restarts: u32,
+    // Share of the non-elite individuals replaced by random ones on a restart
+    pub restart_fraction: f64,
+    // Minimum rest between consecutive orders of the same driver or vehicle, 0 disables the check
+    pub mandatory_break_minutes: i64,
+    // Order slots are half-open, so one order may start exactly when the previous one of the
+    // same driver or vehicle ends. Off, touching orders overlap. Either way the mandatory break
+    // is checked on its own and already keeps touching orders apart when it is positive.
+    pub touching_allowed: bool,
+    // Seed for the random number generator, runs with the same seed and inputs are reproducible.
+    // Only the wall-clock timings in the response metrics differ between such runs. At most
+    // 2^53 - 1, so the seed survives the round trip through a JavaScript number.
+    pub seed: Option<u64>,
+    pub selection: SelectionStrategy,
+    // Chance the best contender of a tournament wins, 1.0 always picks it
+    pub tournament_win_prob: f64,
+    // Score added when a driver gets their preferred vehicle
+    pub preference_bonus: f64,
+    // Include the per-assignment score breakdown in the response
+    pub explain: bool,
+    // Subtracted per unit of priority for every order left unassigned
+    pub unassigned_penalty: f64,
+    pub objective_weights: ObjectiveWeights,
+    // Try the other parent's gene before random placement when an inherited one is infeasible
+    pub crossover_repair: bool,
+    // Every this many generations without improvement the mutation rate is multiplied by the ramp factor
+    pub mutation_ramp_interval: u32,
+    // 1.5 by default, 1.0 keeps the mutation rate fixed
+    pub mutation_ramp_factor: f64,
+    // Upper limit for the ramped mutation rate, the rate falls back to mutation_rate on improvement
+    pub max_mutation_rate: f64,
+    // Share of the initial population built by the priority-first greedy heuristic
+    pub greedy_init_fraction: f64,
+    // Order sequence of the randomly built individuals. Unless it is Shuffle, every second one
+    // still shuffles so the population keeps some diversity.
+    pub init_order: InitOrder,
+    // Solve orders repeating an earlier one in every field but the id as that one order, then
+    // copy its assignments to the repeats. Orders in a precedence chain, group or pin are never
+    // collapsed.
+    pub dedupe: bool,
+    // Build the randomly seeded share of the population coarse to fine instead: orders are
+    // bucketed by window start, spread over the vehicles per bucket and then booked on drivers of
+    // their planned vehicle. Mainly pays off on thousands of orders. Buckets are always offered
+    // highest priority first, so init_order must be left at Shuffle.
+    pub two_phase: bool,
+    pub two_phase_bucket_minutes: i64,
+    // Subtracted per minute a driver works beyond their max_work_minutes
+    pub overtime_penalty: f64,
+    // Stop on generations and stalls only, so seeded runs do not depend on machine speed
+    pub ignore_time_budget: bool,
+    // Mutation attempts made by resolve_after_removal after dropping an order
+    pub local_search_iterations: u32,
+    // Refine the best plan by exchanging the driver and vehicle of assignment pairs after the GA
+    pub local_search: bool,
+    // Time limit of that refinement, in addition to max_duration_ms
+    pub local_search_budget_ms: f64,
+    // Finally move orders with a flexible window as early as their driver and vehicle allow,
+    // without changing who takes them. This favors earliness_bonus and can cost gap_bonus.
+    pub compact: bool,
+    // Used to turn the distance between consecutive orders with coordinates into travel time
+    pub average_speed_kmh: f64,
+    // Share of the population copied unchanged into the next generation, rounded up to at least one
+    pub elite_fraction: f64,
+    // Added for every order group whose members are all assigned
+    pub group_bonus: f64,
+    pub crossover: CrossoverStrategy,
+    // Count why drivers and vehicles were rejected while building the initial population
+    pub collect_rejection_stats: bool,
+    pub relaxation: Relaxation,
+    // Longest stretch of order minutes a driver may work without a long break, 0 disables the rule
+    pub max_continuous_minutes: i64,
+    // Gap between two orders that ends a driver's continuous stretch
+    pub long_break_minutes: i64,
+    pub priority_transform: PriorityTransform,
+    // Boosts orders starting soon after reference_time, 0 leaves priorities alone
+    pub priority_urgency_factor: f64,
+    // "Now" for the urgency boost, the earliest order start when absent
+    #[serde_as(as = "Option<FlexibleTimestamp>")]
+    pub reference_time: Option<NaiveDateTime>,
+    // Attach the best schedule to every this many progress updates, 0 never does. Each snapshot is
+    // built and serialized to JS in full, so small intervals on large instances slow the solve.
+    pub snapshot_interval: u32,
+    // Independent populations of population_size each, 1 runs the plain GA
+    pub islands: usize,
+    // Every this many generations each island's elite is copied into the next island
+    pub migration_interval: u32,
+    // Subtracted per share of max_weight a used vehicle falls below its min_fill_pct, 0 disables it
+    pub min_fill_penalty: f64,
+    // Earned by an order in a flexible window when it starts as early as possible, falling
+    // linearly to nothing at its latest start
+    pub earliness_bonus: f64,
+    // Most vehicles a plan may use, unlimited when absent
+    pub max_vehicles_used: Option<usize>,
+    // Rest between two consecutive orders of a driver that earns gap_bonus, 0 disables the reward.
+    // The mandatory break is still enforced on its own.
+    pub preferred_gap_minutes: i64,
+    pub gap_bonus: f64,
+    // Upper limit of the summed gap reward, so roomy schedules cannot outweigh assigned orders
+    pub gap_bonus_cap: Option<f64>,
+    // Subtracted per driver with at least one assignment, to concentrate work on fewer shifts.
+    // It opposes max_assignment_imbalance, which spreads work over all drivers, and can pull
+    // against objective_weights.balance, which spreads it over vehicles. A penalty above an
+    // order's priority makes leaving the order unassigned cheaper than calling in a driver for it.
+    pub driver_count_penalty: f64,
+    // Largest allowed difference between the assignment counts of the busiest and the least
+    // busy driver. Scoring subtracts imbalance_penalty per assignment beyond it, and the best
+    // plan is then rebalanced by moving assignments to feasible drivers with fewer.
+    pub max_assignment_imbalance: Option<usize>,
+    pub imbalance_penalty: f64,
+    // Minimum gap on a vehicle between consecutive orders of different drivers, 0 disables it
+    pub vehicle_handoff_minutes: i64,
+    pub preference_mode: PreferenceMode,
+    // Earlier solution seeded into the initial population, repaired against the current inputs
+    pub warm_start: Option<SchedulingResponse>,
+    // Placements fixed by hand, booked first into every individual and never changed
+    pub pinned: Vec<PlannedAssignment>,
+    // Earlier plan whose drivers are softly favored, only driver_id and order_id are read
+    pub historical_assignments: Vec<PlannedAssignment>,
+    // Added, like preference_bonus, when an order keeps its historical driver
+    pub continuity_bonus: f64,
+    // Also return the plans that trade total priority against vehicle balance without being
+    // beaten on both, at most pareto_front_size of them
+    pub return_pareto: bool,
+    // Attach the whole final population to the response. Every individual is built and
+    // serialized like the best one, so the payload grows with population_size times islands.
+    pub return_population: bool,
+    pub pareto_front_size: usize,
+    // Units of order and vehicle weights and volumes, only used to label validation warnings
+    pub weight_unit: Option<String>,
+    pub volume_unit: Option<String>,
+    // Fail with InfeasibleRequired instead of returning a plan that leaves a required order out
+    pub strict_required: bool,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        SolverConfig {
+            generations: 1000,
+            population_size: 50,
+            mutation_rate: 0.1,
+            max_duration_ms: 10_000.0,
+            max_generations_without_improvement: 50,
+            stall_generations_fraction: None,
+            min_improvement_delta: 0.0,
+            max_restarts: 0,
+            restart_fraction: 0.5,
+            mandatory_break_minutes: 30,
+            touching_allowed: true,
+            seed: None,
+            selection: SelectionStrategy::default(),
+            tournament_win_prob: 1.0,
+            preference_bonus: 0.1,
+            explain: false,
+            unassigned_penalty: 0.0,
+            objective_weights: ObjectiveWeights::default(),
+            crossover_repair: false,
+            mutation_ramp_interval: 10,
+            mutation_ramp_factor: 1.5,
+            max_mutation_rate: 0.5,
+            greedy_init_fraction: 0.2,
+            init_order: InitOrder::default(),
+            dedupe: false,
+            two_phase: false,
+            two_phase_bucket_minutes: 60,
+            overtime_penalty: 0.0,
+            ignore_time_budget: false,
+            local_search_iterations: 100,
+            local_search: false,
+            local_search_budget_ms: 1_000.0,
+            compact: false,
+            average_speed_kmh: 50.0,
+            elite_fraction: 0.1,
+            group_bonus: 0.0,
+            crossover: CrossoverStrategy::default(),
+            collect_rejection_stats: false,
+            relaxation: Relaxation::default(),
+            max_continuous_minutes: 0,
+            long_break_minutes: 45,
+            priority_transform: PriorityTransform::default(),
+            priority_urgency_factor: 0.0,
+            reference_time: None,
+            snapshot_interval: 0,
+            islands: 1,
+            migration_interval: 10,
+            min_fill_penalty: 0.0,
+            earliness_bonus: 0.0,
+            max_vehicles_used: None,
+            preferred_gap_minutes: 0,
+            gap_bonus: 0.0,
+            gap_bonus_cap: None,
+            driver_count_penalty: 0.0,
+            max_assignment_imbalance: None,
+            imbalance_penalty: 1.0,
+            vehicle_handoff_minutes: 0,
+            preference_mode: PreferenceMode::default(),
+            warm_start: None,
+            pinned: Vec::new(),
+            historical_assignments: Vec::new(),
+            continuity_bonus: 0.0,
+            return_pareto: false,
+            return_population: false,
+            pareto_front_size: 5,
+            weight_unit: None,
+            volume_unit: None,
+            strict_required: false,
+        }
+    }
+}
+
+impl SolverConfig {
+    pub fn mandatory_break(&self) -> ChronoDuration {
+        ChronoDuration::minutes(self.mandatory_break_minutes)
+    }
+
+    // Generations without improvement after which the solve stops
+    pub fn stall_limit(&self) -> u32 {
+        let relative = self.stall_generations_fraction.map_or(0, |fraction| {
+            (self.generations as f64 * fraction).ceil() as u32
+        });
+        self.max_generations_without_improvement.max(relative)
+    }
+
+    // Whether a generation's best score resets the stall counter, gains within
+    // min_improvement_delta keep counting as a stall until they add up to more than the delta
+    pub fn improves_on(&self, best: f64, current: f64) -> bool {
+        current > best + self.min_improvement_delta
+    }
+
+    // Mutation rate after another generation without improvement, stalled counting that one.
+    // Ramps up by mutation_ramp_factor every mutation_ramp_interval stalled generations, never
+    // beyond max_mutation_rate unless mutation_rate itself is higher.
+    pub fn ramped_mutation_rate(&self, current: f64, stalled: u32) -> f64 {
+        if !stalled.is_multiple_of(self.mutation_ramp_interval) {
+            return current;
+        }
+        (current * self.mutation_ramp_factor).min(self.max_mutation_rate.max(self.mutation_rate))
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.population_size < 1 {
+            return Err(format!(
+                "Invalid config: population_size must be at least 1, got {}",
+                self.population_size
+            ));
+        }
+        if let SelectionStrategy::Tournament { size } = self.selection {
+            if size < 1 || size > self.population_size {
+                return Err(format!(
+                    "Invalid config: tournament size must be between 1 and population_size ({}), got {}",
+                    self.population_size, size
+                ));
+            }
+        }
+        if self.tournament_win_prob <= 0.0 || self.tournament_win_prob > 1.0 {
+            return Err(format!(
+                "Invalid config: tournament_win_prob must be above 0 and at most 1, got {}",
+                self.tournament_win_prob
+            ));
+        }
+        if self.mandatory_break_minutes < 0 {
+            return Err(format!(
+                "Invalid config: mandatory_break_minutes must not be negative, got {}",
+                self.mandatory_break_minutes
+            ));
+        }
+        if self.mutation_ramp_interval < 1 {
+            return Err("Invalid config: mutation_ramp_interval must be at least 1".to_string());
+        }
+        if self.mutation_ramp_factor < 1.0 {
+            return Err(format!(
+                "Invalid config: mutation_ramp_factor must be at least 1.0, got {}",
+                self.mutation_ramp_factor
+            ));
+        }
+        if self.average_speed_kmh <= 0.0 {
+            return Err(format!(
+                "Invalid config: average_speed_kmh must be positive, got {}",
+                self.average_speed_kmh
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.greedy_init_fraction) {
+            return Err(format!(
+                "Invalid config: greedy_init_fraction must be between 0 and 1, got {}",
+                self.greedy_init_fraction
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.elite_fraction) {
+            return Err(format!(
+                "Invalid config: elite_fraction must be between 0 and 1, got {}",
+                self.elite_fraction
+            ));
+        }
+        if self.relaxation.break_slack_minutes < 0
+            || self.relaxation.allow_capacity_overflow_pct < 0.0
+        {
+            return Err("Invalid config: relaxation limits must not be negative".to_string());
+        }
+        if self
+            .stall_generations_fraction
+            .is_some_and(|f| !(0.0..=1.0).contains(&f))
+        {
+            return Err(
+                "Invalid config: stall_generations_fraction must be between 0 and 1".to_string(),
+            );
+        }
+        if self.priority_urgency_factor < 0.0 {
+            return Err(format!(
+                "Invalid config: priority_urgency_factor must not be negative, got {}",
+                self.priority_urgency_factor
+            ));
+        }
+        if self.min_improvement_delta < 0.0 {
+            return Err(format!(
+                "Invalid config: min_improvement_delta must not be negative, got {}",
+                self.min_improvement_delta
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.restart_fraction) {
+            return Err(format!(
+                "Invalid config: restart_fraction must be between 0 and 1, got {}",
+                self.restart_fraction
+            ));
+        }
+        if self.two_phase_bucket_minutes <= 0 {
+            return Err(format!(
+                "Invalid config: two_phase_bucket_minutes must be positive, got {}",
+                self.two_phase_bucket_minutes
+            ));
+        }
+        if self.two_phase && !matches!(self.init_order, InitOrder::Shuffle) {
+            return Err(format!(
+                "Invalid config: init_order {:?} does not apply under two_phase",
+                self.init_order
+            ));
+        }
+        if self.local_search_budget_ms < 0.0 {
+            return Err(format!(
+                "Invalid config: local_search_budget_ms must not be negative, got {}",
+                self.local_search_budget_ms
+            ));
+        }
+        if self.driver_count_penalty < 0.0 {
+            return Err(format!(
+                "Invalid config: driver_count_penalty must not be negative, got {}",
+                self.driver_count_penalty
+            ));
+        }
+        if self.imbalance_penalty < 0.0 {
+            return Err(format!(
+                "Invalid config: imbalance_penalty must not be negative, got {}",
+                self.imbalance_penalty
+            ));
+        }
+        if self.pareto_front_size < 1 {
+            return Err("Invalid config: pareto_front_size must be at least 1".to_string());
+        }
+        if self.migration_interval < 1 {
+            return Err("Invalid config: migration_interval must be at least 1".to_string());
+        }
+        if self.max_continuous_minutes < 0 || self.long_break_minutes < 0 {
+            return Err(
+                "Invalid config: max_continuous_minutes and long_break_minutes must not be negative"
+                    .to_string(),
+            );
+        }
+        if let Some(seed) = self.seed.filter(|&seed| seed > MAX_SAFE_INTEGER) {
+            return Err(format!(
+                "Invalid config: seed must be at most 2^53 - 1 ({}), got {}",
+                MAX_SAFE_INTEGER, seed
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fixtures::random_instance;
+    use super::super::{solve_with_observer, ProgressUpdate, SolveObserver};
+    use super::*;
+
+    #[test]
+    fn mutation_rate_ramps_by_half_every_interval_up_to_the_cap() {
+        let config = SolverConfig::default();
+        let mut rate = config.mutation_rate;
+        let mut rates = Vec::new();
+        for stalled in 1..=60 {
+            rate = config.ramped_mutation_rate(rate, stalled);
+            if stalled % 10 == 0 {
+                rates.push(rate);
+            }
+        }
+        let expected = [0.15, 0.225, 0.3375, 0.50625_f64.min(0.5), 0.5, 0.5];
+        for (rate, expected) in rates.iter().zip(expected) {
+            assert!((rate - expected).abs() < 1e-12, "{:?}", rates);
+        }
+        // Between two steps the rate holds
+        assert_eq!(config.ramped_mutation_rate(0.15, 11), 0.15);
+    }
+
+    #[test]
+    fn ramp_factor_of_one_keeps_the_rate() {
+        let config = SolverConfig {
+            mutation_ramp_factor: 1.0,
+            ..SolverConfig::default()
+        };
+        assert_eq!(config.ramped_mutation_rate(0.1, 10), 0.1);
+    }
+
+    #[test]
+    fn two_phase_rejects_an_init_order() {
+        let config = SolverConfig {
+            two_phase: true,
+            init_order: InitOrder::PriorityDesc,
+            ..SolverConfig::default()
+        };
+        assert!(config.validate().is_err());
+        let config = SolverConfig {
+            init_order: InitOrder::Shuffle,
+            ..config
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn a_seed_beyond_a_safe_javascript_integer_is_rejected() {
+        let config = SolverConfig {
+            seed: Some(MAX_SAFE_INTEGER),
+            ..SolverConfig::default()
+        };
+        assert!(config.validate().is_ok());
+        let config = SolverConfig {
+            seed: Some(MAX_SAFE_INTEGER + 1),
+            ..config
+        };
+        assert_eq!(
+            config.validate(),
+            Err(
+                "Invalid config: seed must be at most 2^53 - 1 (9007199254740991), got 9007199254740992"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn a_gain_within_min_improvement_delta_does_not_reset_the_stall() {
+        let config = SolverConfig {
+            min_improvement_delta: 0.001,
+            ..SolverConfig::default()
+        };
+        assert!(!config.improves_on(10.0, 10.0001));
+        assert!(config.improves_on(10.0, 10.002));
+        assert!(SolverConfig::default().improves_on(10.0, 10.0001));
+
+        struct BestScores(Vec<f64>);
+        impl SolveObserver for BestScores {
+            fn on_progress(&mut self, progress: &ProgressUpdate) {
+                self.0.push(progress.best_score);
+            }
+        }
+        let (drivers, vehicles, orders) = random_instance(40, 5, 4, 11);
+        let run = |min_improvement_delta| {
+            let config = SolverConfig {
+                seed: Some(1),
+                population_size: 16,
+                generations: 200,
+                max_generations_without_improvement: 10,
+                min_improvement_delta,
+                ignore_time_budget: true,
+                ..SolverConfig::default()
+            };
+            let mut scores = BestScores(Vec::new());
+            let response = solve_with_observer(&drivers, &vehicles, &orders, &config, &mut scores);
+            (config, response, scores.0)
+        };
+
+        // No gain comes near the delta, so the solve stops after the stall limit even though
+        // the best score still climbed in between
+        let (config, response, scores) = run(1000.0);
+        assert_eq!(response.metrics.generations, config.stall_limit());
+        assert!(scores.last() > scores.first(), "{:?}", scores);
+
+        let (config, response, _) = run(0.0);
+        assert!(response.metrics.generations > config.stall_limit());
+    }
+}
@@ -0,0 +1,310 @@
+use super::{
+    input_warnings, required_unassigned, Order, SchedulingResponse, SolverConfig,
+    ValidationWarning, Vehicle,
+};
+use chrono::NaiveDateTime;
+use std::collections::{HashMap, HashSet};
+
+// Every field of an order but its id, floats by their bits and id and tag lists sorted. of
+// destructures the whole order, so a new Order field does not compile until it is keyed on.
+#[derive(PartialEq, Eq, Hash)]
+struct DuplicateKey {
+    start_time: NaiveDateTime,
+    end_time: NaiveDateTime,
+    priority: Option<u64>,
+    tags: Option<Vec<String>>,
+    weight: u64,
+    volume: Option<u64>,
+    duration_minutes: Option<i64>,
+    earliest_start: Option<NaiveDateTime>,
+    latest_end: Option<NaiveDateTime>,
+    must_follow: Option<Vec<String>>,
+    splittable: bool,
+    required: Option<bool>,
+    lat: Option<u64>,
+    lon: Option<u64>,
+    group_id: Option<String>,
+    same_vehicle_group: Option<String>,
+    service_minutes: Option<i64>,
+    allowed_driver_ids: Option<Vec<String>>,
+    allowed_vehicle_ids: Option<Vec<String>>,
+    required_drivers: Option<usize>,
+}
+
+impl DuplicateKey {
+    fn of(order: &Order) -> Self {
+        let Order {
+            id: _,
+            start_time,
+            end_time,
+            priority,
+            tags,
+            weight,
+            volume,
+            duration_minutes,
+            earliest_start,
+            latest_end,
+            must_follow,
+            splittable,
+            required,
+            lat,
+            lon,
+            group_id,
+            same_vehicle_group,
+            service_minutes,
+            allowed_driver_ids,
+            allowed_vehicle_ids,
+            required_drivers,
+        } = order;
+        let sorted = |ids: &Option<Vec<String>>| {
+            ids.clone().map(|mut ids| {
+                ids.sort();
+                ids
+            })
+        };
+        DuplicateKey {
+            start_time: *start_time,
+            end_time: *end_time,
+            priority: priority.map(f64::to_bits),
+            tags: sorted(tags),
+            weight: weight.to_bits(),
+            volume: volume.map(f64::to_bits),
+            duration_minutes: *duration_minutes,
+            earliest_start: *earliest_start,
+            latest_end: *latest_end,
+            must_follow: sorted(must_follow),
+            splittable: *splittable,
+            required: *required,
+            lat: lat.map(f64::to_bits),
+            lon: lon.map(f64::to_bits),
+            group_id: group_id.clone(),
+            same_vehicle_group: same_vehicle_group.clone(),
+            service_minutes: *service_minutes,
+            allowed_driver_ids: sorted(allowed_driver_ids),
+            allowed_vehicle_ids: sorted(allowed_vehicle_ids),
+            required_drivers: *required_drivers,
+        }
+    }
+}
+
+// Duplicate order ids keyed by the id of the first order they repeat, in input order
+pub type Duplicates = HashMap<String, Vec<String>>;
+
+// The orders without the ones repeating an earlier order in every field but the id, with tags
+// and allowed ids in any order. Orders tied to others through precedence or groups, and pinned
+// ones, are always kept since dropping them would change what the others require.
+pub fn collapse_duplicates(orders: &[Order], config: &SolverConfig) -> (Vec<Order>, Duplicates) {
+    let predecessors: HashSet<&str> = orders
+        .iter()
+        .flat_map(|o| o.must_follow.iter().flatten())
+        .map(String::as_str)
+        .collect();
+    let pinned: HashSet<&str> = config.pinned.iter().map(|a| a.order_id.as_str()).collect();
+    let collapsible = |order: &Order| {
+        order.must_follow.as_ref().is_none_or(Vec::is_empty)
+            && order.group_id.is_none()
+            && order.same_vehicle_group.is_none()
+            && !predecessors.contains(order.id.as_str())
+            && !pinned.contains(order.id.as_str())
+    };
+
+    let mut first_by_key: HashMap<DuplicateKey, &str> = HashMap::new();
+    let mut unique = Vec::new();
+    let mut duplicates = Duplicates::new();
+    for order in orders {
+        if !collapsible(order) {
+            unique.push(order.clone());
+            continue;
+        }
+        let key = DuplicateKey::of(order);
+        match first_by_key.get(&key) {
+            Some(&first) => duplicates
+                .entry(first.to_string())
+                .or_default()
+                .push(order.id.clone()),
+            None => {
+                first_by_key.insert(key, &order.id);
+                unique.push(order.clone());
+            }
+        }
+    }
+    (unique, duplicates)
+}
+
+// Gives every duplicate the assignments and unassigned status of the order it repeats, keeping
+// the input order of the lists, and warns about each duplicate. score and breakdown stay those
+// of the collapsed problem, which counts each set of duplicates once.
+pub fn expand_duplicates(
+    response: &mut SchedulingResponse,
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    duplicates: &Duplicates,
+    config: &SolverConfig,
+) {
+    expand_plan(response, orders, duplicates);
+    let solved_as = solved_as(duplicates);
+    let mut warnings = input_warnings(vehicles, orders, config);
+    for order in orders {
+        if let Some(&first) = solved_as.get(order.id.as_str()) {
+            warnings.push(ValidationWarning::DuplicateOrder {
+                order_id: order.id.clone(),
+                duplicate_of: first.to_string(),
+            });
+        }
+    }
+    response.warnings = warnings;
+}
+
+// Every duplicate id mapped to the id the solver saw instead
+fn solved_as(duplicates: &Duplicates) -> HashMap<&str, &str> {
+    duplicates
+        .iter()
+        .flat_map(|(first, ids)| ids.iter().map(move |id| (id.as_str(), first.as_str())))
+        .collect()
+}
+
+// The plan part of expand_duplicates, also applied to the attached population and Pareto front
+fn expand_plan(response: &mut SchedulingResponse, orders: &[Order], duplicates: &Duplicates) {
+    let solved_as = solved_as(duplicates);
+    let solved_id = |order_id: &str| {
+        solved_as
+            .get(order_id)
+            .copied()
+            .unwrap_or(order_id)
+            .to_string()
+    };
+    let expand_ids = |ids: &[String]| -> Vec<String> {
+        let listed: HashSet<&str> = ids.iter().map(String::as_str).collect();
+        orders
+            .iter()
+            .filter(|o| listed.contains(solved_id(&o.id).as_str()))
+            .map(|o| o.id.clone())
+            .collect()
+    };
+
+    let mut assignments = Vec::with_capacity(response.assignments.len());
+    for assignment in &response.assignments {
+        assignments.push(assignment.clone());
+        for id in duplicates.get(&assignment.order_id).into_iter().flatten() {
+            let mut copy = assignment.clone();
+            copy.order_id = id.clone();
+            assignments.push(copy);
+        }
+    }
+    response.assignments = assignments;
+    response.unassigned_order_ids = expand_ids(&response.unassigned_order_ids);
+    let reasons: HashMap<String, _> = response
+        .unassigned_reasons
+        .drain(..)
+        .map(|r| (r.order_id.clone(), r))
+        .collect();
+    response.unassigned_reasons = orders
+        .iter()
+        .filter_map(|o| {
+            let mut reason = reasons.get(&solved_id(&o.id))?.clone();
+            reason.order_id = o.id.clone();
+            Some(reason)
+        })
+        .collect();
+    response.relaxed_assignments = response
+        .relaxed_assignments
+        .iter()
+        .flat_map(|id| std::iter::once(id).chain(duplicates.get(id).into_iter().flatten()))
+        .cloned()
+        .collect();
+    response.required_unassigned = required_unassigned(&response.assignments, orders);
+
+    for individual in response.population.iter_mut().flatten() {
+        expand_plan(individual, orders, duplicates);
+    }
+    for solution in response.pareto_front.iter_mut().flatten() {
+        expand_plan(&mut solution.response, orders, duplicates);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fixtures::{at, driver, order, vehicle};
+    use super::super::solve;
+    use super::*;
+
+    #[test]
+    fn identical_orders_take_one_slot_and_report_both_ids() {
+        let drivers = [driver("d1")];
+        let vehicles = [Vehicle {
+            tags: Some(vec!["cold".to_string(), "fragile".to_string()]),
+            ..vehicle("v1")
+        }];
+        // Same tags listed in another order still make a duplicate
+        let tagged = |id: &str, tags: [&str; 2]| Order {
+            tags: Some(tags.iter().map(|t| t.to_string()).collect()),
+            ..order(id, at(8, 0), at(9, 0))
+        };
+        let orders = [
+            tagged("a", ["cold", "fragile"]),
+            tagged("b", ["fragile", "cold"]),
+        ];
+        let config = SolverConfig {
+            seed: Some(1),
+            population_size: 8,
+            generations: 10,
+            ignore_time_budget: true,
+            ..SolverConfig::default()
+        };
+
+        let (unique, duplicates) = collapse_duplicates(&orders, &config);
+        assert_eq!(unique.len(), 1);
+        assert_eq!(duplicates["a"], ["b"]);
+
+        // Without dedupe the second copy competes for the only slot and loses
+        let response = solve(&drivers, &vehicles, &orders, &config);
+        assert_eq!(response.assignments.len(), 1);
+        assert_eq!(response.unassigned_order_ids.len(), 1);
+
+        let config = SolverConfig {
+            dedupe: true,
+            ..config
+        };
+        let response = solve(&drivers, &vehicles, &orders, &config);
+        let rows: Vec<(&str, &str, NaiveDateTime)> = response
+            .assignments
+            .iter()
+            .map(|a| (a.order_id.as_str(), a.vehicle_id.as_str(), a.start_time))
+            .collect();
+        assert_eq!(rows, [("a", "v1", at(8, 0)), ("b", "v1", at(8, 0))]);
+        assert!(response.unassigned_order_ids.is_empty());
+        assert!(response.warnings.iter().any(|w| matches!(
+            w,
+            ValidationWarning::DuplicateOrder { order_id, duplicate_of }
+                if order_id == "b" && duplicate_of == "a"
+        )));
+    }
+
+    #[test]
+    fn orders_differing_only_in_priority_or_location_are_kept_apart() {
+        let base = order("a", at(8, 0), at(9, 0));
+        let orders = [
+            base.clone(),
+            Order {
+                id: "b".to_string(),
+                priority: Some(5.0),
+                ..base.clone()
+            },
+            Order {
+                id: "c".to_string(),
+                lat: Some(52.5),
+                lon: Some(13.4),
+                ..base.clone()
+            },
+            Order {
+                id: "d".to_string(),
+                ..base.clone()
+            },
+        ];
+        let (unique, duplicates) = collapse_duplicates(&orders, &SolverConfig::default());
+        let ids: Vec<&str> = unique.iter().map(|o| o.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b", "c"]);
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates["a"], ["d"]);
+    }
+}
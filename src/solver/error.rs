@@ -0,0 +1,325 @@
+use super::{SchedulingResponse, SolverConfig, ValidationError};
+use serde::ser::{SerializeStruct, Serializer};
+use serde::Serialize;
+use std::fmt;
+
+// Why a call was rejected before or instead of solving. Serializes as { code, message, details }:
+// code is the variant name to branch on, message is English text for logs, and details holds
+// the variant's data or null. problem_index is only set for solve_batch.
+//
+// A cancelled solve is an error carrying the plan found so far. Required orders left
+// unassigned are only one under config.strict_required, otherwise the response reports them
+// through required_unassigned.
+#[derive(Clone, Debug)]
+pub enum SolverError {
+    DeserializationFailed {
+        input: String,
+        reason: String,
+    },
+    InvalidConfig {
+        problem_index: Option<usize>,
+        reason: String,
+    },
+    ValidationFailed {
+        problem_index: Option<usize>,
+        errors: Vec<ValidationError>,
+    },
+    // config.pinned cannot be booked as given
+    InfeasiblePin {
+        problem_index: Option<usize>,
+        reason: String,
+    },
+    InvalidArgument {
+        reason: String,
+    },
+    SerializationFailed {
+        reason: String,
+    },
+    // Required orders the plan leaves out or only partly covers, under config.strict_required
+    InfeasibleRequired {
+        order_ids: Vec<String>,
+    },
+    // The caller stopped the solve, partial is the best plan found until then
+    Cancelled {
+        partial: Box<SchedulingResponse>,
+    },
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Details<'a> {
+    None,
+    Input {
+        input: &'a str,
+    },
+    Problem {
+        problem_index: Option<usize>,
+    },
+    Validation {
+        problem_index: Option<usize>,
+        errors: &'a [ValidationError],
+    },
+    Required {
+        order_ids: &'a [String],
+    },
+    Cancelled {
+        partial: &'a SchedulingResponse,
+    },
+}
+
+impl SolverError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            SolverError::DeserializationFailed { .. } => "DeserializationFailed",
+            SolverError::InvalidConfig { .. } => "InvalidConfig",
+            SolverError::ValidationFailed { .. } => "ValidationFailed",
+            SolverError::InfeasiblePin { .. } => "InfeasiblePin",
+            SolverError::InvalidArgument { .. } => "InvalidArgument",
+            SolverError::SerializationFailed { .. } => "SerializationFailed",
+            SolverError::InfeasibleRequired { .. } => "InfeasibleRequired",
+            SolverError::Cancelled { .. } => "Cancelled",
+        }
+    }
+
+    fn details(&self) -> Details<'_> {
+        match self {
+            SolverError::DeserializationFailed { input, .. } => Details::Input { input },
+            SolverError::InvalidConfig { problem_index, .. }
+            | SolverError::InfeasiblePin { problem_index, .. } => Details::Problem {
+                problem_index: *problem_index,
+            },
+            SolverError::ValidationFailed {
+                problem_index,
+                errors,
+            } => Details::Validation {
+                problem_index: *problem_index,
+                errors,
+            },
+            SolverError::InvalidArgument { .. } | SolverError::SerializationFailed { .. } => {
+                Details::None
+            }
+            SolverError::InfeasibleRequired { order_ids } => Details::Required { order_ids },
+            SolverError::Cancelled { partial } => Details::Cancelled { partial },
+        }
+    }
+}
+
+impl fmt::Display for SolverError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Errors of a batch name the problem they came from
+        let problem_index = match self {
+            SolverError::InvalidConfig { problem_index, .. }
+            | SolverError::ValidationFailed { problem_index, .. }
+            | SolverError::InfeasiblePin { problem_index, .. } => *problem_index,
+            _ => None,
+        };
+        if let Some(problem_index) = problem_index {
+            write!(f, "Problem {}: ", problem_index)?;
+        }
+        match self {
+            SolverError::DeserializationFailed { input, reason } => {
+                write!(f, "Failed to deserialize {}: {}", input, reason)
+            }
+            SolverError::ValidationFailed { errors, .. } => {
+                write!(f, "Invalid input, {} problem(s) found", errors.len())
+            }
+            SolverError::InvalidConfig { reason, .. }
+            | SolverError::InfeasiblePin { reason, .. }
+            | SolverError::InvalidArgument { reason } => f.write_str(reason),
+            SolverError::SerializationFailed { reason } => {
+                write!(f, "Failed to serialize the result: {}", reason)
+            }
+            SolverError::InfeasibleRequired { order_ids } => write!(
+                f,
+                "Required order(s) left unassigned: {}",
+                order_ids.join(", ")
+            ),
+            SolverError::Cancelled { partial } => write!(
+                f,
+                "Solve cancelled, the partial plan assigns {} order(s)",
+                partial.assignments.len()
+            ),
+        }
+    }
+}
+
+impl Serialize for SolverError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut error = serializer.serialize_struct("SolverError", 3)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", &self.to_string())?;
+        error.serialize_field("details", &self.details())?;
+        error.end()
+    }
+}
+
+// The response of a GA call, or Cancelled when the caller stopped it and InfeasibleRequired
+// when config.strict_required is set and a required order is left out
+pub fn reject_unfinished(
+    response: SchedulingResponse,
+    config: &SolverConfig,
+) -> Result<SchedulingResponse, SolverError> {
+    if response.cancelled {
+        return Err(SolverError::Cancelled {
+            partial: Box::new(response),
+        });
+    }
+    if config.strict_required && !response.required_unassigned.is_empty() {
+        return Err(SolverError::InfeasibleRequired {
+            order_ids: response.required_unassigned,
+        });
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fixtures::{assignment, at, response};
+    use super::*;
+
+    #[test]
+    fn deserialization_failed_names_the_input() {
+        let error = SolverError::DeserializationFailed {
+            input: "orders".to_string(),
+            reason: "missing field `id`".to_string(),
+        };
+        assert_eq!(error.code(), "DeserializationFailed");
+        assert_eq!(
+            error.to_string(),
+            "Failed to deserialize orders: missing field `id`"
+        );
+        assert!(matches!(
+            error.details(),
+            Details::Input { input: "orders" }
+        ));
+    }
+
+    #[test]
+    fn invalid_config_carries_the_batch_problem() {
+        let error = SolverError::InvalidConfig {
+            problem_index: Some(2),
+            reason: "Invalid config: population_size must be at least 1, got 0".to_string(),
+        };
+        assert_eq!(error.code(), "InvalidConfig");
+        assert_eq!(
+            error.to_string(),
+            "Problem 2: Invalid config: population_size must be at least 1, got 0"
+        );
+        assert!(matches!(
+            error.details(),
+            Details::Problem {
+                problem_index: Some(2)
+            }
+        ));
+    }
+
+    #[test]
+    fn validation_failed_counts_and_lists_the_errors() {
+        let error = SolverError::ValidationFailed {
+            problem_index: None,
+            errors: vec![
+                ValidationError::EmptyDrivers,
+                ValidationError::InvalidTimeRange {
+                    order_id: "o1".to_string(),
+                },
+            ],
+        };
+        assert_eq!(error.code(), "ValidationFailed");
+        assert_eq!(error.to_string(), "Invalid input, 2 problem(s) found");
+        assert!(matches!(
+            error.details(),
+            Details::Validation {
+                problem_index: None,
+                errors: [
+                    ValidationError::EmptyDrivers,
+                    ValidationError::InvalidTimeRange { .. }
+                ],
+            }
+        ));
+    }
+
+    #[test]
+    fn infeasible_pin_keeps_the_reason() {
+        let error = SolverError::InfeasiblePin {
+            problem_index: None,
+            reason: "pinned order o1 overlaps o2 on driver d1".to_string(),
+        };
+        assert_eq!(error.code(), "InfeasiblePin");
+        assert_eq!(
+            error.to_string(),
+            "pinned order o1 overlaps o2 on driver d1"
+        );
+        assert!(matches!(
+            error.details(),
+            Details::Problem {
+                problem_index: None
+            }
+        ));
+    }
+
+    #[test]
+    fn invalid_argument_has_no_details() {
+        let error = SolverError::InvalidArgument {
+            reason: "runs must be at least 1".to_string(),
+        };
+        assert_eq!(error.code(), "InvalidArgument");
+        assert_eq!(error.to_string(), "runs must be at least 1");
+        assert!(matches!(error.details(), Details::None));
+    }
+
+    #[test]
+    fn serialization_failed_wraps_the_reason() {
+        let error = SolverError::SerializationFailed {
+            reason: "unsupported type".to_string(),
+        };
+        assert_eq!(error.code(), "SerializationFailed");
+        assert_eq!(
+            error.to_string(),
+            "Failed to serialize the result: unsupported type"
+        );
+        assert!(matches!(error.details(), Details::None));
+    }
+
+    #[test]
+    fn infeasible_required_lists_the_orders_under_strict_mode() {
+        let plan = SchedulingResponse {
+            required_unassigned: vec!["o2".to_string(), "o3".to_string()],
+            ..response(Vec::new())
+        };
+        assert!(reject_unfinished(plan.clone(), &SolverConfig::default()).is_ok());
+
+        let config = SolverConfig {
+            strict_required: true,
+            ..SolverConfig::default()
+        };
+        let error = reject_unfinished(plan, &config).unwrap_err();
+        assert_eq!(error.code(), "InfeasibleRequired");
+        assert_eq!(
+            error.to_string(),
+            "Required order(s) left unassigned: o2, o3"
+        );
+        assert!(matches!(
+            error.details(),
+            Details::Required { order_ids } if order_ids == ["o2", "o3"]
+        ));
+    }
+
+    #[test]
+    fn cancelled_carries_the_partial_plan() {
+        let plan = SchedulingResponse {
+            cancelled: true,
+            ..response(vec![assignment("o1", "d1", "v1", (at(8, 0), at(9, 0)))])
+        };
+        let error = reject_unfinished(plan, &SolverConfig::default()).unwrap_err();
+        assert_eq!(error.code(), "Cancelled");
+        assert_eq!(
+            error.to_string(),
+            "Solve cancelled, the partial plan assigns 1 order(s)"
+        );
+        assert!(matches!(
+            error.details(),
+            Details::Cancelled { partial }
+                if partial.cancelled && partial.assignments[0].order_id == "o1"
+        ));
+    }
+}
@@ -0,0 +1,309 @@
+use super::{
+    assigned_piece, build_priority_map, driver_may_operate, driver_rejection, handoff_too_short,
+    vehicle_rejection, Assignment, Driver, Order, PlannedAssignment, ProblemIndex, Rejection,
+    SolverConfig, SolverState, Spacing, Vehicle,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ScheduleEvaluation {
+    pub score: f64,
+    pub violations: Vec<String>,
+}
+
+// Scores an externally provided plan and lists every constraint it breaks. Each assignment is
+// checked at its own start_time and end_time against the assignments starting before it, see
+// PlannedAssignment for missing times. Infeasible ones are still applied so the score reflects
+// the plan as given.
+pub fn evaluate_schedule(
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    assignments: &[PlannedAssignment],
+    config: &SolverConfig,
+) -> ScheduleEvaluation {
+    let spacing = Spacing::from_config(config);
+    let priority_map = build_priority_map(orders, config);
+    let index = ProblemIndex::new(drivers, vehicles, orders);
+    let mut state = SolverState::new(drivers, vehicles);
+    let mut violations = Vec::new();
+    let mut seen_order_ids: HashMap<&String, usize> = HashMap::new();
+
+    // Replayed in start order, so a predecessor listed after its dependent still counts
+    let mut replay: Vec<(&Order, Assignment)> = Vec::new();
+    for planned in assignments {
+        let Some(&order) = index.orders.get(planned.order_id.as_str()) else {
+            violations.push(format!("Order {}: unknown order", planned.order_id));
+            continue;
+        };
+        replay.push((order, planned.resolve(order)));
+    }
+    replay.sort_by_key(|(_, a)| a.start_time);
+    for (order, assignment) in &replay {
+        let Some(&driver) = index.drivers.get(assignment.driver_id.as_str()) else {
+            violations.push(format!(
+                "Order {}: unknown driver {}",
+                order.id, assignment.driver_id
+            ));
+            continue;
+        };
+        let Some(&vehicle) = index.vehicles.get(assignment.vehicle_id.as_str()) else {
+            violations.push(format!(
+                "Order {}: unknown vehicle {}",
+                order.id, assignment.vehicle_id
+            ));
+            continue;
+        };
+        // Split orders legitimately appear once per piece, crew orders once per driver
+        let seen = seen_order_ids.entry(&order.id).or_insert(0);
+        *seen += 1;
+        if *seen > order.crew_size() && !order.splittable {
+            violations.push(format!("Order {}: assigned more than once", order.id));
+            continue;
+        }
+
+        let piece = assigned_piece(order, assignment);
+        let order: &Order = &piece;
+        let slot = (assignment.start_time, assignment.end_time);
+
+        let (window_start, window_end) = order.window();
+        if slot.0 < window_start || slot.1 > window_end || slot.1 - slot.0 != order.duration() {
+            violations.push(format!(
+                "Order {} on driver {} / vehicle {}: scheduled outside its time window or for \
+                 the wrong duration",
+                order.id, driver.id, vehicle.id
+            ));
+        }
+        let driver_schedule = &state.driver_schedules[&driver.id];
+        let vehicle_schedule = &state.vehicle_schedules[&vehicle.id];
+        let rejection = if !driver_may_operate(driver, vehicle) {
+            Some(Rejection::VehicleNotAllowed)
+        } else {
+            driver_rejection(order, slot, driver, driver_schedule, spacing)
+                .or_else(|| vehicle_rejection(order, slot, vehicle, vehicle_schedule, spacing))
+                .or_else(|| {
+                    handoff_too_short(order, slot, driver, vehicle_schedule, spacing)
+                        .then_some(Rejection::VehicleHandoff)
+                })
+        };
+        if let Some(rejection) = rejection {
+            violations.push(format!(
+                "Order {} on driver {} / vehicle {}: {}",
+                order.id,
+                driver.id,
+                vehicle.id,
+                rejection.description()
+            ));
+        }
+        if !state.fleet_allows(vehicle, spacing) {
+            violations.push(format!(
+                "Order {} on vehicle {}: {}",
+                order.id,
+                vehicle.id,
+                Rejection::FleetLimit.description()
+            ));
+        }
+        state.assign_order(order, driver, vehicle, slot);
+    }
+
+    for order in orders.iter().filter(|o| o.crew_size() > 1) {
+        let assigned = state
+            .assignments
+            .iter()
+            .filter(|a| a.order_id == order.id)
+            .map(|a| state.crew_count(a))
+            .max();
+        if let Some(assigned) = assigned.filter(|&count| count < order.crew_size()) {
+            violations.push(format!(
+                "Order {}: needs {} drivers together, {} assigned",
+                order.id,
+                order.crew_size(),
+                assigned
+            ));
+        }
+    }
+
+    ScheduleEvaluation {
+        score: state.calculate_score(&priority_map, drivers, vehicles, orders, &index, config),
+        violations,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fixtures::{assignment, at, driver, order, vehicle};
+    use super::super::Break;
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn planned(
+        order_id: &str,
+        driver_id: &str,
+        vehicle_id: &str,
+        slot: (NaiveDateTime, NaiveDateTime),
+    ) -> PlannedAssignment {
+        PlannedAssignment::from(&assignment(order_id, driver_id, vehicle_id, slot))
+    }
+
+    // Flexible order: 60 minutes anywhere in 08:00-12:00
+    fn flexible(id: &str) -> Order {
+        Order {
+            duration_minutes: Some(60),
+            latest_end: Some(at(12, 0)),
+            ..order(id, at(8, 0), at(9, 0))
+        }
+    }
+
+    #[test]
+    fn feasible_plan_has_no_violations() {
+        let config = SolverConfig {
+            mandatory_break_minutes: 0,
+            ..SolverConfig::default()
+        };
+        let evaluation = evaluate_schedule(
+            &[driver("d1")],
+            &[vehicle("v1")],
+            &[flexible("o1"), flexible("o2")],
+            &[
+                planned("o2", "d1", "v1", (at(10, 0), at(11, 0))),
+                planned("o1", "d1", "v1", (at(8, 0), at(9, 0))),
+            ],
+            &config,
+        );
+        assert!(
+            evaluation.violations.is_empty(),
+            "{:?}",
+            evaluation.violations
+        );
+        assert_eq!(evaluation.score, 2.0);
+    }
+
+    #[test]
+    fn checks_assignments_at_their_given_times() {
+        // o2 would fit later in its window, but the plan puts it on top of o1
+        let evaluation = evaluate_schedule(
+            &[driver("d1")],
+            &[vehicle("v1")],
+            &[flexible("o1"), flexible("o2")],
+            &[
+                planned("o1", "d1", "v1", (at(8, 0), at(9, 0))),
+                planned("o2", "d1", "v1", (at(8, 30), at(9, 30))),
+            ],
+            &SolverConfig::default(),
+        );
+        assert_eq!(
+            evaluation.violations,
+            vec!["Order o2 on driver d1 / vehicle v1: overlaps another order"]
+        );
+
+        let evaluation = evaluate_schedule(
+            &[driver("d1")],
+            &[vehicle("v1")],
+            &[flexible("o1")],
+            &[planned("o1", "d1", "v1", (at(11, 30), at(12, 30)))],
+            &SolverConfig::default(),
+        );
+        assert_eq!(evaluation.violations.len(), 1);
+        assert!(evaluation.violations[0].contains("outside its time window"));
+    }
+
+    #[test]
+    fn reports_a_break_hit_by_the_given_slot() {
+        let on_break = Driver {
+            breaks: Some(vec![Break {
+                from: at(12, 0),
+                to: at(12, 30),
+            }]),
+            ..driver("d1")
+        };
+        let evaluation = evaluate_schedule(
+            &[on_break],
+            &[vehicle("v1")],
+            &[order("o1", at(11, 45), at(12, 15))],
+            &[planned("o1", "d1", "v1", (at(11, 45), at(12, 15)))],
+            &SolverConfig::default(),
+        );
+        assert_eq!(
+            evaluation.violations,
+            vec!["Order o1 on driver d1 / vehicle v1: overlaps a driver break"]
+        );
+    }
+
+    #[test]
+    fn scores_the_given_times() {
+        let config = SolverConfig {
+            earliness_bonus: 1.0,
+            ..SolverConfig::default()
+        };
+        let evaluate = |start| {
+            evaluate_schedule(
+                &[driver("d1")],
+                &[vehicle("v1")],
+                &[flexible("o1")],
+                &[planned(
+                    "o1",
+                    "d1",
+                    "v1",
+                    (start, start + chrono::Duration::hours(1)),
+                )],
+                &config,
+            )
+        };
+        let early = evaluate(at(8, 0));
+        let late = evaluate(at(11, 0));
+        assert!(early.violations.is_empty() && late.violations.is_empty());
+        assert_eq!(early.score, 2.0);
+        assert_eq!(late.score, 1.0);
+    }
+
+    #[test]
+    fn missing_times_are_derived_and_the_epoch_is_a_time_like_any_other() {
+        let untimed = |start_time, end_time| PlannedAssignment {
+            start_time,
+            end_time,
+            ..planned("o1", "d1", "v1", (at(8, 0), at(9, 0)))
+        };
+        let evaluate = |planned: PlannedAssignment, orders: &[Order]| {
+            evaluate_schedule(
+                &[driver("d1")],
+                &[vehicle("v1")],
+                orders,
+                &[planned],
+                &SolverConfig {
+                    earliness_bonus: 1.0,
+                    ..SolverConfig::default()
+                },
+            )
+        };
+        let orders = [flexible("o1")];
+        // No times start the order with its window, one time fixes the other by the duration
+        assert_eq!(evaluate(untimed(None, None), &orders).score, 2.0);
+        let late = evaluate(untimed(Some(at(11, 0)), None), &orders);
+        assert!(late.violations.is_empty(), "{:?}", late.violations);
+        assert_eq!(late.score, 1.0);
+        assert_eq!(evaluate(untimed(None, Some(at(12, 0))), &orders).score, 1.0);
+
+        // An order window at the epoch, the given 00:30 start must not be read as missing
+        let epoch = NaiveDateTime::default();
+        let orders = [Order {
+            duration_minutes: Some(60),
+            latest_end: Some(epoch + chrono::Duration::hours(2)),
+            ..order("o1", epoch, epoch + chrono::Duration::hours(1))
+        }];
+        let at_epoch = evaluate(
+            untimed(Some(epoch), Some(epoch + chrono::Duration::hours(1))),
+            &orders,
+        );
+        let half_past = evaluate(
+            untimed(
+                Some(epoch + chrono::Duration::minutes(30)),
+                Some(epoch + chrono::Duration::minutes(90)),
+            ),
+            &orders,
+        );
+        assert!(at_epoch.violations.is_empty() && half_past.violations.is_empty());
+        assert_eq!(at_epoch.score, 2.0);
+        assert_eq!(half_past.score, 1.5);
+    }
+}
@@ -0,0 +1,248 @@
+use super::{
+    assigned_piece, can_assign, Assignment, Driver, Order, ProblemIndex, SolverConfig, SolverState,
+    Spacing, Vehicle,
+};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ResourcePair {
+    pub driver_id: String,
+    pub vehicle_id: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct OrderFeasibility {
+    pub order_id: String,
+    pub feasible_pairs: Vec<ResourcePair>,
+    // No driver and vehicle can take the order even with nothing else scheduled
+    pub infeasible: bool,
+}
+
+// Lists, for every order on its own, the (driver, vehicle) pairs that could take it with empty
+// schedules, by the same rules as feasible_in_isolation. A split order only needs the vehicle to
+// carry a piece of it, a crew order enough other drivers for that vehicle. Independent of the
+// GA, so it is cheap enough to run before solving.
+pub fn order_feasibility(
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    config: &SolverConfig,
+) -> Vec<OrderFeasibility> {
+    let spacing = Spacing::from_config(config);
+    orders
+        .iter()
+        .map(|order| {
+            let mut crewed = HashSet::new();
+            for vehicle in vehicles {
+                let piece = isolated_piece(order, vehicle);
+                let able: Vec<&str> = drivers
+                    .iter()
+                    .filter(|driver| fits_alone(&piece, driver, vehicle, spacing))
+                    .map(|driver| driver.id.as_str())
+                    .collect();
+                if able.len() >= order.crew_size() {
+                    crewed.extend(able.into_iter().map(|id| (id, vehicle.id.as_str())));
+                }
+            }
+            let mut feasible_pairs = Vec::new();
+            for driver in drivers {
+                for vehicle in vehicles {
+                    if crewed.contains(&(driver.id.as_str(), vehicle.id.as_str())) {
+                        feasible_pairs.push(ResourcePair {
+                            driver_id: driver.id.clone(),
+                            vehicle_id: vehicle.id.clone(),
+                        });
+                    }
+                }
+            }
+            OrderFeasibility {
+                order_id: order.id.clone(),
+                infeasible: feasible_pairs.is_empty(),
+                feasible_pairs,
+            }
+        })
+        .collect()
+}
+
+// Lists the (driver, vehicle) pairs, other than the current one, that could take the order with
+// every other assignment of current_assignments held fixed at its scheduled time. The order is
+// checked whole, for a crew order the pair is the vehicle and one of its drivers. An unknown
+// order id gives no options.
+pub fn reassignment_options(
+    order_id: &str,
+    current_assignments: &[Assignment],
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    config: &SolverConfig,
+) -> Vec<ResourcePair> {
+    let spacing = Spacing::from_config(config);
+    let index = ProblemIndex::new(drivers, vehicles, orders);
+    let Some(&order) = index.orders.get(order_id) else {
+        return Vec::new();
+    };
+
+    let mut state = SolverState::new(drivers, vehicles);
+    let mut current_pairs = Vec::new();
+    for assignment in current_assignments {
+        if assignment.order_id == order_id {
+            current_pairs.push((
+                assignment.driver_id.as_str(),
+                assignment.vehicle_id.as_str(),
+            ));
+            continue;
+        }
+        let (Some(&other), Some(&driver), Some(&vehicle)) = (
+            index.orders.get(assignment.order_id.as_str()),
+            index.drivers.get(assignment.driver_id.as_str()),
+            index.vehicles.get(assignment.vehicle_id.as_str()),
+        ) else {
+            continue;
+        };
+        state.assign_order(
+            &assigned_piece(other, assignment),
+            driver,
+            vehicle,
+            (assignment.start_time, assignment.end_time),
+        );
+    }
+
+    let mut options = Vec::new();
+    for driver in drivers {
+        for vehicle in vehicles {
+            if current_pairs.contains(&(driver.id.as_str(), vehicle.id.as_str()))
+                || !state.fleet_allows(vehicle, spacing)
+            {
+                continue;
+            }
+            let driver_schedule = &state.driver_schedules[&driver.id];
+            let vehicle_schedule = &state.vehicle_schedules[&vehicle.id];
+            // A crew order also needs enough other drivers free to join at that slot
+            let crewed = |slot| {
+                order.crew_size() == 1
+                    || state
+                        .crew_for(order, driver, vehicle, slot, drivers, spacing)
+                        .is_some()
+            };
+            if index
+                .can_assign(
+                    order,
+                    driver,
+                    vehicle,
+                    driver_schedule,
+                    vehicle_schedule,
+                    spacing,
+                )
+                .is_some_and(crewed)
+            {
+                options.push(ResourcePair {
+                    driver_id: driver.id.clone(),
+                    vehicle_id: vehicle.id.clone(),
+                });
+            }
+        }
+    }
+    options
+}
+
+// Whether any pair could take the order with nothing else scheduled. A split order only needs
+// some vehicle to carry a piece of it, a crew order enough drivers for one vehicle.
+pub(super) fn feasible_in_isolation(
+    order: &Order,
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    spacing: Spacing,
+) -> bool {
+    vehicles.iter().any(|vehicle| {
+        let piece = isolated_piece(order, vehicle);
+        drivers
+            .iter()
+            .filter(|driver| fits_alone(&piece, driver, vehicle, spacing))
+            .take(order.crew_size())
+            .count()
+            == order.crew_size()
+    })
+}
+
+// The part of the order the vehicle would carry, all of it unless the order is splittable
+fn isolated_piece<'o>(order: &'o Order, vehicle: &Vehicle) -> Cow<'o, Order> {
+    if order.splittable && vehicle.max_weight > 0.0 {
+        Cow::Owned(order.part(order.weight.min(vehicle.max_weight)))
+    } else {
+        Cow::Borrowed(order)
+    }
+}
+
+// Whether the driver could take the piece on the vehicle with nothing else scheduled
+fn fits_alone(piece: &Order, driver: &Driver, vehicle: &Vehicle, spacing: Spacing) -> bool {
+    can_assign(piece, driver, vehicle, &[], &[], spacing).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fixtures::{at, driver, order, vehicle};
+    use super::*;
+
+    fn pairs(feasibility: &OrderFeasibility) -> Vec<(&str, &str)> {
+        feasibility
+            .feasible_pairs
+            .iter()
+            .map(|pair| (pair.driver_id.as_str(), pair.vehicle_id.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn an_oversized_splittable_order_is_feasible_by_pieces() {
+        let oversized = Order {
+            weight: 150.0,
+            ..order("o1", at(8, 0), at(9, 0))
+        };
+        let splittable = Order {
+            splittable: true,
+            ..oversized.clone()
+        };
+        let feasibility = order_feasibility(
+            &[driver("d1")],
+            &[vehicle("v1")],
+            &[oversized, splittable],
+            &SolverConfig::default(),
+        );
+        assert!(feasibility[0].infeasible);
+        assert!(!feasibility[1].infeasible);
+        assert_eq!(pairs(&feasibility[1]), vec![("d1", "v1")]);
+    }
+
+    #[test]
+    fn a_crew_order_needs_enough_drivers_for_the_vehicle() {
+        let crew = Order {
+            required_drivers: Some(2),
+            ..order("o1", at(8, 0), at(9, 0))
+        };
+        let restricted = |id: &str, vehicle_id: &str| Driver {
+            allowed_vehicle_ids: Some(vec![vehicle_id.to_string()]),
+            ..driver(id)
+        };
+        let feasibility = order_feasibility(
+            &[
+                restricted("d1", "v1"),
+                restricted("d2", "v1"),
+                restricted("d3", "v2"),
+            ],
+            &[vehicle("v1"), vehicle("v2")],
+            std::slice::from_ref(&crew),
+            &SolverConfig::default(),
+        );
+        // d3 alone could drive v2, but nobody can join them
+        assert_eq!(pairs(&feasibility[0]), vec![("d1", "v1"), ("d2", "v1")]);
+
+        let feasibility = order_feasibility(
+            &[restricted("d1", "v1"), restricted("d3", "v2")],
+            &[vehicle("v1"), vehicle("v2")],
+            &[crew],
+            &SolverConfig::default(),
+        );
+        assert!(feasibility[0].infeasible);
+    }
+}
@@ -0,0 +1,173 @@
+// Builders shared by the solver tests, every optional field is left unset
+use super::{Assignment, Break, Driver, Order, SchedulingResponse, SolverMetrics, Vehicle};
+use chrono::{Duration as ChronoDuration, NaiveDate, NaiveDateTime};
+use rand::prelude::*;
+
+// The given time of day on 2024-01-01
+pub fn at(hour: u32, minute: u32) -> NaiveDateTime {
+    on(1, hour, minute)
+}
+
+// The given time of day on that day of January 2024
+pub fn on(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 1, day)
+        .unwrap()
+        .and_hms_opt(hour, minute, 0)
+        .unwrap()
+}
+
+pub fn driver(id: &str) -> Driver {
+    Driver {
+        id: id.to_string(),
+        breaks: None,
+        recurring_breaks: None,
+        shifts: None,
+        preference: None,
+        skills: None,
+        max_orders: None,
+        allowed_vehicle_ids: None,
+        max_work_minutes: None,
+    }
+}
+
+// Carries 100 of weight and no volume
+pub fn vehicle(id: &str) -> Vehicle {
+    Vehicle {
+        id: id.to_string(),
+        tags: None,
+        max_weight: 100.0,
+        max_volume: None,
+        volume_unlimited: None,
+        unavailable: None,
+        min_fill_pct: None,
+        min_break_minutes: None,
+        max_distance_km: None,
+    }
+}
+
+// Weighs 10, occupies exactly [start_time, end_time]
+pub fn order(id: &str, start_time: NaiveDateTime, end_time: NaiveDateTime) -> Order {
+    Order {
+        id: id.to_string(),
+        start_time,
+        end_time,
+        priority: None,
+        tags: None,
+        weight: 10.0,
+        volume: None,
+        duration_minutes: None,
+        earliest_start: None,
+        latest_end: None,
+        must_follow: None,
+        splittable: false,
+        required: None,
+        lat: None,
+        lon: None,
+        group_id: None,
+        same_vehicle_group: None,
+        service_minutes: None,
+        allowed_driver_ids: None,
+        allowed_vehicle_ids: None,
+        required_drivers: None,
+    }
+}
+
+pub fn assignment(
+    order_id: &str,
+    driver_id: &str,
+    vehicle_id: &str,
+    slot: (NaiveDateTime, NaiveDateTime),
+) -> Assignment {
+    Assignment {
+        order_id: order_id.to_string(),
+        driver_id: driver_id.to_string(),
+        vehicle_id: vehicle_id.to_string(),
+        start_time: slot.0,
+        end_time: slot.1,
+        weight: None,
+        relaxed: false,
+        pinned: false,
+    }
+}
+
+// Response holding only the given assignments, as an earlier solve or a client would send it
+pub fn response(assignments: Vec<Assignment>) -> SchedulingResponse {
+    SchedulingResponse {
+        assignments,
+        score: 0.0,
+        unassigned_order_ids: Vec::new(),
+        unassigned_reasons: Vec::new(),
+        required_unassigned: Vec::new(),
+        relaxed_assignments: Vec::new(),
+        vehicle_utilization: Vec::new(),
+        breakdown: None,
+        schedule_adjustment: None,
+        run_scores: None,
+        pareto_front: None,
+        population: None,
+        cancelled: false,
+        rejection_stats: None,
+        seed_used: 0,
+        warnings: Vec::new(),
+        metrics: SolverMetrics::default(),
+    }
+}
+
+// Random problem on one day, seeded so failures can be replayed. Mixes fixed and flexible
+// windows, tags matched by some vehicles and driver skills, driver breaks, service times and
+// coordinates, so most pairs are feasible but schedules still conflict.
+pub fn random_instance(
+    order_count: usize,
+    driver_count: usize,
+    vehicle_count: usize,
+    seed: u64,
+) -> (Vec<Driver>, Vec<Vehicle>, Vec<Order>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let drivers = (0..driver_count)
+        .map(|i| {
+            let lunch = at(12, 0) + ChronoDuration::minutes(rng.gen_range(0..4) * 15);
+            Driver {
+                breaks: rng.gen_bool(0.5).then(|| {
+                    vec![Break {
+                        from: lunch,
+                        to: lunch + ChronoDuration::minutes(30),
+                    }]
+                }),
+                skills: rng
+                    .gen_bool(0.3)
+                    .then(|| vec!["cold".to_string()])
+                    .or_else(|| rng.gen_bool(0.5).then(Vec::new)),
+                preference: rng
+                    .gen_bool(0.5)
+                    .then(|| format!("v{}", rng.gen_range(0..vehicle_count))),
+                ..driver(&format!("d{}", i))
+            }
+        })
+        .collect();
+    let vehicles = (0..vehicle_count)
+        .map(|i| Vehicle {
+            tags: (i % 2 == 0).then(|| vec!["cold".to_string()]),
+            max_weight: rng.gen_range(40.0..120.0),
+            ..vehicle(&format!("v{}", i))
+        })
+        .collect();
+    let orders = (0..order_count)
+        .map(|i| {
+            let start = at(6, 0) + ChronoDuration::minutes(rng.gen_range(0..48) * 15);
+            let end = start + ChronoDuration::minutes(rng.gen_range(2..8) * 15);
+            let flexible = rng.gen_bool(0.3);
+            Order {
+                priority: Some(rng.gen_range(1..5) as f64),
+                tags: rng.gen_bool(0.2).then(|| vec!["cold".to_string()]),
+                weight: rng.gen_range(5.0..60.0),
+                duration_minutes: flexible.then(|| (end - start).num_minutes()),
+                latest_end: flexible.then(|| end + ChronoDuration::minutes(90)),
+                service_minutes: rng.gen_bool(0.2).then_some(10),
+                lat: Some(52.0 + rng.gen_range(0.0..0.2)),
+                lon: Some(13.0 + rng.gen_range(0.0..0.2)),
+                ..order(&format!("o{}", i), start, end)
+            }
+        })
+        .collect();
+    (drivers, vehicles, orders)
+}
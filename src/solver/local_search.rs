@@ -0,0 +1,188 @@
+use super::{
+    assigned_piece, feasible_placements, first_infeasible_assignment, repair_constraints,
+    Assignment, Driver, Order, ProblemIndex, SolverConfig, SolverState, Spacing, Stopwatch,
+    Vehicle,
+};
+use std::collections::HashMap;
+
+// Hill climb on a finished plan: exchanges the driver and vehicle of two assignments, places
+// any order the exchange made room for, and keeps the result only when the score strictly
+// improves. Passes repeat until none improves or config.local_search_budget_ms runs out, the
+// budget is ignored together with the GA's under ignore_time_budget.
+#[allow(clippy::too_many_arguments)]
+pub fn local_search(
+    state: &mut SolverState,
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    index: &ProblemIndex,
+    priority_map: &HashMap<String, f64>,
+    config: &SolverConfig,
+) {
+    let spacing = Spacing::from_config(config);
+    let stopwatch = Stopwatch::start();
+    let initial_score = state.score;
+
+    'search: loop {
+        for i in 0..state.assignments.len() {
+            for j in i + 1..state.assignments.len() {
+                if !config.ignore_time_budget
+                    && stopwatch.elapsed_ms() >= config.local_search_budget_ms
+                {
+                    break 'search;
+                }
+                let Some(candidate) = swapped(
+                    state,
+                    (i, j),
+                    drivers,
+                    vehicles,
+                    orders,
+                    index,
+                    priority_map,
+                    config,
+                    spacing,
+                ) else {
+                    continue;
+                };
+                // Indices shift once the plan changes, start the next pass from the top
+                if candidate.score > state.score {
+                    *state = candidate;
+                    continue 'search;
+                }
+            }
+        }
+        break;
+    }
+
+    debug_assert!(state.score >= initial_score);
+    debug_assert_eq!(
+        first_infeasible_assignment(state, drivers, vehicles, index, config),
+        None
+    );
+}
+
+// The plan with the assignments at i and j trading their driver and vehicle, each at the
+// earliest slot the pair offers, or None when either cannot move
+#[allow(clippy::too_many_arguments)]
+fn swapped(
+    state: &SolverState,
+    (i, j): (usize, usize),
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    index: &ProblemIndex,
+    priority_map: &HashMap<String, f64>,
+    config: &SolverConfig,
+    spacing: Spacing,
+) -> Option<SolverState> {
+    let (a, b) = (&state.assignments[i], &state.assignments[j]);
+    // Relaxed assignments only fit because they bend the limits, moving them would undo that.
+    // Pinned ones are fixed by the caller, and a crew member cannot move without the others.
+    let in_crew =
+        |assignment: &Assignment| index.orders[assignment.order_id.as_str()].crew_size() > 1;
+    if a.relaxed
+        || b.relaxed
+        || a.pinned
+        || b.pinned
+        || in_crew(a)
+        || in_crew(b)
+        || (a.driver_id == b.driver_id && a.vehicle_id == b.vehicle_id)
+    {
+        return None;
+    }
+
+    let mut candidate = state.clone();
+    // Remove the later one first so i still points at the same assignment
+    let second = candidate.unassign(j);
+    let first = candidate.unassign(i);
+    for (moved, target) in [(&first, &second), (&second, &first)] {
+        let piece = assigned_piece(index.orders[moved.order_id.as_str()], moved);
+        let (driver, vehicle, slot) =
+            feasible_placements(&candidate, &piece, drivers, vehicles, index, spacing)
+                .into_iter()
+                .find(|(driver, vehicle, _)| {
+                    driver.id == target.driver_id && vehicle.id == target.vehicle_id
+                })?;
+        candidate.assign_order(&piece, driver, vehicle, slot);
+    }
+
+    // The exchange can free a slot for an order nothing could take before
+    for order in orders {
+        if candidate.assignments.iter().any(|a| a.order_id == order.id) {
+            continue;
+        }
+        if let Some(&(driver, vehicle, slot)) =
+            feasible_placements(&candidate, order, drivers, vehicles, index, spacing).first()
+        {
+            candidate.place(order, driver, vehicle, slot, drivers, spacing);
+        }
+    }
+
+    repair_constraints(&mut candidate, orders);
+    candidate.score =
+        candidate.calculate_score(priority_map, drivers, vehicles, orders, index, config);
+    Some(candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fixtures::random_instance;
+    use super::super::{build_priority_map, initialize_random_state, InitOrder};
+    use super::*;
+    use rand::prelude::*;
+
+    #[test]
+    fn local_search_never_lowers_the_score_or_breaks_feasibility() {
+        let config = SolverConfig {
+            local_search: true,
+            ignore_time_budget: true,
+            ..SolverConfig::default()
+        };
+        let mut improved = 0;
+        for seed in 0..20 {
+            let (drivers, vehicles, orders) = random_instance(30, 4, 3, seed);
+            let index = ProblemIndex::new(&drivers, &vehicles, &orders);
+            let priority_map = build_priority_map(&orders, &config);
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut state = initialize_random_state(
+                &drivers,
+                &vehicles,
+                &orders,
+                &index,
+                &priority_map,
+                &config,
+                InitOrder::Shuffle,
+                &mut rng,
+            );
+            state.score =
+                state.calculate_score(&priority_map, &drivers, &vehicles, &orders, &index, &config);
+            let before = state.score;
+
+            local_search(
+                &mut state,
+                &drivers,
+                &vehicles,
+                &orders,
+                &index,
+                &priority_map,
+                &config,
+            );
+            assert!(state.score >= before, "seed {}", seed);
+            assert_eq!(
+                state.score,
+                state.calculate_score(&priority_map, &drivers, &vehicles, &orders, &index, &config)
+            );
+            assert_eq!(
+                first_infeasible_assignment(&state, &drivers, &vehicles, &index, &config),
+                None,
+                "seed {}",
+                seed
+            );
+            if state.score > before {
+                improved += 1;
+            }
+        }
+        // Random plans leave room, the property must not hold only because nothing moved
+        assert!(improved > 0);
+    }
+}
@@ -0,0 +1,91 @@
+use super::{
+    assigned_piece, build_priority_map, repair_constraints, Driver, Order, ProblemIndex,
+    SchedulingResponse, SolverConfig, SolverState, Spacing, Vehicle,
+};
+use serde::Serialize;
+use std::collections::HashSet;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MergeResult {
+    pub response: SchedulingResponse,
+    // Orders from either input that could not be kept in the combined plan
+    pub conflicting_order_ids: Vec<String>,
+}
+
+// Combines two solutions, typically of disjoint order subsets, into one plan. Assignments are
+// replayed at their scheduled times, first, then second, and every one that clashes with what
+// is already placed is dropped. Relaxed assignments are held to the strict limits here.
+pub fn merge_schedules(
+    first: &SchedulingResponse,
+    second: &SchedulingResponse,
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    config: &SolverConfig,
+) -> MergeResult {
+    let spacing = Spacing::from_config(config);
+    let priority_map = build_priority_map(orders, config);
+    let index = ProblemIndex::new(drivers, vehicles, orders);
+    let mut state = SolverState::new(drivers, vehicles);
+    let mut conflicts: HashSet<&str> = HashSet::new();
+    let mut placed: HashSet<&str> = HashSet::new();
+
+    for assignment in first.assignments.iter().chain(&second.assignments) {
+        let order_id = assignment.order_id.as_str();
+        let (Some(&order), Some(&driver), Some(&vehicle)) = (
+            index.orders.get(order_id),
+            index.drivers.get(assignment.driver_id.as_str()),
+            index.vehicles.get(assignment.vehicle_id.as_str()),
+        ) else {
+            conflicts.insert(order_id);
+            continue;
+        };
+        // Both inputs agreeing on the same placement is not a conflict
+        let same_placement = state.assignments.iter().any(|a| {
+            a.order_id == assignment.order_id
+                && a.driver_id == assignment.driver_id
+                && a.vehicle_id == assignment.vehicle_id
+                && (a.start_time, a.end_time) == (assignment.start_time, assignment.end_time)
+                && a.weight == assignment.weight
+        });
+        if same_placement {
+            continue;
+        }
+        // Only split and crew orders may be spread over several assignments, incomplete crews
+        // are dropped by repair_constraints below
+        let duplicate = state.assignments.iter().any(|a| a.order_id == order.id);
+        if duplicate && !order.splittable && order.crew_size() == 1 {
+            conflicts.insert(order_id);
+            continue;
+        }
+
+        let piece = assigned_piece(order, assignment);
+        let slot = (assignment.start_time, assignment.end_time);
+        if state.slot_fits(&piece, driver, vehicle, slot, spacing) {
+            state.assign_order(&piece, driver, vehicle, slot);
+            placed.insert(order_id);
+        } else {
+            conflicts.insert(order_id);
+        }
+    }
+
+    // Crews, groups and precedence can fall apart when only some of their members were kept
+    repair_constraints(&mut state, orders);
+    let kept: HashSet<&str> = state
+        .assignments
+        .iter()
+        .map(|a| a.order_id.as_str())
+        .collect();
+    conflicts.extend(placed.difference(&kept).copied());
+    state.score = state.calculate_score(&priority_map, drivers, vehicles, orders, &index, config);
+
+    let conflicting_order_ids = orders
+        .iter()
+        .filter(|o| conflicts.contains(o.id.as_str()))
+        .map(|o| o.id.clone())
+        .collect();
+    MergeResult {
+        response: state.to_response(&priority_map, drivers, vehicles, orders, &index, config),
+        conflicting_order_ids,
+    }
+}
@@ -1,24 +1,87 @@
-use chrono::{Duration as ChronoDuration, NaiveDateTime};
+use chrono::{Duration as ChronoDuration, NaiveDateTime, NaiveTime};
+use fnv::FnvHasher;
 use rand::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
-use serde_with::TimestampMilliSeconds;
-use std::collections::{HashMap, HashSet};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::Hasher;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::sync::OnceLock;
+
+mod compact;
+mod config;
+mod dedupe;
+mod error;
+mod evaluate;
+mod feasibility;
+#[cfg(test)]
+mod fixtures;
+mod local_search;
+mod merge;
+mod pareto;
+mod rebalance;
+mod resolve;
+mod timestamp;
+mod validation;
+use compact::compact_schedule;
+pub use config::{
+    CrossoverStrategy, InitOrder, PreferenceMode, PriorityTransform, Relaxation, SelectionStrategy,
+    SolverConfig,
+};
+use dedupe::{collapse_duplicates, expand_duplicates};
+pub use error::{reject_unfinished, SolverError};
+pub use evaluate::{evaluate_schedule, ScheduleEvaluation};
+use feasibility::feasible_in_isolation;
+pub use feasibility::{order_feasibility, reassignment_options, OrderFeasibility, ResourcePair};
+use local_search::local_search;
+pub use merge::{merge_schedules, MergeResult};
+use pareto::ParetoArchive;
+pub use pareto::{ParetoObjectives, ParetoSolution};
+use rebalance::rebalance_drivers;
+pub use resolve::{resolve_after_removal, ResolveResponse};
+use timestamp::FlexibleTimestamp;
+pub use validation::{
+    input_warnings, validate_assignment_structure, validate_inputs, ValidationError,
+    ValidationWarning,
+};
 
 #[serde_as]
 #[derive(Clone, Debug, Deserialize)]
 pub struct Break {
-    #[serde_as(as = "TimestampMilliSeconds<i64>")]
+    #[serde_as(as = "FlexibleTimestamp")]
     pub from: NaiveDateTime,
-    #[serde_as(as = "TimestampMilliSeconds<i64>")]
+    #[serde_as(as = "FlexibleTimestamp")]
     pub to: NaiveDateTime,
 }
 
+// Break taken every day at the same time of day. It may run past midnight into the next day,
+// but starts within the day (start_minute_of_day below 1440) and lasts at most a day.
+#[derive(Clone, Debug, Deserialize)]
+pub struct DailyBreak {
+    pub start_minute_of_day: u32,
+    pub duration_minutes: u32,
+}
+
+#[serde_as]
 #[derive(Clone, Debug, Deserialize)]
 pub struct Driver {
     pub id: String,
     pub breaks: Option<Vec<Break>>,
+    // Expanded for every day an order touches, on top of breaks
+    pub recurring_breaks: Option<Vec<DailyBreak>>,
+    // Working periods as [from, to] pairs, always available when absent
+    #[serde_as(as = "Option<Vec<(FlexibleTimestamp, FlexibleTimestamp)>>")]
+    #[serde(default)]
+    pub shifts: Option<Vec<(NaiveDateTime, NaiveDateTime)>>,
     pub preference: Option<String>,
+    pub skills: Option<Vec<String>>,
+    pub max_orders: Option<usize>,
+    // Vehicles this driver is licensed for, unrestricted when absent
+    pub allowed_vehicle_ids: Option<Vec<String>>,
+    // Soft daily budget of assigned order minutes, going over is penalized but allowed
+    pub max_work_minutes: Option<i64>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -27,465 +90,6053 @@ pub struct Vehicle {
     pub tags: Option<Vec<String>>,
     pub max_weight: f64,
     pub max_volume: Option<f64>,
+    // Without a max_volume, true accepts any volume, otherwise orders with a volume are rejected
+    pub volume_unlimited: Option<bool>,
+    // Maintenance or other periods the vehicle cannot be used
+    pub unavailable: Option<Vec<Break>>,
+    // Share of max_weight (0.5 is half full) a used vehicle should carry, see config.min_fill_penalty
+    pub min_fill_pct: Option<f64>,
+    // Rest between consecutive orders on this vehicle, replacing config.mandatory_break_minutes
+    // for its schedule only. Drivers keep the global break.
+    pub min_break_minutes: Option<i64>,
+    // Longest route the vehicle may drive between its orders with coordinates, see route_km
+    pub max_distance_km: Option<f64>,
 }
 
 #[serde_as]
 #[derive(Clone, Debug, Deserialize)]
 pub struct Order {
     pub id: String,
-    #[serde_as(as = "TimestampMilliSeconds<i64>")]
+    #[serde_as(as = "FlexibleTimestamp")]
     pub start_time: NaiveDateTime,
-    #[serde_as(as = "TimestampMilliSeconds<i64>")]
+    #[serde_as(as = "FlexibleTimestamp")]
     pub end_time: NaiveDateTime,
-    pub priority: Option<u32>,
+    // Zero or negative marks an optional filler, only worth placing in capacity nothing else needs
+    pub priority: Option<f64>,
     pub tags: Option<Vec<String>>,
     pub weight: f64,
     pub volume: Option<f64>,
+    // Time actually occupied, when shorter than the window the order may be placed anywhere inside it
+    pub duration_minutes: Option<i64>,
+    #[serde_as(as = "Option<FlexibleTimestamp>")]
+    #[serde(default)]
+    pub earliest_start: Option<NaiveDateTime>,
+    #[serde_as(as = "Option<FlexibleTimestamp>")]
+    #[serde(default)]
+    pub latest_end: Option<NaiveDateTime>,
+    // Orders the same driver has to finish before this one starts
+    pub must_follow: Option<Vec<String>>,
+    // Weight may be spread over several vehicles when no single one can carry it
+    #[serde(default)]
+    pub splittable: bool,
+    // The plan is rejected downstream when a required order is not fully scheduled
+    pub required: Option<bool>,
+    // Pickup location in degrees, travel time is only accounted for when both are set
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+    // Orders sharing a group all go to the same driver or stay unassigned together
+    pub group_id: Option<String>,
+    // Orders sharing a vehicle group all ride on one vehicle, independent of group_id
+    pub same_vehicle_group: Option<String>,
+    // Loading or unloading after end_time that still ties up the driver and vehicle
+    pub service_minutes: Option<i64>,
+    // Pins the order to these drivers or vehicles on top of the tag and skill checks
+    pub allowed_driver_ids: Option<Vec<String>>,
+    pub allowed_vehicle_ids: Option<Vec<String>>,
+    // Distinct drivers the order needs at once on its one vehicle, one assignment each. Such
+    // crew orders cannot be split or pinned.
+    pub required_drivers: Option<usize>,
 }
 
-#[derive(Clone, Debug, Serialize)]
+impl Order {
+    fn crew_size(&self) -> usize {
+        self.required_drivers.unwrap_or(1).max(1)
+    }
+
+    fn is_filler(&self) -> bool {
+        self.priority.unwrap_or(1.0) <= 0.0
+    }
+
+    // Service window, defaults to [start_time, end_time]
+    fn window(&self) -> (NaiveDateTime, NaiveDateTime) {
+        (
+            self.earliest_start.unwrap_or(self.start_time),
+            self.latest_end.unwrap_or(self.end_time),
+        )
+    }
+
+    // Piece of a splittable order carrying the given weight, volume is split proportionally
+    fn part(&self, weight: f64) -> Order {
+        let share = if self.weight > 0.0 {
+            weight / self.weight
+        } else {
+            1.0
+        };
+        Order {
+            weight,
+            volume: self.volume.map(|v| v * share),
+            ..self.clone()
+        }
+    }
+
+    // Share of the order an assignment covers, 1.0 unless the order was split or needs a crew,
+    // where every driver's assignment covers an equal part
+    fn coverage(&self, assignment: &Assignment) -> f64 {
+        match assignment.weight {
+            Some(weight) if self.splittable && self.weight > 0.0 => weight / self.weight,
+            _ => 1.0 / self.crew_size() as f64,
+        }
+    }
+
+    fn location(&self) -> Option<(f64, f64)> {
+        self.lat.zip(self.lon)
+    }
+
+    // Time the driver and vehicle are tied up for when the order runs in the slot
+    fn occupied(&self, slot: (NaiveDateTime, NaiveDateTime)) -> (NaiveDateTime, NaiveDateTime) {
+        let service = ChronoDuration::minutes(self.service_minutes.unwrap_or(0).max(0));
+        (slot.0, slot.1 + service)
+    }
+
+    // 1.0 for a start at the beginning of a flexible window down to 0.0 at the latest possible
+    // start, None when the order cannot move
+    fn earliness(&self, start: NaiveDateTime) -> Option<f64> {
+        let (window_start, window_end) = self.window();
+        let slack = (window_end - self.duration() - window_start).num_seconds();
+        if slack <= 0 {
+            return None;
+        }
+        let delay = (start - window_start).num_seconds() as f64 / slack as f64;
+        Some((1.0 - delay).clamp(0.0, 1.0))
+    }
+
+    fn duration(&self) -> ChronoDuration {
+        match self.duration_minutes {
+            Some(minutes) => ChronoDuration::minutes(minutes),
+            None => self.end_time - self.start_time,
+        }
+    }
+}
+
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Assignment {
     pub order_id: String,
     pub driver_id: String,
     pub vehicle_id: String,
+    // Scheduled slot inside the order window, inputs that may leave it out use PlannedAssignment
+    #[serde_as(as = "FlexibleTimestamp")]
+    pub start_time: NaiveDateTime,
+    #[serde_as(as = "FlexibleTimestamp")]
+    pub end_time: NaiveDateTime,
+    // Weight carried by this assignment, only set for splittable orders
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f64>,
+    // Placed by the relaxation pass, reported through relaxed_assignments
+    #[serde(skip)]
+    pub relaxed: bool,
+    // Fixed through config.pinned, never moved or dropped by the GA
+    #[serde(skip)]
+    pub pinned: bool,
 }
 
-#[derive(Clone, Debug, Serialize)]
+// An assignment given as input, to evaluate_schedule or as a pin. Either time may be left out:
+// the other one is then moved by the order's duration, and with neither the order starts at the
+// beginning of its window.
+#[serde_as]
+#[derive(Clone, Debug, Deserialize)]
+pub struct PlannedAssignment {
+    pub order_id: String,
+    pub driver_id: String,
+    pub vehicle_id: String,
+    #[serde_as(as = "Option<FlexibleTimestamp>")]
+    #[serde(default)]
+    pub start_time: Option<NaiveDateTime>,
+    #[serde_as(as = "Option<FlexibleTimestamp>")]
+    #[serde(default)]
+    pub end_time: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub weight: Option<f64>,
+}
+
+impl PlannedAssignment {
+    // The assignment as booked for the order, with both times filled in
+    fn resolve(&self, order: &Order) -> Assignment {
+        let duration = order.duration();
+        let (start_time, end_time) = match (self.start_time, self.end_time) {
+            (Some(start), Some(end)) => (start, end),
+            (Some(start), None) => (start, start + duration),
+            (None, Some(end)) => (end - duration, end),
+            (None, None) => (order.window().0, order.window().0 + duration),
+        };
+        Assignment {
+            order_id: self.order_id.clone(),
+            driver_id: self.driver_id.clone(),
+            vehicle_id: self.vehicle_id.clone(),
+            start_time,
+            end_time,
+            weight: self.weight,
+            relaxed: false,
+            pinned: false,
+        }
+    }
+}
+
+impl From<&Assignment> for PlannedAssignment {
+    fn from(assignment: &Assignment) -> Self {
+        PlannedAssignment {
+            order_id: assignment.order_id.clone(),
+            driver_id: assignment.driver_id.clone(),
+            vehicle_id: assignment.vehicle_id.clone(),
+            start_time: Some(assignment.start_time),
+            end_time: Some(assignment.end_time),
+            weight: assignment.weight,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AssignmentScore {
+    pub order_id: String,
+    pub base_priority: f64,
+    pub preference_bonus: f64,
+    // Only earned by orders that can move inside a flexible window
+    pub earliness_bonus: f64,
+    // Earned when the order stays with its driver from config.historical_assignments
+    pub continuity_bonus: f64,
+    pub unassigned_penalty: f64,
+    pub total: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnassignedCause {
+    // No driver and vehicle could take the order even with nothing else scheduled
+    Infeasible,
+    // The order fits on its own, the resources it needs went to other orders
+    CrowdedOut,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UnassignedReason {
+    pub order_id: String,
+    pub reason: UnassignedCause,
+}
+
+// Load carried by one vehicle across all its assignments
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VehicleUtilization {
+    pub vehicle_id: String,
+    pub total_weight: f64,
+    pub total_volume: f64,
+    // Share of max_weight in percent, 0 for vehicles without capacity
+    pub weight_pct: f64,
+    pub order_count: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SchedulingResponse {
     pub assignments: Vec<Assignment>,
+    pub score: f64,
+    pub unassigned_order_ids: Vec<String>,
+    // Why each unassigned order was left out, in the same order
+    #[serde(default)]
+    pub unassigned_reasons: Vec<UnassignedReason>,
+    // Required orders left unassigned or only partly covered, the plan is infeasible if not empty
+    #[serde(default)]
+    pub required_unassigned: Vec<String>,
+    // Orders only placed by bending the break or capacity limits in config.relaxation
+    #[serde(default)]
+    pub relaxed_assignments: Vec<String>,
+    // One entry per vehicle in input order, unused vehicles included
+    #[serde(default)]
+    pub vehicle_utilization: Vec<VehicleUtilization>,
+    // Only present when the config asks to explain the score
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breakdown: Option<Vec<AssignmentScore>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule_adjustment: Option<f64>,
+    // Final score of every run, only set by solve_best_of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_scores: Option<Vec<f64>>,
+    // Non-dominated plans trading priority against balance, only set under config.return_pareto
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pareto_front: Option<Vec<ParetoSolution>>,
+    // Every individual of the final population, best first, only set under
+    // config.return_population
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub population: Option<Vec<SchedulingResponse>>,
+    // The caller stopped the solve early, the schedule is still feasible but may be improvable
+    #[serde(default)]
+    pub cancelled: bool,
+    // Rejections met while building the initial population, most frequent first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rejection_stats: Option<Vec<RejectionCount>>,
+    // Seed the run started from, passing it back as config.seed reproduces the response apart
+    // from the timings in metrics
+    #[serde(default)]
+    pub seed_used: u64,
+    // Suspicious inputs, such as orders heavier than every vehicle, the solve still ran
+    #[serde(default)]
+    pub warnings: Vec<ValidationWarning>,
+    // How the solve spent its budget, all zero on responses not produced by a solve
+    #[serde(default)]
+    pub metrics: SolverMetrics,
+}
+
+// The counts are deterministic for a seeded run. The *_ms timings are measured on the wall
+// clock, they are outside the same-seed guarantee and differ from run to run.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SolverMetrics {
+    // Generations evolved, the initial population not included
+    pub generations: u32,
+    // Partial population restarts after a stall, see config.max_restarts
+    pub restarts: u32,
+    pub wall_time_ms: f64,
+    // Building the initial population, the warm start included
+    pub initialization_ms: f64,
+    pub evolution_ms: f64,
+    pub local_search_ms: f64,
+    // Calls to can_assign made by this solve, the slot search behind every placement attempt
+    pub feasibility_checks: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RejectionCount {
+    pub reason: Rejection,
+    pub count: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ProgressUpdate {
+    pub generation: u32,
+    pub best_score: f64,
+    pub avg_score: f64,
+    pub assigned_count: usize,
+    // Estimated share of the solve done, from whichever of the generation limit, the time budget
+    // and the stall limit is closest. Never decreases during a solve and is 1.0 on the last
+    // update, unless the time budget or a cancel ends the solve between two updates.
+    pub progress_fraction: f64,
+    pub message: String,
+    // Current best schedule, only attached every config.snapshot_interval generations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<SchedulingResponse>,
+}
+
+// An order occupying a driver or vehicle
+#[derive(Clone, Debug)]
+struct ScheduleEntry {
+    order_id: String,
+    // Driver holding the vehicle for this order
+    driver_id: String,
+    start_time: NaiveDateTime,
+    end_time: NaiveDateTime,
+    location: Option<(f64, f64)>,
+}
+
+// Minimum spacing between consecutive orders of one driver or vehicle: the mandatory break plus
+// the time needed to travel between their locations. Drivers additionally need a long break
+// after max_continuous of work, a zero max_continuous disables that rule. Also carries the
+// fleet size limit and the preference mode, which callers holding the whole state check through
+// fleet_allows and preferred_vehicle_open.
+#[derive(Clone, Copy, Debug)]
+struct Spacing {
+    mandatory_break: ChronoDuration,
+    // How much of the break relaxed placements may skip, also taken off vehicle breaks
+    break_slack: ChronoDuration,
+    average_speed_kmh: f64,
+    max_continuous: ChronoDuration,
+    long_break: ChronoDuration,
+    max_vehicles_used: Option<usize>,
+    vehicle_handoff: ChronoDuration,
+    hard_preference: bool,
+    // Whether two orders of one resource may touch, end == start, see orders_touch
+    touching_allowed: bool,
+}
+
+impl Spacing {
+    fn from_config(config: &SolverConfig) -> Self {
+        Spacing {
+            mandatory_break: config.mandatory_break(),
+            break_slack: ChronoDuration::zero(),
+            average_speed_kmh: config.average_speed_kmh,
+            max_continuous: ChronoDuration::minutes(config.max_continuous_minutes),
+            long_break: ChronoDuration::minutes(config.long_break_minutes),
+            max_vehicles_used: config.max_vehicles_used,
+            vehicle_handoff: ChronoDuration::minutes(config.vehicle_handoff_minutes),
+            hard_preference: matches!(config.preference_mode, PreferenceMode::HardWhenAvailable),
+            touching_allowed: config.touching_allowed,
+        }
+    }
+
+    // Spacing for checks against the vehicle's own schedule
+    fn for_vehicle(self, vehicle: &Vehicle) -> Spacing {
+        match vehicle.min_break_minutes {
+            Some(minutes) => Spacing {
+                mandatory_break: (ChronoDuration::minutes(minutes) - self.break_slack)
+                    .max(ChronoDuration::zero()),
+                ..self
+            },
+            None => self,
+        }
+    }
+
+    // Falls back to the plain mandatory break when either location is unknown
+    fn gap(&self, from: Option<(f64, f64)>, to: Option<(f64, f64)>) -> ChronoDuration {
+        match (from, to) {
+            (Some(from), Some(to)) => {
+                let hours = haversine_km(from, to) / self.average_speed_kmh;
+                self.mandatory_break + ChronoDuration::seconds((hours * 3600.0).ceil() as i64)
+            }
+            _ => self.mandatory_break,
+        }
+    }
+}
+
+// Great-circle distance between two (lat, lon) points in degrees
+fn haversine_km(from: (f64, f64), to: (f64, f64)) -> f64 {
+    const EARTH_RADIUS_KM: f64 = 6371.0;
+    let (lat1, lon1) = (from.0.to_radians(), from.1.to_radians());
+    let (lat2, lon2) = (to.0.to_radians(), to.1.to_radians());
+    let a = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+impl ScheduleEntry {
+    fn slot(&self) -> (NaiveDateTime, NaiveDateTime) {
+        (self.start_time, self.end_time)
+    }
+}
+
+// Hooks the solver calls while running, every method defaults to doing nothing
+pub trait SolveObserver {
+    fn on_progress(&mut self, _progress: &ProgressUpdate) {}
+
+    // Polled once per generation, returning true stops with the best solution so far
+    fn should_cancel(&mut self) -> bool {
+        false
+    }
 }
 
+impl SolveObserver for () {}
+
+// Wall clock for the time budget, std::time::Instant is unavailable on wasm32-unknown-unknown
+struct Stopwatch {
+    #[cfg(target_arch = "wasm32")]
+    started_at_ms: f64,
+    #[cfg(not(target_arch = "wasm32"))]
+    started_at: std::time::Instant,
+}
+
+impl Stopwatch {
+    #[cfg(target_arch = "wasm32")]
+    fn start() -> Stopwatch {
+        Stopwatch {
+            started_at_ms: js_sys::Date::now(),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn start() -> Stopwatch {
+        Stopwatch {
+            started_at: std::time::Instant::now(),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn elapsed_ms(&self) -> f64 {
+        js_sys::Date::now() - self.started_at_ms
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn elapsed_ms(&self) -> f64 {
+        self.started_at.elapsed().as_secs_f64() * 1000.0
+    }
+}
+
+// Schedules are keyed in id order, so anything iterating them sees the same order on every run.
+// assignments are only changed through assign_order and unassign, which keep the schedules and
+// the cached fingerprint in step with them.
 #[derive(Clone)]
 pub struct SolverState {
-    driver_schedules: HashMap<String, Vec<(NaiveDateTime, NaiveDateTime)>>,
-    vehicle_schedules: HashMap<String, Vec<(NaiveDateTime, NaiveDateTime)>>,
+    driver_schedules: BTreeMap<String, Vec<ScheduleEntry>>,
+    vehicle_schedules: BTreeMap<String, Vec<ScheduleEntry>>,
     pub assignments: Vec<Assignment>,
     pub score: f64,
+    // Filled by initialize_random_state when the config collects rejection stats
+    rejection_counts: HashMap<Rejection, usize>,
+    // Computed on the first ranking after a change, see fingerprint
+    fingerprint: OnceLock<u64>,
 }
 
 impl SolverState {
-    fn new(drivers: &Vec<Driver>, vehicles: &Vec<Vehicle>) -> SolverState {
+    fn new(drivers: &[Driver], vehicles: &[Vehicle]) -> SolverState {
         let driver_schedules = drivers
             .iter()
             .map(|d| (d.id.clone(), Vec::new()))
-            .collect::<HashMap<_, _>>();
+            .collect::<BTreeMap<_, _>>();
         let vehicle_schedules = vehicles
             .iter()
             .map(|v| (v.id.clone(), Vec::new()))
-            .collect::<HashMap<_, _>>();
+            .collect::<BTreeMap<_, _>>();
         SolverState {
             driver_schedules,
             vehicle_schedules,
             assignments: Vec::new(),
             score: 0.0,
+            rejection_counts: HashMap::new(),
+            fingerprint: OnceLock::new(),
+        }
+    }
+
+    // Schedules holding only the pinned assignments of the index, the start of every individual
+    fn with_pinned(drivers: &[Driver], vehicles: &[Vehicle], index: &ProblemIndex) -> SolverState {
+        let mut state = SolverState::new(drivers, vehicles);
+        for assignment in &index.pinned {
+            let order = index.orders[assignment.order_id.as_str()];
+            let driver = index.drivers[assignment.driver_id.as_str()];
+            let vehicle = index.vehicles[assignment.vehicle_id.as_str()];
+            state.assign_order(
+                &assigned_piece(order, assignment),
+                driver,
+                vehicle,
+                (assignment.start_time, assignment.end_time),
+            );
+            if let Some(placed) = state.assignments.last_mut() {
+                placed.pinned = true;
+            }
         }
+        state
     }
 
+    // Books the slot on both resources. Leaves score alone, callers refresh it with
+    // calculate_score once they are done changing the assignments.
     fn assign_order(
         &mut self,
         order: &Order,
         driver: &Driver,
         vehicle: &Vehicle,
-        priority_map: &HashMap<String, u32>,
+        slot: (NaiveDateTime, NaiveDateTime),
     ) {
+        let (start_time, end_time) = order.occupied(slot);
+        let entry = ScheduleEntry {
+            order_id: order.id.clone(),
+            driver_id: driver.id.clone(),
+            start_time,
+            end_time,
+            location: order.location(),
+        };
         self.driver_schedules
             .get_mut(&driver.id)
             .unwrap()
-            .push((order.start_time, order.end_time));
-        self.vehicle_schedules
-            .get_mut(&vehicle.id)
-            .unwrap()
-            .push((order.start_time, order.end_time));
+            .push(entry.clone());
+        // The members of a crew share one vehicle entry, booked by the first of them
+        let vehicle_schedule = self.vehicle_schedules.get_mut(&vehicle.id).unwrap();
+        if !vehicle_schedule
+            .iter()
+            .any(|e| e.order_id == entry.order_id && e.start_time == entry.start_time)
+        {
+            vehicle_schedule.push(entry);
+        }
+        self.fingerprint.take();
         self.assignments.push(Assignment {
             order_id: order.id.clone(),
             driver_id: driver.id.clone(),
             vehicle_id: vehicle.id.clone(),
+            start_time: slot.0,
+            end_time: slot.1,
+            weight: order.splittable.then_some(order.weight),
+            relaxed: false,
+            pinned: false,
+        });
+    }
+
+    // Removes the assignment at idx and frees its driver and vehicle slots
+    fn unassign(&mut self, idx: usize) -> Assignment {
+        let assignment = self.assignments.remove(idx);
+        self.fingerprint.take();
+        // Pieces of a split order share the id but never the same start on one resource
+        let is_same = |e: &ScheduleEntry| {
+            e.order_id == assignment.order_id && e.start_time == assignment.start_time
+        };
+        self.driver_schedules
+            .get_mut(&assignment.driver_id)
+            .unwrap()
+            .retain(|e| !is_same(e));
+        // The vehicle stays booked while another member of the crew is still assigned,
+        // repair_crews drops such incomplete crews
+        let crew_left = self.assignments.iter().any(|a| {
+            a.order_id == assignment.order_id
+                && a.vehicle_id == assignment.vehicle_id
+                && a.start_time == assignment.start_time
         });
-        let mut weight = *priority_map.get(&order.id).unwrap_or(&1) as f64;
-        const PREFERENCE_BONUS: f64 = 0.1;
-        if let Some(preferred_vehicle) = &driver.preference {
-            if &vehicle.id == preferred_vehicle {
-                weight += PREFERENCE_BONUS;
+        if !crew_left {
+            self.vehicle_schedules
+                .get_mut(&assignment.vehicle_id)
+                .unwrap()
+                .retain(|e| !is_same(e));
+        }
+        assignment
+    }
+
+    // Books the order on the driver and, for a crew order, as many other drivers as it needs at
+    // the same slot. Placements come from feasible_placements, which only offers a crew slot
+    // when enough drivers are free.
+    fn place(
+        &mut self,
+        order: &Order,
+        driver: &Driver,
+        vehicle: &Vehicle,
+        slot: (NaiveDateTime, NaiveDateTime),
+        drivers: &[Driver],
+        spacing: Spacing,
+    ) {
+        if order.crew_size() == 1 {
+            self.assign_order(order, driver, vehicle, slot);
+            return;
+        }
+        if let Some(crew) = self.crew_for(order, driver, vehicle, slot, drivers, spacing) {
+            for member in crew {
+                self.assign_order(order, member, vehicle, slot);
             }
         }
-        self.score += weight;
     }
 
-    pub fn calculate_score(
+    // The driver followed by the first other drivers who can join them at exactly this slot
+    // on the vehicle, or None when fewer than the order's crew size are free
+    fn crew_for<'a>(
         &self,
-        priority_map: &HashMap<String, u32>,
-        drivers: &Vec<Driver>,
-    ) -> f64 {
-        let mut score = 0.0;
-        for assignment in &self.assignments {
-            let priority = *priority_map.get(&assignment.order_id).unwrap_or(&1) as f64;
-            let driver = drivers
-                .iter()
-                .find(|d| d.id == assignment.driver_id)
-                .unwrap();
-            let mut weight = priority;
-            const PREFERENCE_BONUS: f64 = 0.1;
-            if let Some(preferred_vehicle) = &driver.preference {
-                if &assignment.vehicle_id == preferred_vehicle {
-                    weight += PREFERENCE_BONUS;
-                }
+        order: &Order,
+        driver: &'a Driver,
+        vehicle: &Vehicle,
+        slot: (NaiveDateTime, NaiveDateTime),
+        drivers: &'a [Driver],
+        spacing: Spacing,
+    ) -> Option<Vec<&'a Driver>> {
+        let crew_size = order.crew_size();
+        let mut crew = vec![driver];
+        for other in drivers {
+            if crew.len() == crew_size {
+                break;
+            }
+            if other.id != driver.id
+                && driver_may_operate(other, vehicle)
+                && driver_fits(
+                    order,
+                    slot,
+                    other,
+                    &self.driver_schedules[&other.id],
+                    spacing,
+                )
+            {
+                crew.push(other);
             }
-            score += weight;
         }
-        score
+        (crew.len() == crew_size).then_some(crew)
     }
-}
 
-// Helper functions
-fn orders_overlap(order1: &Order, order2: &Order) -> bool {
-    order1.start_time < order2.end_time && order2.start_time < order1.end_time
-}
+    // Assignments sharing the order, vehicle and start with the one given, itself included
+    fn crew_count(&self, assignment: &Assignment) -> usize {
+        self.assignments
+            .iter()
+            .filter(|a| {
+                a.order_id == assignment.order_id
+                    && a.vehicle_id == assignment.vehicle_id
+                    && a.start_time == assignment.start_time
+            })
+            .count()
+    }
 
-fn insufficient_break(order1: &Order, order2: &Order, mandatory_break: ChronoDuration) -> bool {
-    if order1.end_time <= order2.start_time {
-        order2.start_time - order1.end_time < mandatory_break
-    } else if order2.end_time <= order1.start_time {
-        order1.start_time - order2.end_time < mandatory_break
-    } else {
-        false
+    // Orders best first: higher score, then more assignments, then a fingerprint of the plan so
+    // equal-score individuals always sort the same way
+    pub fn rank(a: &SolverState, b: &SolverState) -> Ordering {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| b.assignments.len().cmp(&a.assignments.len()))
+            .then_with(|| a.fingerprint().cmp(&b.fingerprint()))
     }
-}
 
-fn is_driver_on_break(driver: &Driver, time: NaiveDateTime) -> bool {
-    if let Some(breaks) = &driver.breaks {
-        for b in breaks {
-            if b.from <= time && time < b.to {
-                return true;
+    // FNV-1a hash of the sorted assignments, independent of the order they were made in. Every
+    // field is written out as bytes, so the value only depends on the plan and not on the Hash
+    // implementations of Rust or chrono. Cached until the assignments change.
+    fn fingerprint(&self) -> u64 {
+        *self.fingerprint.get_or_init(|| {
+            let mut genes: Vec<_> = self
+                .assignments
+                .iter()
+                .map(|a| {
+                    (
+                        &a.order_id,
+                        &a.driver_id,
+                        &a.vehicle_id,
+                        a.start_time,
+                        a.weight.map(f64::to_bits),
+                    )
+                })
+                .collect();
+            genes.sort();
+            let mut hasher = FnvHasher::default();
+            for (order_id, driver_id, vehicle_id, start_time, weight) in genes {
+                for id in [order_id, driver_id, vehicle_id] {
+                    hasher.write(id.as_bytes());
+                    hasher.write(&[0xff]);
+                }
+                hasher.write(&start_time.and_utc().timestamp_millis().to_le_bytes());
+                match weight {
+                    Some(bits) => {
+                        hasher.write(&[1]);
+                        hasher.write(&bits.to_le_bytes());
+                    }
+                    None => hasher.write(&[0]),
+                }
             }
-        }
+            hasher.finish()
+        })
     }
-    false
-}
 
-fn can_assign_driver(
-    order: &Order,
-    driver: &Driver,
-    driver_schedule: &Vec<(NaiveDateTime, NaiveDateTime)>,
-    mandatory_break: ChronoDuration,
-) -> bool {
-    if is_driver_on_break(driver, order.start_time) {
-        return false;
+    // A vehicle already in use can always take more, an unused one only while the number of
+    // vehicles in use is below max_vehicles_used
+    fn fleet_allows(&self, vehicle: &Vehicle, spacing: Spacing) -> bool {
+        let Some(max_vehicles_used) = spacing.max_vehicles_used else {
+            return true;
+        };
+        !self.vehicle_schedules[&vehicle.id].is_empty()
+            || self
+                .vehicle_schedules
+                .values()
+                .filter(|schedule| !schedule.is_empty())
+                .count()
+                < max_vehicles_used
     }
 
-    for &(start, end) in driver_schedule {
-        // Create temporary orders for comparison
-        let existing_order = Order {
-            id: "".to_string(),
-            start_time: start,
-            end_time: end,
-            priority: None,
-            tags: None,
-            weight: 0.0,
-            volume: None,
-        };
+    // Every check for booking the order at exactly this slot, used when replaying a given plan
+    fn slot_fits(
+        &self,
+        order: &Order,
+        driver: &Driver,
+        vehicle: &Vehicle,
+        slot: (NaiveDateTime, NaiveDateTime),
+        spacing: Spacing,
+    ) -> bool {
+        let driver_schedule = &self.driver_schedules[&driver.id];
+        let vehicle_schedule = &self.vehicle_schedules[&vehicle.id];
+        driver_may_operate(driver, vehicle)
+            && self.fleet_allows(vehicle, spacing)
+            && driver_fits(order, slot, driver, driver_schedule, spacing)
+            && vehicle_fits(order, slot, vehicle, vehicle_schedule, spacing)
+            && !handoff_too_short(order, slot, driver, vehicle_schedule, spacing)
+    }
 
-        // Check for overlapping intervals
-        if orders_overlap(&existing_order, order) {
-            return false;
+    pub fn calculate_score(
+        &self,
+        priority_map: &HashMap<String, f64>,
+        drivers: &[Driver],
+        vehicles: &[Vehicle],
+        orders: &[Order],
+        index: &ProblemIndex,
+        config: &SolverConfig,
+    ) -> f64 {
+        let order_terms: f64 = self
+            .score_breakdown(priority_map, index, orders, config)
+            .iter()
+            .map(|s| s.total)
+            .sum();
+        order_terms + self.schedule_adjustment(drivers, vehicles, orders, index, config)
+    }
+
+    // Schedule-wide objective terms that cannot be attributed to a single order
+    pub fn schedule_adjustment(
+        &self,
+        drivers: &[Driver],
+        vehicles: &[Vehicle],
+        orders: &[Order],
+        index: &ProblemIndex,
+        config: &SolverConfig,
+    ) -> f64 {
+        let mut adjustment = 0.0;
+        let balance_weight = config.objective_weights.balance;
+        if balance_weight != 0.0 {
+            adjustment -= balance_weight * self.vehicle_load_variance();
         }
-        // Check for insufficient break
-        if insufficient_break(&existing_order, order, mandatory_break) {
-            return false;
+        if config.overtime_penalty != 0.0 {
+            adjustment -= config.overtime_penalty * self.overtime_minutes(drivers) as f64;
+        }
+        if config.group_bonus != 0.0 {
+            adjustment += config.group_bonus * self.complete_groups(orders, index) as f64;
+        }
+        if config.min_fill_penalty != 0.0 {
+            adjustment -= config.min_fill_penalty * self.fill_shortfall(vehicles, index);
+        }
+        if config.gap_bonus != 0.0 && config.preferred_gap_minutes > 0 {
+            let gaps = self.roomy_gaps(config.preferred_gap_minutes) as f64;
+            adjustment += (config.gap_bonus * gaps).min(config.gap_bonus_cap.unwrap_or(f64::MAX));
+        }
+        if config.driver_count_penalty != 0.0 {
+            adjustment -= config.driver_count_penalty * self.drivers_used() as f64;
         }
+        if let Some(cap) = config.max_assignment_imbalance {
+            let excess = self.assignment_imbalance().saturating_sub(cap);
+            adjustment -= config.imbalance_penalty * excess as f64;
+        }
+        adjustment
     }
-    true
-}
 
-fn can_assign_vehicle(
-    order: &Order,
-    vehicle: &Vehicle,
-    vehicle_schedule: &Vec<(NaiveDateTime, NaiveDateTime)>,
-    mandatory_break: ChronoDuration,
-) -> bool {
-    for &(start, end) in vehicle_schedule {
-        let existing_order = Order {
-            id: "".to_string(),
-            start_time: start,
-            end_time: end,
-            priority: None,
-            tags: None,
-            weight: 0.0,
-            volume: None,
-        };
+    // Drivers with at least one assignment
+    fn drivers_used(&self) -> usize {
+        self.driver_schedules
+            .values()
+            .filter(|schedule| !schedule.is_empty())
+            .count()
+    }
 
-        // Check for overlapping intervals
-        if orders_overlap(&existing_order, order) {
-            return false;
-        }
-        // Check for insufficient break
-        if insufficient_break(&existing_order, order, mandatory_break) {
-            return false;
-        }
+    // Assignments of the busiest driver minus those of the least busy, idle drivers count as zero
+    fn assignment_imbalance(&self) -> usize {
+        let counts = self.driver_schedules.values().map(Vec::len);
+        counts.clone().max().unwrap_or(0) - counts.min().unwrap_or(0)
     }
 
-    // Check capacity constraints
-    if order.weight > vehicle.max_weight {
-        return false;
+    // Number of consecutive order pairs, per driver, separated by at least preferred_gap_minutes
+    fn roomy_gaps(&self, preferred_gap_minutes: i64) -> usize {
+        let preferred_gap = ChronoDuration::minutes(preferred_gap_minutes);
+        self.driver_schedules
+            .values()
+            .map(|schedule| {
+                let mut slots: Vec<_> = schedule.iter().map(ScheduleEntry::slot).collect();
+                slots.sort();
+                slots
+                    .windows(2)
+                    .filter(|pair| pair[1].0 - pair[0].1 >= preferred_gap)
+                    .count()
+            })
+            .sum()
     }
-    if let Some(order_volume) = order.volume {
-        if let Some(vehicle_volume) = vehicle.max_volume {
-            if order_volume > vehicle_volume {
-                return false;
-            }
-        } else {
-            return false;
+
+    // How far used vehicles fall short of their min_fill_pct, in shares of max_weight summed over
+    // vehicles. Unused vehicles never count, they are not dispatched at all.
+    fn fill_shortfall(&self, vehicles: &[Vehicle], index: &ProblemIndex) -> f64 {
+        let mut loads: HashMap<&str, f64> = HashMap::new();
+        for assignment in &self.assignments {
+            let weight = assignment.weight.unwrap_or_else(|| {
+                index
+                    .orders
+                    .get(assignment.order_id.as_str())
+                    .map_or(0.0, |o| o.weight)
+            });
+            *loads.entry(assignment.vehicle_id.as_str()).or_insert(0.0) += weight;
         }
+        vehicles
+            .iter()
+            .filter(|vehicle| vehicle.max_weight > 0.0)
+            .filter_map(|vehicle| {
+                let min_fill = vehicle.min_fill_pct?;
+                let load = loads.get(vehicle.id.as_str())?;
+                Some((min_fill - load / vehicle.max_weight).max(0.0))
+            })
+            .sum()
     }
 
-    // Check tags
-    if let Some(order_tags) = &order.tags {
-        if let Some(vehicle_tags) = &vehicle.tags {
-            let vehicle_tags_set: HashSet<_> = vehicle_tags.iter().collect();
-            if !order_tags.iter().all(|tag| vehicle_tags_set.contains(tag)) {
-                return false;
+    // Number of order groups whose members are all assigned
+    fn complete_groups(&self, orders: &[Order], index: &ProblemIndex) -> usize {
+        let mut covered: HashMap<&str, f64> = HashMap::new();
+        for assignment in &self.assignments {
+            if let Some(order) = index.orders.get(assignment.order_id.as_str()) {
+                *covered.entry(order.id.as_str()).or_insert(0.0) += order.coverage(assignment);
             }
-        } else {
-            return false;
         }
+        let mut groups: HashMap<&String, bool> = HashMap::new();
+        for order in orders {
+            let Some(group_id) = &order.group_id else {
+                continue;
+            };
+            let covered = covered.get(order.id.as_str()).copied().unwrap_or(0.0);
+            let complete = groups.entry(group_id).or_insert(true);
+            *complete &= covered >= 1.0 - 1e-9;
+        }
+        groups.values().filter(|&&complete| complete).count()
     }
 
-    true
-}
-
-fn can_assign(
-    order: &Order,
-    driver: &Driver,
-    vehicle: &Vehicle,
-    driver_schedule: &Vec<(NaiveDateTime, NaiveDateTime)>,
-    vehicle_schedule: &Vec<(NaiveDateTime, NaiveDateTime)>,
-    mandatory_break: ChronoDuration,
-) -> bool {
-    // Check driver availability
-    if !can_assign_driver(order, driver, driver_schedule, mandatory_break) {
-        return false;
+    // Minutes of assigned work beyond each driver's max_work_minutes, summed over all drivers
+    fn overtime_minutes(&self, drivers: &[Driver]) -> i64 {
+        drivers
+            .iter()
+            .filter_map(|driver| {
+                let budget = driver.max_work_minutes?;
+                let worked: i64 = self.driver_schedules[&driver.id]
+                    .iter()
+                    .map(|entry| (entry.end_time - entry.start_time).num_minutes())
+                    .sum();
+                Some((worked - budget).max(0))
+            })
+            .sum()
     }
 
-    // Check vehicle availability and constraints
-    if !can_assign_vehicle(order, vehicle, vehicle_schedule, mandatory_break) {
-        return false;
+    // Variance of the number of orders per vehicle, unused vehicles count as zero
+    fn vehicle_load_variance(&self) -> f64 {
+        let count = self.vehicle_schedules.len();
+        if count == 0 {
+            return 0.0;
+        }
+        let entries: usize = self.vehicle_schedules.values().map(Vec::len).sum();
+        let mean = entries as f64 / count as f64;
+        self.vehicle_schedules
+            .values()
+            .map(|schedule| (schedule.len() as f64 - mean).powi(2))
+            .sum::<f64>()
+            / count as f64
     }
 
-    true
-}
-
-pub fn initialize_random_state(
-    drivers: &Vec<Driver>,
-    vehicles: &Vec<Vehicle>,
-    orders: &Vec<Order>,
-    priority_map: &HashMap<String, u32>,
-    mandatory_break: ChronoDuration,
-) -> SolverState {
-    let mut state = SolverState::new(drivers, vehicles);
-    let mut rng = rand::thread_rng();
-    let mut orders_shuffled = orders.clone();
-    orders_shuffled.shuffle(&mut rng);
-
-    for order in &orders_shuffled {
-        let mut possible_assignments = Vec::new();
-
-        for driver in drivers {
-            let driver_schedule = &state.driver_schedules[&driver.id];
-            if !can_assign_driver(order, driver, driver_schedule, mandatory_break) {
-                continue;
+    // Per-order contributions weighted by the priority objective. Their totals plus
+    // schedule_adjustment sum to calculate_score. Unassigned orders only appear when they
+    // carry a penalty.
+    pub fn score_breakdown(
+        &self,
+        priority_map: &HashMap<String, f64>,
+        index: &ProblemIndex,
+        orders: &[Order],
+        config: &SolverConfig,
+    ) -> Vec<AssignmentScore> {
+        let mut breakdown = Vec::new();
+        let mut covered: HashMap<&String, f64> = HashMap::new();
+        for assignment in &self.assignments {
+            // Split orders earn their priority in proportion to the weight each piece carries
+            let order = index.orders.get(assignment.order_id.as_str()).copied();
+            // A crew order only earns anything once all of its drivers are assigned together
+            let staffed = order
+                .is_none_or(|o| o.crew_size() == 1 || self.crew_count(assignment) >= o.crew_size());
+            let coverage = match order {
+                Some(o) if staffed => o.coverage(assignment),
+                Some(_) => 0.0,
+                None => 1.0,
+            };
+            *covered.entry(&assignment.order_id).or_insert(0.0) += coverage;
+            let base_priority = coverage * *priority_map.get(&assignment.order_id).unwrap_or(&1.0);
+            let driver = index.drivers[assignment.driver_id.as_str()];
+            // Fillers earn no bonuses, those could make one worth more than a regular order
+            let filler = order.is_some_and(Order::is_filler);
+            let mut preference_bonus = 0.0;
+            if let Some(preferred_vehicle) = &driver.preference {
+                if &assignment.vehicle_id == preferred_vehicle && !filler {
+                    preference_bonus = coverage * config.preference_bonus;
+                }
             }
+            let earliness_bonus = order
+                .filter(|_| !filler)
+                .and_then(|o| o.earliness(assignment.start_time))
+                .map_or(0.0, |earliness| {
+                    coverage * config.earliness_bonus * earliness
+                });
+            let kept_driver = config
+                .historical_assignments
+                .iter()
+                .any(|h| h.order_id == assignment.order_id && h.driver_id == assignment.driver_id);
+            let continuity_bonus = if kept_driver && !filler {
+                coverage * config.continuity_bonus
+            } else {
+                0.0
+            };
+            breakdown.push(AssignmentScore {
+                order_id: assignment.order_id.clone(),
+                base_priority,
+                preference_bonus,
+                earliness_bonus,
+                continuity_bonus,
+                unassigned_penalty: 0.0,
+                total: config.objective_weights.priority
+                    * (base_priority + preference_bonus + earliness_bonus + continuity_bonus),
+            });
+        }
 
-            for vehicle in vehicles {
-                let vehicle_schedule = &state.vehicle_schedules[&vehicle.id];
-                if !can_assign_vehicle(order, vehicle, vehicle_schedule, mandatory_break) {
+        // Penalize dropped orders, or the uncovered share of split ones, in proportion to
+        // their priority
+        if config.unassigned_penalty != 0.0 {
+            for order in orders {
+                let uncovered = 1.0 - covered.get(&order.id).copied().unwrap_or(0.0).min(1.0);
+                if uncovered <= 0.0 {
                     continue;
                 }
+                let base_priority = *priority_map.get(&order.id).unwrap_or(&1.0);
+                let unassigned_penalty = uncovered * config.unassigned_penalty * base_priority;
+                breakdown.push(AssignmentScore {
+                    order_id: order.id.clone(),
+                    base_priority,
+                    preference_bonus: 0.0,
+                    earliness_bonus: 0.0,
+                    continuity_bonus: 0.0,
+                    unassigned_penalty,
+                    total: -config.objective_weights.priority * unassigned_penalty,
+                });
+            }
+        }
+        breakdown
+    }
 
-                if can_assign(
-                    order,
-                    driver,
-                    vehicle,
-                    driver_schedule,
-                    vehicle_schedule,
-                    mandatory_break,
-                ) {
-                    possible_assignments.push((driver.clone(), vehicle.clone()));
-                }
-            }
+    pub fn to_response(
+        &self,
+        priority_map: &HashMap<String, f64>,
+        drivers: &[Driver],
+        vehicles: &[Vehicle],
+        orders: &[Order],
+        index: &ProblemIndex,
+        config: &SolverConfig,
+    ) -> SchedulingResponse {
+        // Orders this solution could not place
+        let assigned_order_ids: HashSet<&String> =
+            self.assignments.iter().map(|a| &a.order_id).collect();
+        let unassigned_order_ids: Vec<String> = orders
+            .iter()
+            .filter(|order| !assigned_order_ids.contains(&order.id))
+            .map(|order| order.id.clone())
+            .collect();
+        let spacing = Spacing::from_config(config);
+        let unassigned_reasons = orders
+            .iter()
+            .filter(|order| !assigned_order_ids.contains(&order.id))
+            .map(|order| UnassignedReason {
+                order_id: order.id.clone(),
+                reason: if feasible_in_isolation(order, drivers, vehicles, spacing) {
+                    UnassignedCause::CrowdedOut
+                } else {
+                    UnassignedCause::Infeasible
+                },
+            })
+            .collect();
+        let required_unassigned = required_unassigned(&self.assignments, orders);
+
+        SchedulingResponse {
+            assignments: self.assignments.clone(),
+            score: self.score,
+            unassigned_order_ids,
+            unassigned_reasons,
+            required_unassigned,
+            relaxed_assignments: self
+                .assignments
+                .iter()
+                .filter(|a| a.relaxed)
+                .map(|a| a.order_id.clone())
+                .collect(),
+            vehicle_utilization: self.vehicle_utilization(vehicles, index),
+            breakdown: config
+                .explain
+                .then(|| self.score_breakdown(priority_map, index, orders, config)),
+            schedule_adjustment: config
+                .explain
+                .then(|| self.schedule_adjustment(drivers, vehicles, orders, index, config)),
+            run_scores: None,
+            pareto_front: None,
+            population: None,
+            cancelled: false,
+            rejection_stats: None,
+            seed_used: 0,
+            warnings: Vec::new(),
+            metrics: SolverMetrics::default(),
         }
+    }
+
+    pub fn vehicle_utilization(
+        &self,
+        vehicles: &[Vehicle],
+        index: &ProblemIndex,
+    ) -> Vec<VehicleUtilization> {
+        vehicles
+            .iter()
+            .map(|vehicle| {
+                let mut total_weight = 0.0;
+                let mut total_volume = 0.0;
+                let mut order_count = 0;
+                for assignment in self
+                    .assignments
+                    .iter()
+                    .filter(|a| a.vehicle_id == vehicle.id)
+                {
+                    let Some(&order) = index.orders.get(assignment.order_id.as_str()) else {
+                        continue;
+                    };
+                    let piece = assigned_piece(order, assignment);
+                    total_weight += piece.weight;
+                    total_volume += piece.volume.unwrap_or(0.0);
+                    order_count += 1;
+                }
+                let weight_pct = if vehicle.max_weight > 0.0 {
+                    total_weight / vehicle.max_weight * 100.0
+                } else {
+                    0.0
+                };
+                VehicleUtilization {
+                    vehicle_id: vehicle.id.clone(),
+                    total_weight,
+                    total_volume,
+                    weight_pct,
+                    order_count,
+                }
+            })
+            .collect()
+    }
+}
+
+// Id lookups built once per solve, so the GA operators avoid linear scans
+pub struct ProblemIndex<'a> {
+    pub orders: HashMap<&'a str, &'a Order>,
+    pub drivers: HashMap<&'a str, &'a Driver>,
+    pub vehicles: HashMap<&'a str, &'a Vehicle>,
+    // Per order, the (driver, vehicle) positions that pass every time-independent check, in
+    // driver-major order. The checks that depend on the schedules still run on top.
+    compatible_pairs: HashMap<&'a str, Vec<(usize, usize)>>,
+    // Assignments booked into every individual before anything else, see place_pinned
+    pinned: Vec<Assignment>,
+    pinned_order_ids: HashSet<String>,
+    // can_assign calls made through this index, one index per solve keeps concurrent solves
+    // from counting into each other's metrics
+    feasibility_checks: AtomicU64,
+}
 
-        if !possible_assignments.is_empty() {
-            let (driver, vehicle) = possible_assignments.choose(&mut rng).unwrap();
-            state.assign_order(order, driver, vehicle, priority_map);
+impl<'a> ProblemIndex<'a> {
+    pub fn new(drivers: &'a [Driver], vehicles: &'a [Vehicle], orders: &'a [Order]) -> Self {
+        let compatible_pairs = orders
+            .iter()
+            .map(|order| (order.id.as_str(), static_pairs(order, drivers, vehicles)))
+            .collect();
+        ProblemIndex {
+            orders: orders.iter().map(|o| (o.id.as_str(), o)).collect(),
+            drivers: drivers.iter().map(|d| (d.id.as_str(), d)).collect(),
+            vehicles: vehicles.iter().map(|v| (v.id.as_str(), v)).collect(),
+            compatible_pairs,
+            pinned: Vec::new(),
+            pinned_order_ids: HashSet::new(),
+            feasibility_checks: AtomicU64::new(0),
         }
     }
 
-    state.score = state.calculate_score(priority_map, drivers);
-    state
+    // can_assign, counted into feasibility_checks
+    fn can_assign(
+        &self,
+        order: &Order,
+        driver: &Driver,
+        vehicle: &Vehicle,
+        driver_schedule: &[ScheduleEntry],
+        vehicle_schedule: &[ScheduleEntry],
+        spacing: Spacing,
+    ) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        self.feasibility_checks
+            .fetch_add(1, AtomicOrdering::Relaxed);
+        can_assign(
+            order,
+            driver,
+            vehicle,
+            driver_schedule,
+            vehicle_schedule,
+            spacing,
+        )
+    }
+
+    fn feasibility_checks(&self) -> u64 {
+        self.feasibility_checks.load(AtomicOrdering::Relaxed)
+    }
+
+    fn pin(&mut self, pinned: Vec<Assignment>) {
+        self.pinned_order_ids = pinned.iter().map(|a| a.order_id.clone()).collect();
+        self.pinned = pinned;
+    }
 }
 
-pub fn select_parent<'a>(population: &'a Vec<SolverState>) -> &'a SolverState {
-    // Tournament selection
-    let mut rng = rand::thread_rng();
-    let tournament_size = 3;
-    let mut best = population.choose(&mut rng).unwrap();
-    for _ in 1..tournament_size {
-        let contender = population.choose(&mut rng).unwrap();
-        if contender.score > best.score {
-            best = contender;
+// The (driver, vehicle) positions that pass every time-independent check for the order, in
+// driver-major order
+fn static_pairs(order: &Order, drivers: &[Driver], vehicles: &[Vehicle]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    for (d, driver) in drivers.iter().enumerate() {
+        if !has_skills(order, driver) || !order_allows_driver(order, driver) {
+            continue;
+        }
+        for (v, vehicle) in vehicles.iter().enumerate() {
+            if driver_may_operate(driver, vehicle) && statically_fits(order, vehicle) {
+                pairs.push((d, v));
+            }
         }
     }
-    best
+    pairs
 }
 
-pub fn crossover(
-    parent1: &SolverState,
-    parent2: &SolverState,
-    drivers: &Vec<Driver>,
-    vehicles: &Vec<Vehicle>,
-    orders: &Vec<Order>,
-    priority_map: &HashMap<String, u32>,
-    mandatory_break: ChronoDuration,
-    rng: &mut ThreadRng,
-) -> SolverState {
-    let mut child = SolverState::new(drivers, vehicles);
+// Books every order in input order on its first feasible placement, rounds times over a fresh
+// plan as the individuals of a population are, and returns how many the last round placed.
+// Without precomputed the compatible pairs of an order are rebuilt before each lookup, as the
+// checks ran before ProblemIndex cached them. Only for examples/compatibility_benchmark.rs.
+#[doc(hidden)]
+pub fn greedy_placement_rounds(
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    rounds: usize,
+    precomputed: bool,
+) -> usize {
+    let mut index = ProblemIndex::new(drivers, vehicles, orders);
+    let spacing = Spacing::from_config(&SolverConfig::default());
+    let mut placed = 0;
+    for _ in 0..rounds {
+        let mut state = SolverState::new(drivers, vehicles);
+        for order in orders {
+            if !precomputed {
+                index
+                    .compatible_pairs
+                    .insert(&order.id, static_pairs(order, drivers, vehicles));
+            }
+            let placements = feasible_placements(&state, order, drivers, vehicles, &index, spacing);
+            if let Some(&(driver, vehicle, slot)) = placements.first() {
+                state.assign_order(order, driver, vehicle, slot);
+            }
+        }
+        placed = state.assignments.len();
+    }
+    placed
+}
 
-    // One-point crossover
-    let crossover_point = rng.gen_range(0..orders.len());
+// Books config.pinned into the state in the given order. The driver and vehicle are fixed, the
+// given times are kept when they lie inside the order window and fit, otherwise the earliest
+// feasible slot is used. Stops at the first pin that references an unknown id, repeats an
+// unsplittable order or cannot be placed at all.
+fn place_pinned(
+    state: &mut SolverState,
+    index: &ProblemIndex,
+    config: &SolverConfig,
+) -> Result<(), String> {
+    let spacing = Spacing::from_config(config);
+    for assignment in &config.pinned {
+        let order_id = &assignment.order_id;
+        let (Some(&order), Some(&driver), Some(&vehicle)) = (
+            index.orders.get(order_id.as_str()),
+            index.drivers.get(assignment.driver_id.as_str()),
+            index.vehicles.get(assignment.vehicle_id.as_str()),
+        ) else {
+            return Err(format!(
+                "Pinned assignment of order {} references an unknown order, driver or vehicle",
+                order_id
+            ));
+        };
+        if !order.splittable && state.assignments.iter().any(|a| &a.order_id == order_id) {
+            return Err(format!("Order {} is pinned more than once", order_id));
+        }
+        if order.crew_size() > 1 {
+            return Err(format!(
+                "Order {} needs {} drivers and cannot be pinned",
+                order_id,
+                order.crew_size()
+            ));
+        }
 
-    for (i, order) in orders.iter().enumerate() {
-        let assignment = if i < crossover_point {
-            parent1.assignments.iter().find(|a| a.order_id == order.id)
+        let pin = assignment.resolve(order);
+        let piece = assigned_piece(order, &pin);
+        let given = (pin.start_time, pin.end_time);
+        let (window_start, window_end) = piece.window();
+        let slot = if given.0 >= window_start
+            && given.1 <= window_end
+            && given.1 - given.0 == piece.duration()
+            && state.slot_fits(&piece, driver, vehicle, given, spacing)
+        {
+            Some(given)
         } else {
-            parent2.assignments.iter().find(|a| a.order_id == order.id)
+            index
+                .can_assign(
+                    &piece,
+                    driver,
+                    vehicle,
+                    &state.driver_schedules[&driver.id],
+                    &state.vehicle_schedules[&vehicle.id],
+                    spacing,
+                )
+                .filter(|_| state.fleet_allows(vehicle, spacing))
+        };
+        let Some(slot) = slot else {
+            return Err(format!(
+                "Pinned assignment of order {} to driver {} / vehicle {} is infeasible",
+                order_id, driver.id, vehicle.id
+            ));
         };
+        state.assign_order(&piece, driver, vehicle, slot);
+        if let Some(placed) = state.assignments.last_mut() {
+            placed.pinned = true;
+        }
+    }
+    Ok(())
+}
 
-        if let Some(assignment) = assignment {
-            let driver = drivers
-                .iter()
-                .find(|d| d.id == assignment.driver_id)
-                .unwrap();
-            let vehicle = vehicles
+// Whether config.pinned can be booked as a whole, run before solving so an infeasible pin is
+// reported instead of being left out
+pub fn check_pinned(
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    config: &SolverConfig,
+) -> Result<(), String> {
+    let index = ProblemIndex::new(drivers, vehicles, orders);
+    place_pinned(&mut SolverState::new(drivers, vehicles), &index, config)
+}
+
+// Vehicle checks that hold for every piece of the order. Split pieces carry less weight and
+// volume than the whole order, so capacity is only decided up front for unsplittable orders.
+fn statically_fits(order: &Order, vehicle: &Vehicle) -> bool {
+    has_tags(order, vehicle)
+        && order_allows_vehicle(order, vehicle)
+        && (order.splittable || capacity_rejection(order, vehicle).is_none())
+}
+
+// Priorities used for scoring, after the configured transform and the urgency boost. Missing
+// priorities count as 1.0.
+// Fillers, orders with a priority of zero or below, share less than the smallest positive
+// priority between them, so no set of fillers ever outscores a single regular order. A higher
+// (less negative) filler priority still earns more.
+pub fn build_priority_map(orders: &[Order], config: &SolverConfig) -> HashMap<String, f64> {
+    let raw = |order: &Order| order.priority.unwrap_or(1.0);
+    let (fillers, regular): (Vec<&Order>, Vec<&Order>) =
+        orders.iter().partition(|order| order.is_filler());
+    let mut priority_map: HashMap<String, f64> = match config.priority_transform {
+        PriorityTransform::Raw => regular
+            .iter()
+            .map(|order| (order.id.clone(), raw(order)))
+            .collect(),
+        PriorityTransform::Log => regular
+            .iter()
+            .map(|order| (order.id.clone(), raw(order).ln_1p()))
+            .collect(),
+        PriorityTransform::Rank => {
+            let distinct = distinct_priorities(&regular);
+            regular
                 .iter()
-                .find(|v| v.id == assignment.vehicle_id)
-                .unwrap();
+                .map(|order| {
+                    let rank = distinct.partition_point(|&p| p < raw(order)) + 1;
+                    (order.id.clone(), rank as f64)
+                })
+                .collect()
+        }
+    };
 
-            if can_assign(
-                order,
-                driver,
-                vehicle,
-                &child.driver_schedules[&driver.id],
-                &child.vehicle_schedules[&vehicle.id],
-                mandatory_break,
-            ) {
-                child.assign_order(order, driver, vehicle, priority_map);
+    // Sooner orders count for more, as 1 + factor / (1 + hours until they start)
+    if config.priority_urgency_factor > 0.0 {
+        let reference = config
+            .reference_time
+            .or_else(|| orders.iter().map(|o| o.start_time).min());
+        if let Some(reference) = reference {
+            for order in &regular {
+                let hours = ((order.start_time - reference).num_seconds() as f64 / 3600.0).max(0.0);
+                if let Some(priority) = priority_map.get_mut(&order.id) {
+                    *priority *= 1.0 + config.priority_urgency_factor / (1.0 + hours);
+                }
             }
         }
     }
 
-    // Attempt to assign unassigned orders
-    for order in orders {
-        if child.assignments.iter().any(|a| a.order_id == order.id) {
-            continue;
+    if !fillers.is_empty() {
+        let smallest = priority_map
+            .values()
+            .copied()
+            .reduce(f64::min)
+            .unwrap_or(1.0);
+        let share = smallest / (fillers.len() + 1) as f64;
+        let distinct = distinct_priorities(&fillers);
+        for order in fillers {
+            let rank = distinct.partition_point(|&p| p < raw(order)) + 1;
+            priority_map.insert(
+                order.id.clone(),
+                share * rank as f64 / distinct.len() as f64,
+            );
         }
-        let mut possible_assignments = Vec::new();
-        for driver in drivers {
-            let driver_schedule = &child.driver_schedules[&driver.id];
-            if !can_assign_driver(order, driver, driver_schedule, mandatory_break) {
-                continue;
+    }
+    priority_map
+}
+
+// Raw priorities of the orders, ascending and without duplicates
+fn distinct_priorities(orders: &[&Order]) -> Vec<f64> {
+    let mut distinct: Vec<f64> = orders.iter().map(|o| o.priority.unwrap_or(1.0)).collect();
+    distinct.sort_by(f64::total_cmp);
+    distinct.dedup();
+    distinct
+}
+
+// score_upper_bound with the priorities built as the solver would, for callers that never run it
+pub fn max_possible_score(orders: &[Order], drivers: &[Driver], config: &SolverConfig) -> f64 {
+    score_upper_bound(orders, drivers, &build_priority_map(orders, config), config)
+}
+
+// Highest score any schedule could reach: every order assigned, each with the preference bonus
+// whenever some driver has a preference at all and the continuity bonus whenever it has a
+// historical driver. Ignores feasibility and assumes a non-negative
+// priority weight.
+pub fn score_upper_bound(
+    orders: &[Order],
+    drivers: &[Driver],
+    priority_map: &HashMap<String, f64>,
+    config: &SolverConfig,
+) -> f64 {
+    let any_preference = drivers.iter().any(|d| d.preference.is_some());
+    let priority_terms: f64 = orders
+        .iter()
+        .map(|order| {
+            let priority = *priority_map.get(&order.id).unwrap_or(&1.0);
+            let earliness = order
+                .earliness(order.window().0)
+                .map_or(0.0, |_| config.earliness_bonus.max(0.0));
+            let continuity = if config
+                .historical_assignments
+                .iter()
+                .any(|h| h.order_id == order.id)
+            {
+                config.continuity_bonus.max(0.0)
+            } else {
+                0.0
+            };
+            if any_preference {
+                priority + config.preference_bonus.max(0.0) + earliness + continuity
+            } else {
+                priority + earliness + continuity
             }
-            for vehicle in vehicles {
-                let vehicle_schedule = &child.vehicle_schedules[&vehicle.id];
-                if !can_assign_vehicle(order, vehicle, vehicle_schedule, mandatory_break) {
-                    continue;
-                }
-                if can_assign(
-                    order,
-                    driver,
-                    vehicle,
-                    driver_schedule,
-                    vehicle_schedule,
-                    mandatory_break,
-                ) {
-                    possible_assignments.push((driver.clone(), vehicle.clone()));
-                }
+        })
+        .sum();
+    let group_count = orders
+        .iter()
+        .filter_map(|o| o.group_id.as_ref())
+        .collect::<HashSet<_>>()
+        .len();
+    // At most one roomy gap per order, and never more than the cap
+    let gap_terms = if config.preferred_gap_minutes > 0 {
+        (config.gap_bonus.max(0.0) * orders.len() as f64)
+            .min(config.gap_bonus_cap.unwrap_or(f64::MAX))
+    } else {
+        0.0
+    };
+    // The balance and overtime terms never add to the score
+    config.objective_weights.priority * priority_terms
+        + config.group_bonus.max(0.0) * group_count as f64
+        + gap_terms.max(0.0)
+}
+
+// Helper functions
+
+// Required orders the assignments leave unassigned or only partly covered
+fn required_unassigned(assignments: &[Assignment], orders: &[Order]) -> Vec<String> {
+    orders
+        .iter()
+        .filter(|order| order.required.unwrap_or(false))
+        .filter(|order| {
+            let covered: f64 = assignments
+                .iter()
+                .filter(|a| a.order_id == order.id)
+                .map(|a| order.coverage(a))
+                .sum();
+            covered < 1.0 - 1e-9
+        })
+        .map(|order| order.id.clone())
+        .collect()
+}
+
+// Slots are half-open [start, end): touching slots (end == start) do not overlap,
+// and identical or nested slots always do
+fn orders_overlap(
+    slot1: (NaiveDateTime, NaiveDateTime),
+    slot2: (NaiveDateTime, NaiveDateTime),
+) -> bool {
+    slot1.0 < slot2.1 && slot2.0 < slot1.1
+}
+
+// Back-to-back slots, one ending exactly when the other starts. These only conflict between
+// orders of one resource and only when config.touching_allowed is off, breaks, shifts and
+// unavailability windows always use the half-open reading of orders_overlap.
+fn orders_touch(
+    slot1: (NaiveDateTime, NaiveDateTime),
+    slot2: (NaiveDateTime, NaiveDateTime),
+) -> bool {
+    slot1.1 == slot2.0 || slot2.1 == slot1.0
+}
+
+// A gap exactly equal to mandatory_break is sufficient, only strictly shorter gaps are rejected.
+// Overlapping slots are left to orders_overlap and never count as an insufficient break.
+fn insufficient_break(
+    slot1: (NaiveDateTime, NaiveDateTime),
+    slot2: (NaiveDateTime, NaiveDateTime),
+    mandatory_break: ChronoDuration,
+) -> bool {
+    if mandatory_break <= ChronoDuration::zero() {
+        return false;
+    }
+    if slot1.1 <= slot2.0 {
+        slot2.0 - slot1.1 < mandatory_break
+    } else if slot2.1 <= slot1.0 {
+        slot1.0 - slot2.1 < mandatory_break
+    } else {
+        false
+    }
+}
+
+// True when any break intersects the slot, not only when the slot starts during a break
+fn is_driver_on_break(driver: &Driver, slot: (NaiveDateTime, NaiveDateTime)) -> bool {
+    if let Some(breaks) = &driver.breaks {
+        for b in breaks {
+            if orders_overlap((b.from, b.to), slot) {
+                return true;
             }
         }
-        if !possible_assignments.is_empty() {
-            let (driver, vehicle) = possible_assignments.choose(rng).unwrap();
-            child.assign_order(order, driver, vehicle, priority_map);
+    }
+    recurring_break_windows(driver, slot).any(|window| orders_overlap(window, slot))
+}
+
+// The driver's daily breaks on every date the period touches, starting a day earlier to catch
+// breaks that run past midnight. One day is enough because validate_inputs limits daily breaks
+// to a day.
+fn recurring_break_windows(
+    driver: &Driver,
+    period: (NaiveDateTime, NaiveDateTime),
+) -> impl Iterator<Item = (NaiveDateTime, NaiveDateTime)> + '_ {
+    let first_day = period.0.date() - ChronoDuration::days(1);
+    let days = (period.1.date() - first_day).num_days();
+    (0..=days).flat_map(move |offset| {
+        let midnight = (first_day + ChronoDuration::days(offset)).and_time(NaiveTime::MIN);
+        driver.recurring_breaks.iter().flatten().map(move |b| {
+            let from = midnight + ChronoDuration::minutes(b.start_minute_of_day.into());
+            (
+                from,
+                from + ChronoDuration::minutes(b.duration_minutes.into()),
+            )
+        })
+    })
+}
+
+// Candidate slots for an order: the window start plus every release time that falls inside the
+// window, earliest first. Orders without a flexible window only ever yield [start_time, end_time].
+fn candidate_slots(
+    order: &Order,
+    release_times: impl IntoIterator<Item = NaiveDateTime>,
+) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+    let (window_start, window_end) = order.window();
+    let duration = order.duration();
+    let latest_start = window_end - duration;
+    if latest_start < window_start {
+        return Vec::new();
+    }
+    if latest_start == window_start {
+        return vec![(window_start, window_end)];
+    }
+
+    let mut starts: Vec<NaiveDateTime> = release_times
+        .into_iter()
+        .filter(|&t| window_start < t && t <= latest_start)
+        .collect();
+    starts.push(window_start);
+    starts.sort();
+    starts.dedup();
+    starts.into_iter().map(|t| (t, t + duration)).collect()
+}
+
+// Earliest instants at which an order at location may start after each busy period of a resource
+fn release_times(
+    schedule: &[ScheduleEntry],
+    location: Option<(f64, f64)>,
+    spacing: Spacing,
+) -> impl Iterator<Item = NaiveDateTime> + '_ {
+    // Without a gap the order would touch the previous one, so it is offered a millisecond later
+    let apart = if spacing.touching_allowed {
+        ChronoDuration::zero()
+    } else {
+        ChronoDuration::milliseconds(1)
+    };
+    schedule
+        .iter()
+        .map(move |e| e.end_time + spacing.gap(e.location, location).max(apart))
+}
+
+// Release times of a driver also include the end of each break and the start of each shift
+fn driver_release_times<'a>(
+    order: &Order,
+    driver: &'a Driver,
+    driver_schedule: &'a [ScheduleEntry],
+    spacing: Spacing,
+) -> impl Iterator<Item = NaiveDateTime> + 'a {
+    release_times(driver_schedule, order.location(), spacing)
+        .chain(driver.breaks.iter().flatten().map(|b| b.to))
+        .chain(recurring_break_windows(driver, order.window()).map(|(_, to)| to))
+        .chain(driver.shifts.iter().flatten().map(|&(from, _)| from))
+}
+
+fn is_vehicle_unavailable(vehicle: &Vehicle, slot: (NaiveDateTime, NaiveDateTime)) -> bool {
+    vehicle
+        .unavailable
+        .iter()
+        .flatten()
+        .any(|window| orders_overlap((window.from, window.to), slot))
+}
+
+// Release times of a vehicle also include the end of each unavailability window
+fn vehicle_release_times<'a>(
+    order: &Order,
+    vehicle: &'a Vehicle,
+    vehicle_schedule: &'a [ScheduleEntry],
+    spacing: Spacing,
+) -> impl Iterator<Item = NaiveDateTime> + 'a {
+    release_times(
+        vehicle_schedule,
+        order.location(),
+        spacing.for_vehicle(vehicle),
+    )
+    .chain(vehicle.unavailable.iter().flatten().map(|w| w.to))
+}
+
+fn is_within_shift(driver: &Driver, slot: (NaiveDateTime, NaiveDateTime)) -> bool {
+    match &driver.shifts {
+        Some(shifts) => shifts
+            .iter()
+            .any(|&(from, to)| from <= slot.0 && slot.1 <= to),
+        None => true,
+    }
+}
+
+// Constraint that prevents an order from taking a slot on a driver or vehicle
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Rejection {
+    DriverBreak,
+    OutsideShift,
+    MaxOrders,
+    VehicleUnavailable,
+    MissingSkill,
+    Precedence,
+    Overlap,
+    InsufficientBreak,
+    Weight,
+    Volume,
+    Tags,
+    VehicleNotAllowed,
+    WindowTooShort,
+    ContinuousWork,
+    FleetLimit,
+    VehicleHandoff,
+    DriverNotListed,
+    VehicleNotListed,
+    Range,
+}
+
+impl Rejection {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Rejection::DriverBreak => "overlaps a driver break",
+            Rejection::OutsideShift => "falls outside the driver's shifts",
+            Rejection::MaxOrders => "exceeds the driver's maximum number of orders",
+            Rejection::VehicleUnavailable => "overlaps a vehicle unavailability window",
+            Rejection::MissingSkill => "driver lacks a required skill",
+            Rejection::Precedence => "scheduled before a required predecessor",
+            Rejection::Overlap => "overlaps another order",
+            Rejection::InsufficientBreak => "insufficient break between orders",
+            Rejection::Weight => "exceeds vehicle weight capacity",
+            Rejection::Volume => "exceeds vehicle volume capacity",
+            Rejection::Tags => "vehicle is missing a required tag",
+            Rejection::VehicleNotAllowed => "driver is not allowed to operate the vehicle",
+            Rejection::WindowTooShort => "duration does not fit the time window",
+            Rejection::ContinuousWork => "driver works too long without a long break",
+            Rejection::FleetLimit => "using the vehicle would exceed max_vehicles_used",
+            Rejection::VehicleHandoff => "too little time to hand the vehicle over between drivers",
+            Rejection::DriverNotListed => "driver is not in the order's allowed_driver_ids",
+            Rejection::VehicleNotListed => "vehicle is not in the order's allowed_vehicle_ids",
+            Rejection::Range => "the vehicle's route would exceed its max_distance_km",
+        }
+    }
+}
+
+// First constraint the slot breaks on the driver's side, if any
+fn driver_rejection(
+    order: &Order,
+    slot: (NaiveDateTime, NaiveDateTime),
+    driver: &Driver,
+    driver_schedule: &[ScheduleEntry],
+    spacing: Spacing,
+) -> Option<Rejection> {
+    if is_driver_on_break(driver, slot) {
+        return Some(Rejection::DriverBreak);
+    }
+
+    // Check the whole slot falls inside one of the driver's shifts
+    if !is_within_shift(driver, slot) {
+        return Some(Rejection::OutsideShift);
+    }
+
+    // Check the per-driver order cap
+    if let Some(max_orders) = driver.max_orders {
+        if driver_schedule.len() >= max_orders {
+            return Some(Rejection::MaxOrders);
         }
     }
 
-    child.score = child.calculate_score(priority_map, drivers);
-    child
+    if !has_skills(order, driver) {
+        return Some(Rejection::MissingSkill);
+    }
+    if !order_allows_driver(order, driver) {
+        return Some(Rejection::DriverNotListed);
+    }
+
+    // Check precedence, every predecessor must already be done by this driver
+    if let Some(predecessors) = &order.must_follow {
+        let satisfied = predecessors.iter().all(|pred| {
+            driver_schedule
+                .iter()
+                .any(|e| &e.order_id == pred && e.end_time <= slot.0)
+        });
+        if !satisfied {
+            return Some(Rejection::Precedence);
+        }
+    }
+
+    schedule_rejection(order, slot, driver_schedule, spacing).or_else(|| {
+        exceeds_continuous_work(order.occupied(slot), driver_schedule, spacing)
+            .then_some(Rejection::ContinuousWork)
+    })
 }
 
-pub fn mutate(
-    individual: &mut SolverState,
-    drivers: &Vec<Driver>,
-    vehicles: &Vec<Vehicle>,
-    orders: &Vec<Order>,
-    priority_map: &HashMap<String, u32>,
-    mandatory_break: ChronoDuration,
-    rng: &mut ThreadRng,
-) {
-    // Randomly select an assignment to mutate
-    if individual.assignments.is_empty() {
-        return;
+// Walks the driver's schedule with the slot added in start order. Orders separated by less than
+// the long break form one run, and the order minutes in a run must stay within max_continuous.
+fn exceeds_continuous_work(
+    slot: (NaiveDateTime, NaiveDateTime),
+    driver_schedule: &[ScheduleEntry],
+    spacing: Spacing,
+) -> bool {
+    if spacing.max_continuous <= ChronoDuration::zero() {
+        return false;
+    }
+    let mut slots: Vec<_> = driver_schedule.iter().map(ScheduleEntry::slot).collect();
+    slots.push(slot);
+    slots.sort();
+
+    let mut run = ChronoDuration::zero();
+    let mut previous_end: Option<NaiveDateTime> = None;
+    for (start, end) in slots {
+        if previous_end.is_some_and(|previous_end| start - previous_end >= spacing.long_break) {
+            run = ChronoDuration::zero();
+        }
+        run += end - start;
+        if run > spacing.max_continuous {
+            return true;
+        }
+        previous_end = Some(previous_end.map_or(end, |previous_end| previous_end.max(end)));
     }
+    false
+}
 
-    let idx = rng.gen_range(0..individual.assignments.len());
-    let assignment = individual.assignments[idx].clone();
-    let order = orders.iter().find(|o| o.id == assignment.order_id).unwrap();
+fn driver_fits(
+    order: &Order,
+    slot: (NaiveDateTime, NaiveDateTime),
+    driver: &Driver,
+    driver_schedule: &[ScheduleEntry],
+    spacing: Spacing,
+) -> bool {
+    driver_rejection(order, slot, driver, driver_schedule, spacing).is_none()
+}
 
-    // Remove the assignment
-    individual.assignments.remove(idx);
-    individual
-        .driver_schedules
-        .get_mut(&assignment.driver_id)
-        .unwrap()
-        .retain(|&(start, end)| start != order.start_time || end != order.end_time);
-    individual
-        .vehicle_schedules
-        .get_mut(&assignment.vehicle_id)
-        .unwrap()
-        .retain(|&(start, end)| start != order.start_time || end != order.end_time);
+// First constraint the slot breaks on the vehicle's side, if any
+fn vehicle_rejection(
+    order: &Order,
+    slot: (NaiveDateTime, NaiveDateTime),
+    vehicle: &Vehicle,
+    vehicle_schedule: &[ScheduleEntry],
+    spacing: Spacing,
+) -> Option<Rejection> {
+    if is_vehicle_unavailable(vehicle, slot) {
+        return Some(Rejection::VehicleUnavailable);
+    }
 
-    // Attempt to reassign the order to a different driver or vehicle
-    let mut possible_assignments = Vec::new();
-    for driver in drivers {
-        if driver.id == assignment.driver_id {
-            continue; // Skip the same driver
+    let spacing = spacing.for_vehicle(vehicle);
+    // Another member of the crew booking the same slot is not a conflict
+    let vehicle_schedule: Cow<[ScheduleEntry]> = if order.crew_size() > 1 {
+        Cow::Owned(
+            vehicle_schedule
+                .iter()
+                .filter(|e| !(e.order_id == order.id && e.start_time == slot.0))
+                .cloned()
+                .collect(),
+        )
+    } else {
+        Cow::Borrowed(vehicle_schedule)
+    };
+    if let Some(rejection) = schedule_rejection(order, slot, &vehicle_schedule, spacing) {
+        return Some(rejection);
+    }
+    // An order without coordinates leaves the route as it is
+    if let (Some(max_distance_km), Some(_)) = (vehicle.max_distance_km, order.location()) {
+        if route_km(order, slot, &vehicle_schedule) > max_distance_km {
+            return Some(Rejection::Range);
         }
-        let driver_schedule = &individual.driver_schedules[&driver.id];
-        if !can_assign_driver(order, driver, driver_schedule, mandatory_break) {
-            continue;
+    }
+
+    if let Some(rejection) = capacity_rejection(order, vehicle) {
+        return Some(rejection);
+    }
+    if !has_tags(order, vehicle) {
+        return Some(Rejection::Tags);
+    }
+    if !order_allows_vehicle(order, vehicle) {
+        return Some(Rejection::VehicleNotListed);
+    }
+
+    None
+}
+
+// Distance the vehicle covers going from order to order in start order once the order runs in
+// the slot. Orders without coordinates are skipped, the route runs straight from the one before
+// to the one after them.
+fn route_km(
+    order: &Order,
+    slot: (NaiveDateTime, NaiveDateTime),
+    vehicle_schedule: &[ScheduleEntry],
+) -> f64 {
+    let mut stops: Vec<(NaiveDateTime, (f64, f64))> = vehicle_schedule
+        .iter()
+        .filter_map(|e| Some((e.start_time, e.location?)))
+        .collect();
+    if let Some(location) = order.location() {
+        stops.push((slot.0, location));
+    }
+    stops.sort_by_key(|&(start, _)| start);
+    stops
+        .windows(2)
+        .map(|pair| haversine_km(pair[0].1, pair[1].1))
+        .sum()
+}
+
+// Drivers without a skill list are not restricted
+fn has_skills(order: &Order, driver: &Driver) -> bool {
+    match (&order.tags, &driver.skills) {
+        (Some(order_tags), Some(driver_skills)) => {
+            let driver_skills_set: HashSet<_> = driver_skills.iter().collect();
+            order_tags.iter().all(|tag| driver_skills_set.contains(tag))
         }
-        for vehicle in vehicles {
-            if vehicle.id == assignment.vehicle_id {
-                continue; // Skip the same vehicle
-            }
-            let vehicle_schedule = &individual.vehicle_schedules[&vehicle.id];
-            if !can_assign_vehicle(order, vehicle, vehicle_schedule, mandatory_break) {
-                continue;
-            }
-            if can_assign(
-                order,
-                driver,
-                vehicle,
-                driver_schedule,
-                vehicle_schedule,
-                mandatory_break,
-            ) {
-                possible_assignments.push((driver.clone(), vehicle.clone()));
+        _ => true,
+    }
+}
+
+// Orders without allowed_driver_ids take any driver
+fn order_allows_driver(order: &Order, driver: &Driver) -> bool {
+    match &order.allowed_driver_ids {
+        Some(allowed_driver_ids) => allowed_driver_ids.contains(&driver.id),
+        None => true,
+    }
+}
+
+fn order_allows_vehicle(order: &Order, vehicle: &Vehicle) -> bool {
+    match &order.allowed_vehicle_ids {
+        Some(allowed_vehicle_ids) => allowed_vehicle_ids.contains(&vehicle.id),
+        None => true,
+    }
+}
+
+fn capacity_rejection(order: &Order, vehicle: &Vehicle) -> Option<Rejection> {
+    if order.weight > vehicle.max_weight {
+        return Some(Rejection::Weight);
+    }
+    if let Some(order_volume) = order.volume {
+        if let Some(vehicle_volume) = vehicle.max_volume {
+            if order_volume > vehicle_volume {
+                return Some(Rejection::Volume);
             }
+        } else if !vehicle.volume_unlimited.unwrap_or(false) {
+            return Some(Rejection::Volume);
+        }
+    }
+    None
+}
+
+// A vehicle without tags cannot serve an order that requires any
+fn has_tags(order: &Order, vehicle: &Vehicle) -> bool {
+    match (&order.tags, &vehicle.tags) {
+        (Some(order_tags), Some(vehicle_tags)) => {
+            let vehicle_tags_set: HashSet<_> = vehicle_tags.iter().collect();
+            order_tags.iter().all(|tag| vehicle_tags_set.contains(tag))
         }
+        (Some(_), None) => false,
+        (None, _) => true,
     }
+}
+
+fn vehicle_fits(
+    order: &Order,
+    slot: (NaiveDateTime, NaiveDateTime),
+    vehicle: &Vehicle,
+    vehicle_schedule: &[ScheduleEntry],
+    spacing: Spacing,
+) -> bool {
+    vehicle_rejection(order, slot, vehicle, vehicle_schedule, spacing).is_none()
+}
 
-    if !possible_assignments.is_empty() {
-        let (new_driver, new_vehicle) = possible_assignments.choose(rng).unwrap();
-        individual.assign_order(order, new_driver, new_vehicle, priority_map);
+// Overlap and break checks shared by drivers and vehicles. The required gap between two orders
+// grows by the travel time between them when both have coordinates.
+fn schedule_rejection(
+    order: &Order,
+    slot: (NaiveDateTime, NaiveDateTime),
+    schedule: &[ScheduleEntry],
+    spacing: Spacing,
+) -> Option<Rejection> {
+    // Existing entries already include their service time
+    let slot = order.occupied(slot);
+    for entry in schedule {
+        let existing = entry.slot();
+        // Check for overlapping intervals
+        if orders_overlap(existing, slot)
+            || (!spacing.touching_allowed && orders_touch(existing, slot))
+        {
+            return Some(Rejection::Overlap);
+        }
+        // Check for insufficient break
+        if insufficient_break(
+            existing,
+            slot,
+            spacing.gap(entry.location, order.location()),
+        ) {
+            return Some(Rejection::InsufficientBreak);
+        }
     }
+    None
+}
+
+fn can_assign_driver(
+    order: &Order,
+    driver: &Driver,
+    driver_schedule: &[ScheduleEntry],
+    spacing: Spacing,
+) -> bool {
+    driver_slot_rejection(order, driver, driver_schedule, spacing).is_none()
+}
 
-    individual.score = individual.calculate_score(priority_map, drivers);
+// None when some candidate slot fits the driver, otherwise why the earliest candidate fails
+fn driver_slot_rejection(
+    order: &Order,
+    driver: &Driver,
+    driver_schedule: &[ScheduleEntry],
+    spacing: Spacing,
+) -> Option<Rejection> {
+    let release = driver_release_times(order, driver, driver_schedule, spacing);
+    let mut first_rejection = Some(Rejection::WindowTooShort);
+    for (i, slot) in candidate_slots(order, release).into_iter().enumerate() {
+        match driver_rejection(order, slot, driver, driver_schedule, spacing) {
+            None => return None,
+            Some(rejection) if i == 0 => first_rejection = Some(rejection),
+            Some(_) => {}
+        }
+    }
+    first_rejection
+}
+
+fn can_assign_vehicle(
+    order: &Order,
+    vehicle: &Vehicle,
+    vehicle_schedule: &[ScheduleEntry],
+    spacing: Spacing,
+) -> bool {
+    vehicle_slot_rejection(order, vehicle, vehicle_schedule, spacing).is_none()
+}
+
+// None when some candidate slot fits the vehicle, otherwise why the earliest candidate fails
+fn vehicle_slot_rejection(
+    order: &Order,
+    vehicle: &Vehicle,
+    vehicle_schedule: &[ScheduleEntry],
+    spacing: Spacing,
+) -> Option<Rejection> {
+    let release = vehicle_release_times(order, vehicle, vehicle_schedule, spacing);
+    let mut first_rejection = Some(Rejection::WindowTooShort);
+    for (i, slot) in candidate_slots(order, release).into_iter().enumerate() {
+        match vehicle_rejection(order, slot, vehicle, vehicle_schedule, spacing) {
+            None => return None,
+            Some(rejection) if i == 0 => first_rejection = Some(rejection),
+            Some(_) => {}
+        }
+    }
+    first_rejection
+}
+
+fn driver_may_operate(driver: &Driver, vehicle: &Vehicle) -> bool {
+    match &driver.allowed_vehicle_ids {
+        Some(allowed_vehicle_ids) => allowed_vehicle_ids.contains(&vehicle.id),
+        None => true,
+    }
+}
+
+// Returns the earliest slot where both the driver and the vehicle can take the order
+fn can_assign(
+    order: &Order,
+    driver: &Driver,
+    vehicle: &Vehicle,
+    driver_schedule: &[ScheduleEntry],
+    vehicle_schedule: &[ScheduleEntry],
+    spacing: Spacing,
+) -> Option<(NaiveDateTime, NaiveDateTime)> {
+    // Check the driver is allowed to operate this vehicle
+    if !driver_may_operate(driver, vehicle) {
+        return None;
+    }
+
+    let release = driver_release_times(order, driver, driver_schedule, spacing).chain(
+        vehicle_release_times(order, vehicle, vehicle_schedule, spacing),
+    );
+    candidate_slots(order, release).into_iter().find(|&slot| {
+        // Check driver availability
+        driver_fits(order, slot, driver, driver_schedule, spacing)
+            // Check vehicle availability and constraints
+            && vehicle_fits(order, slot, vehicle, vehicle_schedule, spacing)
+            && !handoff_too_short(order, slot, driver, vehicle_schedule, spacing)
+    })
+}
+
+// The vehicle needs vehicle_handoff between the orders right before and after the slot when
+// they are driven by someone else. Only neighbours count, a driver change further away is
+// separated by those neighbours already.
+fn handoff_too_short(
+    order: &Order,
+    slot: (NaiveDateTime, NaiveDateTime),
+    driver: &Driver,
+    vehicle_schedule: &[ScheduleEntry],
+    spacing: Spacing,
+) -> bool {
+    if spacing.vehicle_handoff <= ChronoDuration::zero() {
+        return false;
+    }
+    let (start, end) = order.occupied(slot);
+    let previous = vehicle_schedule
+        .iter()
+        .filter(|e| e.end_time <= start)
+        .max_by_key(|e| e.end_time);
+    let next = vehicle_schedule
+        .iter()
+        .filter(|e| e.start_time >= end)
+        .min_by_key(|e| e.start_time);
+    previous
+        .is_some_and(|e| e.driver_id != driver.id && start - e.end_time < spacing.vehicle_handoff)
+        || next.is_some_and(|e| {
+            e.driver_id != driver.id && e.start_time - end < spacing.vehicle_handoff
+        })
+}
+
+// Drops assignments whose predecessors are no longer done earlier by the same driver, which
+// crossover and mutate can cause by moving or removing a predecessor. Repeats until stable
+// because each removal can orphan further dependents.
+fn repair_precedence(state: &mut SolverState, orders: &[Order]) {
+    let predecessors: HashMap<&String, &Vec<String>> = orders
+        .iter()
+        .filter_map(|o| o.must_follow.as_ref().map(|preds| (&o.id, preds)))
+        .collect();
+    if predecessors.is_empty() {
+        return;
+    }
+
+    loop {
+        // Pinned orders stay even when their predecessors are missing
+        let broken = state.assignments.iter().position(|a| {
+            !a.pinned
+                && predecessors.get(&a.order_id).is_some_and(|preds| {
+                    !preds.iter().all(|pred| {
+                        state.assignments.iter().any(|p| {
+                            &p.order_id == pred
+                                && p.driver_id == a.driver_id
+                                && p.end_time <= a.start_time
+                        })
+                    })
+                })
+        });
+        match broken {
+            Some(idx) => {
+                state.unassign(idx);
+            }
+            None => break,
+        }
+    }
+}
+
+// Keeps every order group on a single driver or drops it entirely. The driver holding most of
+// the group's assignments keeps them when the whole group is covered there, otherwise all
+// assignments of the group are removed.
+fn repair_groups(state: &mut SolverState, orders: &[Order]) {
+    let mut groups: Vec<(&String, Vec<&Order>)> = Vec::new();
+    for order in orders {
+        let Some(group_id) = &order.group_id else {
+            continue;
+        };
+        match groups.iter_mut().find(|(id, _)| *id == group_id) {
+            Some((_, members)) => members.push(order),
+            None => groups.push((group_id, vec![order])),
+        }
+    }
+
+    for (_, members) in groups {
+        let in_group = |a: &Assignment| members.iter().any(|o| o.id == a.order_id);
+
+        let mut driver_counts: Vec<(String, usize)> = Vec::new();
+        for assignment in state.assignments.iter().filter(|a| in_group(a)) {
+            match driver_counts
+                .iter_mut()
+                .find(|(id, _)| *id == assignment.driver_id)
+            {
+                Some((_, count)) => *count += 1,
+                None => driver_counts.push((assignment.driver_id.clone(), 1)),
+            }
+        }
+        // Ties go to the driver that appears first
+        let Some(keep) = driver_counts
+            .iter()
+            .rev()
+            .max_by_key(|(_, count)| *count)
+            .map(|(id, _)| id.clone())
+        else {
+            continue;
+        };
+
+        let complete = members.iter().all(|order| {
+            let covered: f64 = state
+                .assignments
+                .iter()
+                .filter(|a| a.order_id == order.id && a.driver_id == keep)
+                .map(|a| order.coverage(a))
+                .sum();
+            covered >= 1.0 - 1e-9
+        });
+
+        for idx in (0..state.assignments.len()).rev() {
+            let assignment = &state.assignments[idx];
+            if in_group(assignment)
+                && !assignment.pinned
+                && (!complete || assignment.driver_id != keep)
+            {
+                state.unassign(idx);
+            }
+        }
+    }
+}
+
+// Keeps every vehicle group on the vehicle holding most of its assignments and drops the
+// members placed on any other vehicle. Unlike order groups, a partly assigned group is fine.
+fn repair_vehicle_groups(state: &mut SolverState, orders: &[Order]) {
+    let mut groups: Vec<(&String, Vec<&String>)> = Vec::new();
+    for order in orders {
+        let Some(group) = &order.same_vehicle_group else {
+            continue;
+        };
+        match groups.iter_mut().find(|(id, _)| *id == group) {
+            Some((_, members)) => members.push(&order.id),
+            None => groups.push((group, vec![&order.id])),
+        }
+    }
+
+    for (_, members) in groups {
+        let in_group = |a: &Assignment| members.contains(&&a.order_id);
+
+        // A pinned member decides the vehicle, otherwise the most used one does, ties going to
+        // the vehicle that appears first
+        let mut vehicle_counts: Vec<(String, (usize, usize))> = Vec::new();
+        for assignment in state.assignments.iter().filter(|a| in_group(a)) {
+            let pinned = usize::from(assignment.pinned);
+            match vehicle_counts
+                .iter_mut()
+                .find(|(id, _)| *id == assignment.vehicle_id)
+            {
+                Some((_, (pinned_count, count))) => {
+                    *pinned_count += pinned;
+                    *count += 1;
+                }
+                None => vehicle_counts.push((assignment.vehicle_id.clone(), (pinned, 1))),
+            }
+        }
+        let Some(keep) = vehicle_counts
+            .iter()
+            .rev()
+            .max_by_key(|(_, count)| *count)
+            .map(|(id, _)| id.clone())
+        else {
+            continue;
+        };
+
+        for idx in (0..state.assignments.len()).rev() {
+            let assignment = &state.assignments[idx];
+            if in_group(assignment) && !assignment.pinned && assignment.vehicle_id != keep {
+                state.unassign(idx);
+            }
+        }
+    }
+}
+
+// Drops every crew that lost a member, a crew order only counts when all of its drivers share
+// one vehicle and slot
+fn repair_crews(state: &mut SolverState, orders: &[Order]) {
+    let crew_sizes: HashMap<&str, usize> = orders
+        .iter()
+        .filter(|o| o.crew_size() > 1)
+        .map(|o| (o.id.as_str(), o.crew_size()))
+        .collect();
+    if crew_sizes.is_empty() {
+        return;
+    }
+    for idx in (0..state.assignments.len()).rev() {
+        let assignment = &state.assignments[idx];
+        let understaffed = crew_sizes
+            .get(assignment.order_id.as_str())
+            .is_some_and(|&crew_size| state.crew_count(assignment) < crew_size);
+        if understaffed {
+            state.unassign(idx);
+        }
+    }
+}
+
+// Restores the crew, group and precedence invariants, alternating because each repair can break
+// the others
+fn repair_constraints(state: &mut SolverState, orders: &[Order]) {
+    loop {
+        let before = state.assignments.len();
+        repair_crews(state, orders);
+        repair_groups(state, orders);
+        repair_vehicle_groups(state, orders);
+        repair_precedence(state, orders);
+        if state.assignments.len() == before {
+            break;
+        }
+    }
+}
+
+// Spreads a splittable order over several vehicles when none can carry it whole. Each piece
+// takes as much weight as its vehicle allows until the order is covered or nothing fits.
+fn assign_split_order(
+    state: &mut SolverState,
+    order: &Order,
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    index: &ProblemIndex,
+    config: &SolverConfig,
+    rng: &mut impl Rng,
+) {
+    let spacing = Spacing::from_config(config);
+    let mut remaining = order.weight;
+    let mut used_vehicles: HashSet<&String> = HashSet::new();
+
+    while remaining > 0.0 {
+        let mut possible_assignments = Vec::new();
+        for driver in drivers {
+            for vehicle in vehicles {
+                if vehicle.max_weight <= 0.0
+                    || used_vehicles.contains(&vehicle.id)
+                    || !state.fleet_allows(vehicle, spacing)
+                {
+                    continue;
+                }
+                let piece = order.part(remaining.min(vehicle.max_weight));
+                if let Some(slot) = index.can_assign(
+                    &piece,
+                    driver,
+                    vehicle,
+                    &state.driver_schedules[&driver.id],
+                    &state.vehicle_schedules[&vehicle.id],
+                    spacing,
+                ) {
+                    possible_assignments.push((driver, vehicle, piece, slot));
+                }
+            }
+        }
+
+        let Some((driver, vehicle, piece, slot)) = possible_assignments.choose(rng) else {
+            break;
+        };
+        remaining -= piece.weight;
+        used_vehicles.insert(&vehicle.id);
+        state.assign_order(piece, driver, vehicle, *slot);
+    }
+}
+
+// The order as carried by one assignment, a piece of it when the order was split
+fn assigned_piece<'a>(order: &'a Order, assignment: &Assignment) -> Cow<'a, Order> {
+    match assignment.weight {
+        Some(weight) if order.splittable && weight != order.weight => {
+            Cow::Owned(order.part(weight))
+        }
+        _ => Cow::Borrowed(order),
+    }
+}
+
+// Every (driver, vehicle) pair that can take the order given the current schedules, with the
+// earliest slot for each. drivers and vehicles must be the slices the index was built from.
+fn feasible_placements<'a>(
+    state: &SolverState,
+    order: &Order,
+    drivers: &'a [Driver],
+    vehicles: &'a [Vehicle],
+    index: &ProblemIndex,
+    spacing: Spacing,
+) -> Vec<(&'a Driver, &'a Vehicle, (NaiveDateTime, NaiveDateTime))> {
+    let mut possible_assignments = Vec::new();
+    let Some(pairs) = index.compatible_pairs.get(order.id.as_str()) else {
+        return possible_assignments;
+    };
+
+    // The per-resource prefilters only depend on the resource, evaluate each at most once
+    let mut driver_ok: Vec<Option<bool>> = vec![None; drivers.len()];
+    let mut vehicle_ok: Vec<Option<bool>> = vec![None; vehicles.len()];
+    let mut preferred_open: Vec<Option<bool>> = vec![None; drivers.len()];
+    for &(d, v) in pairs {
+        let driver = &drivers[d];
+        let driver_schedule = &state.driver_schedules[&driver.id];
+        if !*driver_ok[d]
+            .get_or_insert_with(|| can_assign_driver(order, driver, driver_schedule, spacing))
+        {
+            continue;
+        }
+        if driver.preference.as_ref() != Some(&vehicles[v].id)
+            && *preferred_open[d]
+                .get_or_insert_with(|| preferred_vehicle_open(state, order, driver, index, spacing))
+        {
+            continue;
+        }
+
+        let vehicle = &vehicles[v];
+        let vehicle_schedule = &state.vehicle_schedules[&vehicle.id];
+        if !*vehicle_ok[v].get_or_insert_with(|| {
+            state.fleet_allows(vehicle, spacing)
+                && can_assign_vehicle(order, vehicle, vehicle_schedule, spacing)
+        }) {
+            continue;
+        }
+
+        if let Some(slot) = index.can_assign(
+            order,
+            driver,
+            vehicle,
+            driver_schedule,
+            vehicle_schedule,
+            spacing,
+        ) {
+            if order.crew_size() == 1
+                || state
+                    .crew_for(order, driver, vehicle, slot, drivers, spacing)
+                    .is_some()
+            {
+                possible_assignments.push((driver, vehicle, slot));
+            }
+        }
+    }
+
+    possible_assignments
+}
+
+// Under the hard preference mode, whether the driver's preferred vehicle could take the order
+// right now, in which case no other vehicle may be paired with this driver for it. This is
+// decided greedily at placement time: a later order can still occupy the preferred vehicle, and
+// an assignment made while it was busy is kept once it frees up.
+fn preferred_vehicle_open(
+    state: &SolverState,
+    order: &Order,
+    driver: &Driver,
+    index: &ProblemIndex,
+    spacing: Spacing,
+) -> bool {
+    if !spacing.hard_preference {
+        return false;
+    }
+    let Some(&preferred) = driver
+        .preference
+        .as_ref()
+        .and_then(|id| index.vehicles.get(id.as_str()))
+    else {
+        return false;
+    };
+    state.fleet_allows(preferred, spacing)
+        && index
+            .can_assign(
+                order,
+                driver,
+                preferred,
+                &state.driver_schedules[&driver.id],
+                &state.vehicle_schedules[&preferred.id],
+                spacing,
+            )
+            .is_some()
+}
+
+// Counts, per reason, the drivers and vehicles that cannot take the order at this point
+fn record_rejections(
+    state: &mut SolverState,
+    order: &Order,
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    spacing: Spacing,
+) {
+    let driver_rejections = drivers.iter().filter_map(|driver| {
+        driver_slot_rejection(order, driver, &state.driver_schedules[&driver.id], spacing)
+    });
+    let vehicle_rejections = vehicles.iter().filter_map(|vehicle| {
+        if !state.fleet_allows(vehicle, spacing) {
+            return Some(Rejection::FleetLimit);
+        }
+        vehicle_slot_rejection(
+            order,
+            vehicle,
+            &state.vehicle_schedules[&vehicle.id],
+            spacing,
+        )
+    });
+    let rejections: Vec<Rejection> = driver_rejections.chain(vehicle_rejections).collect();
+    for rejection in rejections {
+        *state.rejection_counts.entry(rejection).or_insert(0) += 1;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_random_state(
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    index: &ProblemIndex,
+    priority_map: &HashMap<String, f64>,
+    config: &SolverConfig,
+    init_order: InitOrder,
+    rng: &mut impl Rng,
+) -> SolverState {
+    let spacing = Spacing::from_config(config);
+    let mut state = SolverState::with_pinned(drivers, vehicles, index);
+    let mut order_sequence = orders.to_vec();
+    if !matches!(init_order, InitOrder::PriorityDesc) {
+        order_sequence.shuffle(rng);
+    }
+    if !matches!(init_order, InitOrder::Shuffle) {
+        // Stable, so equal priorities keep the order from above
+        order_sequence.sort_by(|a, b| {
+            priority_map[&b.id]
+                .partial_cmp(&priority_map[&a.id])
+                .unwrap_or(Ordering::Equal)
+        });
+    }
+    order_sequence.retain(|order| !index.pinned_order_ids.contains(&order.id));
+
+    for order in &order_sequence {
+        if config.collect_rejection_stats {
+            record_rejections(&mut state, order, drivers, vehicles, spacing);
+        }
+        let possible_assignments =
+            feasible_placements(&state, order, drivers, vehicles, index, spacing);
+
+        if let Some(&(driver, vehicle, slot)) = possible_assignments.choose(rng) {
+            state.place(order, driver, vehicle, slot, drivers, spacing);
+        } else if order.splittable {
+            assign_split_order(&mut state, order, drivers, vehicles, index, config, rng);
+        }
+    }
+
+    place_relaxed(
+        &mut state,
+        order_sequence.iter(),
+        drivers,
+        vehicles,
+        index,
+        config,
+        rng,
+    );
+    repair_constraints(&mut state, orders);
+    state.score = state.calculate_score(priority_map, drivers, vehicles, orders, index, config);
+    state
+}
+
+// Second pass for what is still unplaced, with the soft limits relaxed. Orders are offered in
+// the given sequence, nothing happens unless config.relaxation is enabled.
+fn place_relaxed<'o>(
+    state: &mut SolverState,
+    order_sequence: impl Iterator<Item = &'o Order>,
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    index: &ProblemIndex,
+    config: &SolverConfig,
+    rng: &mut impl Rng,
+) {
+    if !config.relaxation.is_enabled() {
+        return;
+    }
+    for order in order_sequence {
+        if state.assignments.iter().any(|a| a.order_id == order.id) {
+            continue;
+        }
+        let possible_assignments =
+            relaxed_placements(state, order, drivers, vehicles, index, config);
+        if let Some((driver, vehicle, slot)) = possible_assignments.choose(rng) {
+            state.assign_order(order, driver, vehicle, *slot);
+            if let Some(assignment) = state.assignments.last_mut() {
+                assignment.relaxed = true;
+            }
+        }
+    }
+}
+
+// Placements allowed once the break is shortened by break_slack_minutes and vehicle capacities
+// are stretched by allow_capacity_overflow_pct. Every other constraint, tags included, stays hard.
+fn relaxed_placements<'a>(
+    state: &SolverState,
+    order: &Order,
+    drivers: &'a [Driver],
+    vehicles: &[Vehicle],
+    index: &ProblemIndex,
+    config: &SolverConfig,
+) -> Vec<(&'a Driver, Vehicle, (NaiveDateTime, NaiveDateTime))> {
+    let relaxation = &config.relaxation;
+    let mut spacing = Spacing::from_config(config);
+    spacing.break_slack = ChronoDuration::minutes(relaxation.break_slack_minutes);
+    spacing.mandatory_break =
+        (spacing.mandatory_break - spacing.break_slack).max(ChronoDuration::zero());
+    let stretch = 1.0 + relaxation.allow_capacity_overflow_pct / 100.0;
+
+    let mut possible_assignments = Vec::new();
+    // Crews are only placed under the strict limits
+    if order.crew_size() > 1 {
+        return possible_assignments;
+    }
+    for vehicle in vehicles {
+        if !state.fleet_allows(vehicle, spacing) {
+            continue;
+        }
+        let mut stretched = vehicle.clone();
+        stretched.max_weight *= stretch;
+        stretched.max_volume = vehicle.max_volume.map(|volume| volume * stretch);
+        let vehicle_schedule = &state.vehicle_schedules[&vehicle.id];
+        for driver in drivers {
+            if let Some(slot) = index.can_assign(
+                order,
+                driver,
+                &stretched,
+                &state.driver_schedules[&driver.id],
+                vehicle_schedule,
+                spacing,
+            ) {
+                possible_assignments.push((driver, vehicle.clone(), slot));
+            }
+        }
+    }
+    possible_assignments
+}
+
+// Places the highest-priority orders first, each on the least-loaded feasible driver. The rng
+// only breaks ties between equal priorities and equally loaded drivers, so greedy individuals
+// still differ from each other.
+pub fn initialize_greedy_state(
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    index: &ProblemIndex,
+    priority_map: &HashMap<String, f64>,
+    config: &SolverConfig,
+    rng: &mut impl Rng,
+) -> SolverState {
+    let spacing = Spacing::from_config(config);
+    let mut state = SolverState::with_pinned(drivers, vehicles, index);
+    let mut orders_sorted = orders.to_vec();
+    orders_sorted.shuffle(rng);
+    orders_sorted.sort_by(|a, b| {
+        priority_map[&b.id]
+            .partial_cmp(&priority_map[&a.id])
+            .unwrap_or(Ordering::Equal)
+    });
+    orders_sorted.retain(|order| !index.pinned_order_ids.contains(&order.id));
+    // Random driver order for tie-breaking, placements are visited in this order
+    let mut driver_order: Vec<usize> = (0..drivers.len()).collect();
+    driver_order.shuffle(rng);
+    let driver_rank: HashMap<&str, usize> = driver_order
+        .iter()
+        .enumerate()
+        .map(|(rank, &d)| (drivers[d].id.as_str(), rank))
+        .collect();
+
+    for order in &orders_sorted {
+        let mut possible_assignments =
+            feasible_placements(&state, order, drivers, vehicles, index, spacing);
+        possible_assignments.sort_by_key(|(driver, _, _)| driver_rank[driver.id.as_str()]);
+
+        if let Some(&(driver, vehicle, slot)) = possible_assignments
+            .iter()
+            .min_by_key(|(driver, _, _)| state.driver_schedules[&driver.id].len())
+        {
+            state.place(order, driver, vehicle, slot, drivers, spacing);
+        } else if order.splittable {
+            assign_split_order(&mut state, order, drivers, vehicles, index, config, rng);
+        }
+    }
+
+    repair_constraints(&mut state, orders);
+    state.score = state.calculate_score(priority_map, drivers, vehicles, orders, index, config);
+    state
+}
+
+// Coarse-to-fine start for large instances. Orders are bucketed by the start of their window
+// into config.two_phase_bucket_minutes, and each bucket is first spread over the vehicles by the
+// order minutes they already carry in it, highest priority first. The fine phase then books
+// every order on a driver of its planned vehicle and only searches the whole fleet when none
+// fits, which keeps most slot searches to a single vehicle. Rejection stats and the relaxed
+// second pass work as in initialize_random_state.
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_two_phase_state(
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    index: &ProblemIndex,
+    priority_map: &HashMap<String, f64>,
+    config: &SolverConfig,
+    rng: &mut impl Rng,
+) -> SolverState {
+    let spacing = Spacing::from_config(config);
+    let mut state = SolverState::with_pinned(drivers, vehicles, index);
+    let bucket_seconds = config.two_phase_bucket_minutes.max(1) * 60;
+    let Some(first_start) = orders.iter().map(|o| o.window().0).min() else {
+        return state;
+    };
+    let mut buckets: BTreeMap<i64, Vec<&Order>> = BTreeMap::new();
+    for order in orders {
+        if !index.pinned_order_ids.contains(&order.id) {
+            let bucket = (order.window().0 - first_start).num_seconds() / bucket_seconds;
+            buckets.entry(bucket).or_default().push(order);
+        }
+    }
+
+    for bucket in buckets.values_mut() {
+        bucket.shuffle(rng);
+        bucket.sort_by(|a, b| {
+            priority_map[&b.id]
+                .partial_cmp(&priority_map[&a.id])
+                .unwrap_or(Ordering::Equal)
+        });
+
+        // Coarse phase: the least loaded vehicle in this bucket that can carry the order
+        let mut load = vec![0i64; vehicles.len()];
+        let planned: Vec<Option<usize>> = bucket
+            .iter()
+            .map(|order| {
+                let pairs = index.compatible_pairs.get(order.id.as_str())?;
+                let vehicle = pairs.iter().map(|&(_, v)| v).min_by_key(|&v| load[v])?;
+                load[vehicle] += order.duration().num_minutes();
+                Some(vehicle)
+            })
+            .collect();
+
+        // Fine phase: the least busy driver who can take the order on the planned vehicle
+        for (order, planned) in bucket.iter().zip(planned) {
+            if config.collect_rejection_stats {
+                record_rejections(&mut state, order, drivers, vehicles, spacing);
+            }
+            let on_planned = planned.and_then(|v| {
+                planned_placement(&state, order, v, drivers, vehicles, index, spacing)
+            });
+            let placement = on_planned.or_else(|| {
+                feasible_placements(&state, order, drivers, vehicles, index, spacing)
+                    .into_iter()
+                    .min_by_key(|(driver, _, _)| state.driver_schedules[&driver.id].len())
+            });
+            if let Some((driver, vehicle, slot)) = placement {
+                state.place(order, driver, vehicle, slot, drivers, spacing);
+            } else if order.splittable {
+                assign_split_order(&mut state, order, drivers, vehicles, index, config, rng);
+            }
+        }
+    }
+
+    // Bucket by bucket, in the sequence the fine phase offered them
+    place_relaxed(
+        &mut state,
+        buckets.values().flatten().copied(),
+        drivers,
+        vehicles,
+        index,
+        config,
+        rng,
+    );
+    repair_constraints(&mut state, orders);
+    state.score = state.calculate_score(priority_map, drivers, vehicles, orders, index, config);
+    state
+}
+
+// The fine phase of initialize_two_phase_state for one order, its placement on vehicles[v] with
+// the driver holding the fewest orders, under the same checks as feasible_placements
+fn planned_placement<'a>(
+    state: &SolverState,
+    order: &Order,
+    v: usize,
+    drivers: &'a [Driver],
+    vehicles: &'a [Vehicle],
+    index: &ProblemIndex,
+    spacing: Spacing,
+) -> Option<(&'a Driver, &'a Vehicle, (NaiveDateTime, NaiveDateTime))> {
+    let vehicle = &vehicles[v];
+    if !state.fleet_allows(vehicle, spacing) {
+        return None;
+    }
+    let vehicle_schedule = &state.vehicle_schedules[&vehicle.id];
+    index.compatible_pairs[order.id.as_str()]
+        .iter()
+        .filter(|&&(_, pv)| pv == v)
+        .filter_map(|&(d, _)| {
+            let driver = &drivers[d];
+            if driver.preference.as_ref() != Some(&vehicle.id)
+                && preferred_vehicle_open(state, order, driver, index, spacing)
+            {
+                return None;
+            }
+            let slot = index.can_assign(
+                order,
+                driver,
+                vehicle,
+                &state.driver_schedules[&driver.id],
+                vehicle_schedule,
+                spacing,
+            )?;
+            let crewed = order.crew_size() == 1
+                || state
+                    .crew_for(order, driver, vehicle, slot, drivers, spacing)
+                    .is_some();
+            crewed.then_some((driver, vehicle, slot))
+        })
+        .min_by_key(|(driver, _, _)| state.driver_schedules[&driver.id].len())
+}
+
+// Builds the initial population. Each individual gets its own generator seeded from
+// base_seed + index, so native builds can initialize in parallel and still produce the
+// same population as the serial wasm build for a given base seed. The first
+// greedy_init_fraction of the population is seeded greedily, the rest randomly in the
+// sequence of config.init_order, alternating with shuffled ones, or coarse to fine under
+// config.two_phase.
+pub fn initialize_population(
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    index: &ProblemIndex,
+    priority_map: &HashMap<String, f64>,
+    config: &SolverConfig,
+    base_seed: u64,
+) -> Vec<SolverState> {
+    let greedy_count = (config.population_size as f64 * config.greedy_init_fraction) as usize;
+    map_indices(config.population_size, |i| {
+        let mut rng = StdRng::seed_from_u64(base_seed.wrapping_add(i as u64));
+        if i < greedy_count {
+            initialize_greedy_state(
+                drivers,
+                vehicles,
+                orders,
+                index,
+                priority_map,
+                config,
+                &mut rng,
+            )
+        } else if config.two_phase {
+            initialize_two_phase_state(
+                drivers,
+                vehicles,
+                orders,
+                index,
+                priority_map,
+                config,
+                &mut rng,
+            )
+        } else {
+            let init_order = if (i - greedy_count).is_multiple_of(2) {
+                config.init_order
+            } else {
+                InitOrder::Shuffle
+            };
+            initialize_random_state(
+                drivers,
+                vehicles,
+                orders,
+                index,
+                priority_map,
+                config,
+                init_order,
+                &mut rng,
+            )
+        }
+    })
+}
+
+// Maps 0..count through f, keeping index order, on rayon's pool in native builds. Every call
+// works on its own index, so the results are the same as the serial wasm build's.
+#[cfg(not(target_arch = "wasm32"))]
+fn map_indices<T: Send>(count: usize, f: impl Fn(usize) -> T + Sync + Send) -> Vec<T> {
+    use rayon::prelude::*;
+    (0..count).into_par_iter().map(f).collect()
+}
+
+// wasm has no threads, fall back to serial evaluation
+#[cfg(target_arch = "wasm32")]
+fn map_indices<T: Send>(count: usize, f: impl Fn(usize) -> T + Sync + Send) -> Vec<T> {
+    (0..count).map(f).collect()
+}
+
+// tournament_win_prob below 1.0 lets the best contender lose: contenders are tried best first
+// and each wins with that probability, the worst takes whatever is left. A size of 1 picks
+// uniformly at random either way.
+pub fn select_parent<'a>(
+    population: &'a [SolverState],
+    strategy: &SelectionStrategy,
+    tournament_win_prob: f64,
+    rng: &mut impl Rng,
+) -> &'a SolverState {
+    match strategy {
+        SelectionStrategy::Tournament { size } if tournament_win_prob < 1.0 => {
+            let mut contenders: Vec<&SolverState> = (0..*size)
+                .map(|_| population.choose(rng).unwrap())
+                .collect();
+            contenders.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+            let last = contenders.len() - 1;
+            contenders
+                .iter()
+                .take(last)
+                .find(|_| rng.gen_bool(tournament_win_prob))
+                .copied()
+                .unwrap_or(contenders[last])
+        }
+        SelectionStrategy::Tournament { size } => {
+            let mut best = population.choose(rng).unwrap();
+            for _ in 1..*size {
+                let contender = population.choose(rng).unwrap();
+                if contender.score > best.score {
+                    best = contender;
+                }
+            }
+            best
+        }
+        SelectionStrategy::RouletteWheel => {
+            // Fitness-proportionate selection, negative scores get no share of the wheel
+            let total: f64 = population.iter().map(|s| s.score.max(0.0)).sum();
+            if total <= 0.0 {
+                return population.choose(rng).unwrap();
+            }
+            let mut pick = rng.gen::<f64>() * total;
+            for state in population {
+                pick -= state.score.max(0.0);
+                if pick < 0.0 {
+                    return state;
+                }
+            }
+            population.last().unwrap()
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn crossover(
+    parent1: &SolverState,
+    parent2: &SolverState,
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    index: &ProblemIndex,
+    priority_map: &HashMap<String, f64>,
+    config: &SolverConfig,
+    rng: &mut impl Rng,
+) -> SolverState {
+    let spacing = Spacing::from_config(config);
+    let mut child = SolverState::with_pinned(drivers, vehicles, index);
+
+    // Decide per order which parent it is primarily inherited from
+    let from_first: Vec<bool> = match config.crossover {
+        CrossoverStrategy::OnePoint => {
+            let point = rng.gen_range(0..orders.len());
+            (0..orders.len()).map(|i| i < point).collect()
+        }
+        CrossoverStrategy::TwoPoint => {
+            let a = rng.gen_range(0..orders.len());
+            let b = rng.gen_range(0..orders.len());
+            let (low, high) = (a.min(b), a.max(b));
+            (0..orders.len()).map(|i| i < low || i >= high).collect()
+        }
+        CrossoverStrategy::Uniform => (0..orders.len()).map(|_| rng.gen_bool(0.5)).collect(),
+    };
+
+    for (i, order) in orders.iter().enumerate() {
+        let (primary, secondary) = if from_first[i] {
+            (parent1, parent2)
+        } else {
+            (parent2, parent1)
+        };
+
+        // With repair enabled, fall back to the other parent's gene when the inherited one
+        // conflicts with what the child already holds
+        let mut inherited = vec![primary];
+        if config.crossover_repair {
+            inherited.push(secondary);
+        }
+
+        for parent in inherited {
+            if order.crew_size() > 1 {
+                if inherit_crew(&mut child, parent, order, index, spacing) {
+                    break;
+                }
+                continue;
+            }
+            // A split order carries one gene per piece
+            let mut inherited_any = false;
+            for assignment in parent
+                .assignments
+                .iter()
+                .filter(|a| a.order_id == order.id && !a.pinned)
+            {
+                let driver = index.drivers[assignment.driver_id.as_str()];
+                let vehicle = index.vehicles[assignment.vehicle_id.as_str()];
+                let piece = assigned_piece(order, assignment);
+                if !child.fleet_allows(vehicle, spacing) {
+                    continue;
+                }
+                if driver.preference.as_ref() != Some(&vehicle.id)
+                    && preferred_vehicle_open(&child, &piece, driver, index, spacing)
+                {
+                    continue;
+                }
+
+                if let Some(slot) = index.can_assign(
+                    &piece,
+                    driver,
+                    vehicle,
+                    &child.driver_schedules[&driver.id],
+                    &child.vehicle_schedules[&vehicle.id],
+                    spacing,
+                ) {
+                    child.assign_order(&piece, driver, vehicle, slot);
+                    inherited_any = true;
+                }
+            }
+            if inherited_any {
+                break;
+            }
+        }
+    }
+
+    // Attempt to assign unassigned orders
+    fill_unassigned(&mut child, drivers, vehicles, orders, index, config, rng);
+
+    repair_constraints(&mut child, orders);
+    child.score = child.calculate_score(priority_map, drivers, vehicles, orders, index, config);
+    child
+}
+
+// Copies the parent's crew of a crew order into the child at its slot, all members or none
+fn inherit_crew(
+    child: &mut SolverState,
+    parent: &SolverState,
+    order: &Order,
+    index: &ProblemIndex,
+    spacing: Spacing,
+) -> bool {
+    let crew: Vec<&Assignment> = parent
+        .assignments
+        .iter()
+        .filter(|a| a.order_id == order.id)
+        .collect();
+    if crew.len() != order.crew_size() {
+        return false;
+    }
+    let vehicle = index.vehicles[crew[0].vehicle_id.as_str()];
+    let slot = (crew[0].start_time, crew[0].end_time);
+    let fits = crew.iter().all(|member| {
+        let driver = index.drivers[member.driver_id.as_str()];
+        child.slot_fits(order, driver, vehicle, slot, spacing)
+    });
+    if fits {
+        for member in crew {
+            child.assign_order(
+                order,
+                index.drivers[member.driver_id.as_str()],
+                vehicle,
+                slot,
+            );
+        }
+    }
+    fits
+}
+
+// Replays the plan in start order into empty schedules and returns the first order whose slot
+// fails the driver or vehicle checks against the orders before it. Relaxed assignments are
+// replayed without being checked, they break the limits on purpose.
+fn first_infeasible_assignment(
+    state: &SolverState,
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    index: &ProblemIndex,
+    config: &SolverConfig,
+) -> Option<String> {
+    let spacing = Spacing::from_config(config);
+    let mut assignments: Vec<&Assignment> = state.assignments.iter().collect();
+    assignments.sort_by_key(|a| (a.start_time, a.end_time));
+
+    let mut replay = SolverState::new(drivers, vehicles);
+    for assignment in assignments {
+        let order = index.orders[assignment.order_id.as_str()];
+        let driver = index.drivers[assignment.driver_id.as_str()];
+        let vehicle = index.vehicles[assignment.vehicle_id.as_str()];
+        let piece = assigned_piece(order, assignment);
+        let slot = (assignment.start_time, assignment.end_time);
+        let feasible =
+            assignment.relaxed || replay.slot_fits(&piece, driver, vehicle, slot, spacing);
+        if !feasible {
+            return Some(assignment.order_id.clone());
+        }
+        replay.assign_order(&piece, driver, vehicle, slot);
+    }
+    None
+}
+
+// Places every order without any assignment on a random feasible pair, splitting it when that
+// is allowed and no single pair fits
+pub fn fill_unassigned(
+    state: &mut SolverState,
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    index: &ProblemIndex,
+    config: &SolverConfig,
+    rng: &mut impl Rng,
+) {
+    let spacing = Spacing::from_config(config);
+    for order in orders {
+        if state.assignments.iter().any(|a| a.order_id == order.id) {
+            continue;
+        }
+        let possible_assignments =
+            feasible_placements(state, order, drivers, vehicles, index, spacing);
+        if let Some(&(driver, vehicle, slot)) = possible_assignments.choose(rng) {
+            state.place(order, driver, vehicle, slot, drivers, spacing);
+        } else if order.splittable {
+            assign_split_order(state, order, drivers, vehicles, index, config, rng);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn mutate(
+    individual: &mut SolverState,
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    index: &ProblemIndex,
+    priority_map: &HashMap<String, f64>,
+    config: &SolverConfig,
+    rng: &mut impl Rng,
+) {
+    let spacing = Spacing::from_config(config);
+    // Randomly select an assignment to mutate
+    if individual.assignments.is_empty() {
+        return;
+    }
+
+    let movable: Vec<usize> = (0..individual.assignments.len())
+        .filter(|&i| !individual.assignments[i].pinned)
+        .collect();
+    if movable.is_empty() {
+        return;
+    }
+    let idx = movable[rng.gen_range(0..movable.len())];
+    // Remove the assignment
+    let assignment = individual.unassign(idx);
+    let order = index.orders[assignment.order_id.as_str()];
+    let piece = assigned_piece(order, &assignment);
+    let order: &Order = &piece;
+
+    // Attempt to reassign the order to a different driver and vehicle
+    let possible_assignments: Vec<_> =
+        feasible_placements(individual, order, drivers, vehicles, index, spacing)
+            .into_iter()
+            .filter(|(driver, vehicle, _)| {
+                driver.id != assignment.driver_id && vehicle.id != assignment.vehicle_id
+            })
+            .collect();
+
+    // Moving one member of a crew books a whole new crew, repair_crews drops the old one
+    if let Some(&(new_driver, new_vehicle, slot)) = possible_assignments.choose(rng) {
+        individual.place(order, new_driver, new_vehicle, slot, drivers, spacing);
+    }
+
+    repair_constraints(individual, orders);
+    individual.score =
+        individual.calculate_score(priority_map, drivers, vehicles, orders, index, config);
+}
+
+// Runs the genetic algorithm without any JS dependency. Inputs are expected to have passed
+// validate_inputs and config.validate().
+pub fn solve(
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    config: &SolverConfig,
+) -> SchedulingResponse {
+    solve_with_observer(drivers, vehicles, orders, config, &mut ())
+}
+
+pub fn solve_with_observer(
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    config: &SolverConfig,
+    observer: &mut impl SolveObserver,
+) -> SchedulingResponse {
+    if config.dedupe {
+        let (unique, duplicates) = collapse_duplicates(orders, config);
+        if !duplicates.is_empty() {
+            let collapsed = SolverConfig {
+                dedupe: false,
+                ..config.clone()
+            };
+            let mut response =
+                solve_with_observer(drivers, vehicles, &unique, &collapsed, observer);
+            expand_duplicates(&mut response, vehicles, orders, &duplicates, config);
+            return response;
+        }
+    }
+
+    // Initialize parameters
+    let generations = config.generations; // Maximum number of generations
+    let population_size = config.population_size;
+    let mutation_rate = config.mutation_rate;
+    let mut current_mutation_rate = mutation_rate;
+    let stopwatch = Stopwatch::start();
+    let max_duration = config.max_duration_ms;
+
+    // Map for order priorities
+    let order_priority_map = build_priority_map(orders, config);
+    let mut index = ProblemIndex::new(drivers, vehicles, orders);
+    // Pins are expected to have passed check_pinned, one that fails here is left out together
+    // with the pins after it
+    let mut pinned = SolverState::new(drivers, vehicles);
+    let _ = place_pinned(&mut pinned, &index, config);
+    index.pin(pinned.assignments);
+
+    let seed_used = seed_or_random(config);
+    let mut rng = StdRng::seed_from_u64(seed_used);
+
+    // Initialize one population per island, a single island is the plain GA
+    let island_count = config.islands.max(1);
+    let mut islands: Vec<Vec<SolverState>> = (0..island_count)
+        .map(|_| {
+            initialize_population(
+                drivers,
+                vehicles,
+                orders,
+                &index,
+                &order_priority_map,
+                config,
+                rng.gen(),
+            )
+        })
+        .collect();
+    if let Some(previous) = &config.warm_start {
+        let warm = warm_start_state(
+            previous,
+            drivers,
+            vehicles,
+            orders,
+            &index,
+            &order_priority_map,
+            config,
+            &mut rng,
+        );
+        if let Some(last) = islands[0].last_mut() {
+            *last = warm;
+        }
+    }
+
+    let initialization_ms = stopwatch.elapsed_ms();
+
+    // Sum the rejections every individual met while being initialized
+    let rejection_stats = config.collect_rejection_stats.then(|| {
+        let mut totals: HashMap<Rejection, usize> = HashMap::new();
+        for state in islands.iter().flatten() {
+            for (&rejection, &count) in &state.rejection_counts {
+                *totals.entry(rejection).or_insert(0) += count;
+            }
+        }
+        let mut stats: Vec<RejectionCount> = totals
+            .into_iter()
+            .map(|(reason, count)| RejectionCount { reason, count })
+            .collect();
+        stats.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.reason.cmp(&b.reason)));
+        stats
+    });
+
+    let mut generation = 0;
+    let mut cancelled = false;
+    let mut restarts = 0;
+    let mut pareto = config
+        .return_pareto
+        .then(|| ParetoArchive::new(config.pareto_front_size));
+
+    // At least one elite survives so the best score never decreases
+    let elite_count = ((population_size as f64 * config.elite_fraction).ceil() as usize)
+        .clamp(1, population_size);
+
+    // Initialize variables for termination criterion
+    let mut best_score = best_individual(&islands).score;
+    let mut generations_without_improvement = 0;
+    let max_generations_without_improvement = config.stall_limit();
+    let max_score = score_upper_bound(orders, drivers, &order_priority_map, config);
+    // Stalls reset on improvement and restarts, the reported fraction holds its highest value
+    let mut progress_fraction: f64 = 0.0;
+
+    // Use generations variable to control the loop
+    while (config.ignore_time_budget || stopwatch.elapsed_ms() < max_duration)
+        && generation < generations
+    {
+        // Evaluate fitness
+        for population in &mut islands {
+            population.sort_by(SolverState::rank);
+        }
+        if let Some(archive) = &mut pareto {
+            for state in islands.iter().flatten() {
+                archive.offer(state, &order_priority_map, &index, orders, config);
+            }
+        }
+        let best = best_individual(&islands);
+        let current_best_score = best.score;
+        // Elites are carried over unchanged, so the best individual can only get better
+        debug_assert!(
+            generation == 0 || current_best_score >= best_score,
+            "best score dropped from {} to {}",
+            best_score,
+            current_best_score
+        );
+
+        // Report progress
+        let mut estimate = (generation + 1) as f64 / generations as f64;
+        if !config.ignore_time_budget && max_duration > 0.0 {
+            estimate = estimate.max(stopwatch.elapsed_ms() / max_duration);
+        }
+        // Each restart left to use grants another full stall limit
+        if max_generations_without_improvement > 0 {
+            let limit = max_generations_without_improvement as f64;
+            let stalled = restarts as f64 * limit + generations_without_improvement as f64;
+            estimate = estimate.max(stalled / ((config.max_restarts as f64 + 1.0) * limit));
+        }
+        progress_fraction = progress_fraction.max(estimate.min(1.0));
+        let individuals = islands.iter().flatten();
+        let mut progress = ProgressUpdate {
+            generation: generation + 1,
+            best_score: current_best_score,
+            avg_score: individuals.clone().map(|s| s.score).sum::<f64>()
+                / individuals.count() as f64,
+            assigned_count: best.assignments.len(),
+            progress_fraction,
+            message: format!(
+                "Generation {}: Best Score {:.2}",
+                generation + 1,
+                current_best_score
+            ),
+            snapshot: (config.snapshot_interval > 0
+                && (generation + 1) % config.snapshot_interval == 0)
+                .then(|| {
+                    let mut snapshot = best.to_response(
+                        &order_priority_map,
+                        drivers,
+                        vehicles,
+                        orders,
+                        &index,
+                        config,
+                    );
+                    snapshot.seed_used = seed_used;
+                    snapshot
+                }),
+        };
+        observer.on_progress(&progress);
+
+        // Check for improvement. The initial population has nothing to improve on yet, so only
+        // bred generations count as a stall.
+        if config.improves_on(best_score, current_best_score) {
+            best_score = current_best_score;
+            generations_without_improvement = 0;
+            current_mutation_rate = mutation_rate;
+        } else if generation > 0 {
+            generations_without_improvement += 1;
+            // Push harder on exploration while the population is stuck on a plateau
+            current_mutation_rate =
+                config.ramped_mutation_rate(current_mutation_rate, generations_without_improvement);
+        }
+
+        if observer.should_cancel() {
+            progress.message = "Cancelled, returning the best solution so far.".to_string();
+            observer.on_progress(&progress);
+            cancelled = true;
+            break;
+        }
+
+        // Nothing left to gain once the best solution reaches the upper bound
+        if current_best_score >= max_score - 1e-9 {
+            progress.message =
+                "Best score reached the maximum possible score, terminating.".to_string();
+            progress.progress_fraction = 1.0;
+            observer.on_progress(&progress);
+            break;
+        }
+
+        // Restart part of the population, or terminate once no restarts are left, when there
+        // was no improvement over the threshold
+        if generations_without_improvement >= max_generations_without_improvement {
+            if restarts >= config.max_restarts {
+                progress.message = format!(
+                    "No improvement over {} generations, terminating.",
+                    max_generations_without_improvement
+                );
+                progress.progress_fraction = 1.0;
+                observer.on_progress(&progress);
+                break;
+            }
+            restarts += 1;
+            generations_without_improvement = 0;
+            current_mutation_rate = mutation_rate;
+            for population in &mut islands {
+                restart_population(
+                    population,
+                    elite_count,
+                    drivers,
+                    vehicles,
+                    orders,
+                    &index,
+                    &order_priority_map,
+                    config,
+                    &mut rng,
+                );
+            }
+            progress.message = format!(
+                "No improvement over {} generations, restart {} of {}.",
+                max_generations_without_improvement, restarts, config.max_restarts
+            );
+            observer.on_progress(&progress);
+        }
+
+        // Islands evolve independently, in order, from the shared generator
+        for population in &mut islands {
+            *population = next_generation(
+                population,
+                elite_count,
+                drivers,
+                vehicles,
+                orders,
+                &index,
+                &order_priority_map,
+                config,
+                current_mutation_rate,
+                &mut rng,
+            );
+        }
+        generation += 1;
+
+        if island_count > 1 && generation % config.migration_interval == 0 {
+            migrate(&mut islands, elite_count);
+        }
+    }
+
+    // Return the best solution
+    for population in &mut islands {
+        population.sort_by(SolverState::rank);
+    }
+    let evolution_ms = stopwatch.elapsed_ms() - initialization_ms;
+    let mut best = best_individual(&islands).clone();
+    debug_assert!(
+        index.pinned.iter().all(|pin| best
+            .assignments
+            .iter()
+            .any(|a| a.pinned && a.order_id == pin.order_id && a.start_time == pin.start_time)),
+        "a pinned assignment was lost"
+    );
+    let mut local_search_ms = 0.0;
+    if config.local_search && !cancelled {
+        let local_search_start_ms = stopwatch.elapsed_ms();
+        local_search(
+            &mut best,
+            drivers,
+            vehicles,
+            orders,
+            &index,
+            &order_priority_map,
+            config,
+        );
+        local_search_ms = stopwatch.elapsed_ms() - local_search_start_ms;
+    }
+    rebalance_drivers(
+        &mut best,
+        drivers,
+        vehicles,
+        orders,
+        &index,
+        &order_priority_map,
+        config,
+    );
+    compact_schedule(
+        &mut best,
+        drivers,
+        vehicles,
+        orders,
+        &index,
+        &order_priority_map,
+        config,
+    );
+    let mut response = best.to_response(
+        &order_priority_map,
+        drivers,
+        vehicles,
+        orders,
+        &index,
+        config,
+    );
+    if config.return_population {
+        let mut individuals: Vec<&SolverState> = islands.iter().flatten().collect();
+        individuals.sort_by(|a, b| SolverState::rank(a, b));
+        response.population = Some(
+            individuals
+                .into_iter()
+                .map(|state| {
+                    state.to_response(
+                        &order_priority_map,
+                        drivers,
+                        vehicles,
+                        orders,
+                        &index,
+                        config,
+                    )
+                })
+                .collect(),
+        );
+    }
+    if let Some(mut archive) = pareto {
+        // The last generation and the refined plan were never offered inside the loop
+        for state in islands.iter().flatten().chain([&best]) {
+            archive.offer(state, &order_priority_map, &index, orders, config);
+        }
+        response.pareto_front = Some(archive.into_solutions(
+            &order_priority_map,
+            drivers,
+            vehicles,
+            orders,
+            &index,
+            config,
+        ));
+    }
+    response.cancelled = cancelled;
+    response.rejection_stats = rejection_stats;
+    response.seed_used = seed_used;
+    response.warnings = input_warnings(vehicles, orders, config);
+    response.metrics = SolverMetrics {
+        generations: generation,
+        restarts,
+        wall_time_ms: stopwatch.elapsed_ms(),
+        initialization_ms,
+        evolution_ms,
+        local_search_ms,
+        feasibility_checks: index.feasibility_checks(),
+    };
+    response
+}
+
+// Individual built from an earlier response: its assignments are replayed at their times, the
+// ones that no longer fit are dropped and the orders left over are placed randomly
+#[allow(clippy::too_many_arguments)]
+fn warm_start_state(
+    previous: &SchedulingResponse,
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    index: &ProblemIndex,
+    priority_map: &HashMap<String, f64>,
+    config: &SolverConfig,
+    rng: &mut StdRng,
+) -> SolverState {
+    let spacing = Spacing::from_config(config);
+    let mut state = SolverState::with_pinned(drivers, vehicles, index);
+    for assignment in &previous.assignments {
+        let (Some(&order), Some(&driver), Some(&vehicle)) = (
+            index.orders.get(assignment.order_id.as_str()),
+            index.drivers.get(assignment.driver_id.as_str()),
+            index.vehicles.get(assignment.vehicle_id.as_str()),
+        ) else {
+            continue;
+        };
+        if index.pinned_order_ids.contains(&order.id)
+            || (!order.splittable && state.assignments.iter().any(|a| a.order_id == order.id))
+        {
+            continue;
+        }
+        let piece = assigned_piece(order, assignment);
+        let slot = (assignment.start_time, assignment.end_time);
+        if state.slot_fits(&piece, driver, vehicle, slot, spacing) {
+            state.assign_order(&piece, driver, vehicle, slot);
+        }
+    }
+    fill_unassigned(&mut state, drivers, vehicles, orders, index, config, rng);
+    repair_constraints(&mut state, orders);
+    state.score = state.calculate_score(priority_map, drivers, vehicles, orders, index, config);
+    state
+}
+
+// Best individual over all islands, every island must already be sorted by rank
+fn best_individual(islands: &[Vec<SolverState>]) -> &SolverState {
+    islands
+        .iter()
+        .map(|population| &population[0])
+        .min_by(|a, b| SolverState::rank(a, b))
+        .unwrap()
+}
+
+// Elitism plus crossover and mutation for one island, population must be sorted by rank
+#[allow(clippy::too_many_arguments)]
+fn next_generation(
+    population: &[SolverState],
+    elite_count: usize,
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    index: &ProblemIndex,
+    priority_map: &HashMap<String, f64>,
+    config: &SolverConfig,
+    mutation_rate: f64,
+    rng: &mut StdRng,
+) -> Vec<SolverState> {
+    // Each child draws from its own generator seeded from one base seed plus its index, so
+    // children are bred in parallel and still come out the same on every build
+    let base_seed: u64 = rng.gen();
+    let children = map_indices(population.len() - elite_count, |i| {
+        let mut rng = StdRng::seed_from_u64(base_seed.wrapping_add(i as u64));
+        let parent1 = select_parent(
+            population,
+            &config.selection,
+            config.tournament_win_prob,
+            &mut rng,
+        );
+        let parent2 = select_parent(
+            population,
+            &config.selection,
+            config.tournament_win_prob,
+            &mut rng,
+        );
+        // crossover and mutate both leave the child scored
+        let mut child = crossover(
+            parent1,
+            parent2,
+            drivers,
+            vehicles,
+            orders,
+            index,
+            priority_map,
+            config,
+            &mut rng,
+        );
+        if rng.gen::<f64>() < mutation_rate {
+            mutate(
+                &mut child,
+                drivers,
+                vehicles,
+                orders,
+                index,
+                priority_map,
+                config,
+                &mut rng,
+            );
+        }
+        child
+    });
+
+    let mut new_population: Vec<SolverState> = population[..elite_count].to_vec();
+    new_population.extend(children);
+    new_population
+}
+
+// Replaces restart_fraction of the non-elite individuals, worst first, with fresh random ones.
+// The population must be sorted best first.
+#[allow(clippy::too_many_arguments)]
+fn restart_population(
+    population: &mut [SolverState],
+    elite_count: usize,
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    index: &ProblemIndex,
+    priority_map: &HashMap<String, f64>,
+    config: &SolverConfig,
+    rng: &mut impl Rng,
+) {
+    let non_elite = population.len().saturating_sub(elite_count);
+    let count = ((non_elite as f64 * config.restart_fraction).ceil() as usize).min(non_elite);
+    for individual in population.iter_mut().rev().take(count) {
+        *individual = initialize_random_state(
+            drivers,
+            vehicles,
+            orders,
+            index,
+            priority_map,
+            config,
+            config.init_order,
+            rng,
+        );
+    }
+}
+
+// Copies each island's elite over the worst individuals of the next island in the ring. Only
+// non-elite slots are overwritten, so no island loses its own best.
+fn migrate(islands: &mut [Vec<SolverState>], elite_count: usize) {
+    for population in islands.iter_mut() {
+        population.sort_by(SolverState::rank);
+    }
+    let migrants: Vec<Vec<SolverState>> = islands
+        .iter()
+        .map(|population| population[..elite_count].to_vec())
+        .collect();
+    let island_count = islands.len();
+    for (from, elite) in migrants.into_iter().enumerate() {
+        let population = &mut islands[(from + 1) % island_count];
+        let replaced = elite_count.min(population.len() - elite_count);
+        let keep = population.len() - replaced;
+        population.truncate(keep);
+        population.extend(elite.into_iter().take(replaced));
+    }
+}
+
+// Largest integer a JS number holds exactly, seeds never exceed it
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+// The configured seed, otherwise a fresh one from entropy. Drawn seeds stay below 2^53 so they
+// survive the round trip through a JS number.
+fn seed_or_random(config: &SolverConfig) -> u64 {
+    config
+        .seed
+        .unwrap_or_else(|| StdRng::from_entropy().gen_range(0..=MAX_SAFE_INTEGER))
+}
+
+// One independent instance of a batch, config falls back to the defaults when absent
+#[derive(Clone, Debug, Deserialize)]
+pub struct Problem {
+    pub drivers: Vec<Driver>,
+    pub vehicles: Vec<Vehicle>,
+    pub orders: Vec<Order>,
+    #[serde(default)]
+    pub config: Option<SolverConfig>,
+}
+
+// Solves every problem in turn with its own config, responses come back in input order
+pub fn solve_batch(problems: &[Problem]) -> Vec<SchedulingResponse> {
+    problems
+        .iter()
+        .map(|problem| {
+            let config = problem.config.clone().unwrap_or_default();
+            solve(
+                &problem.drivers,
+                &problem.vehicles,
+                &problem.orders,
+                &config,
+            )
+        })
+        .collect()
+}
+
+// Seed of the run-th run of solve_best_of, seed + run modulo 2^53
+fn run_seed(seed: u64, run: u32) -> u64 {
+    const SEEDS: u64 = MAX_SAFE_INTEGER + 1;
+    (seed % SEEDS + u64::from(run)) % SEEDS
+}
+
+// Runs the solver `runs` times and keeps the best response, with every run's score attached.
+// Seeded configs use seed, seed + 1, ... modulo 2^53, so the whole batch stays reproducible and
+// every seed_used stays a safe JavaScript integer. Each run gets
+// the full max_duration_ms, so the wall time can reach runs times the budget.
+pub fn solve_best_of(
+    runs: u32,
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    config: &SolverConfig,
+    observer: &mut impl SolveObserver,
+) -> SchedulingResponse {
+    let mut best: Option<SchedulingResponse> = None;
+    let mut run_scores = Vec::new();
+    for run in 0..runs.max(1) {
+        let run_config = SolverConfig {
+            seed: config.seed.map(|seed| run_seed(seed, run)),
+            ..config.clone()
+        };
+        let response = solve_with_observer(drivers, vehicles, orders, &run_config, observer);
+        run_scores.push(response.score);
+        let cancelled = response.cancelled;
+        if best.as_ref().is_none_or(|b| response.score > b.score) {
+            best = Some(response);
+        }
+        // Skip the remaining runs once the caller gave up
+        if cancelled {
+            best.as_mut().unwrap().cancelled = true;
+            break;
+        }
+    }
+    let mut best = best.unwrap();
+    best.run_scores = Some(run_scores);
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixtures::{assignment, at, driver, on, order, random_instance, response, vehicle};
+    use super::*;
+
+    // Small seeded run that does not depend on machine speed
+    fn quick(config: SolverConfig) -> SolverConfig {
+        SolverConfig {
+            seed: Some(1),
+            population_size: 16,
+            generations: 30,
+            ignore_time_budget: true,
+            ..config
+        }
+    }
+
+    // Earliest slot for the order next to what the state already holds
+    fn slot_in(
+        state: &SolverState,
+        order: &Order,
+        driver: &Driver,
+        vehicle: &Vehicle,
+        config: &SolverConfig,
+    ) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        can_assign(
+            order,
+            driver,
+            vehicle,
+            &state.driver_schedules[&driver.id],
+            &state.vehicle_schedules[&vehicle.id],
+            Spacing::from_config(config),
+        )
+    }
+
+    fn assigned_ids(response: &SchedulingResponse) -> Vec<&str> {
+        let mut ids: Vec<&str> = response
+            .assignments
+            .iter()
+            .map(|a| a.order_id.as_str())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn map_indices_keeps_index_order() {
+        for count in [0, 1, 5, 9, 50, 1000] {
+            let serial: Vec<usize> = (0..count).map(|i| i * i).collect();
+            assert_eq!(map_indices(count, |i| i * i), serial);
+        }
+    }
+
+    #[test]
+    fn parallel_initialization_and_breeding_match_serial() {
+        let (drivers, vehicles, orders) = random_instance(40, 6, 4, 1);
+        let config = SolverConfig {
+            population_size: 12,
+            greedy_init_fraction: 0.0,
+            ..SolverConfig::default()
+        };
+        let index = ProblemIndex::new(&drivers, &vehicles, &orders);
+        let priority_map = build_priority_map(&orders, &config);
+        let build = |i: usize| {
+            let mut rng = StdRng::seed_from_u64(100 + i as u64);
+            initialize_random_state(
+                &drivers,
+                &vehicles,
+                &orders,
+                &index,
+                &priority_map,
+                &config,
+                InitOrder::Shuffle,
+                &mut rng,
+            )
+        };
+
+        let serial: Vec<SolverState> = (0..12).map(build).collect();
+        // initialize_population seeds individual i from base_seed + i the same way
+        let mut population = initialize_population(
+            &drivers,
+            &vehicles,
+            &orders,
+            &index,
+            &priority_map,
+            &config,
+            100,
+        );
+        for (a, b) in serial.iter().zip(&population) {
+            assert_eq!(a.assignments, b.assignments);
+            assert_eq!(a.score, b.score);
+        }
+
+        // Children bred in parallel arrive scored, and the same generator gives the same brood
+        population.sort_by(SolverState::rank);
+        let breed = |seed: u64| {
+            next_generation(
+                &population,
+                2,
+                &drivers,
+                &vehicles,
+                &orders,
+                &index,
+                &priority_map,
+                &config,
+                0.5,
+                &mut StdRng::seed_from_u64(seed),
+            )
+        };
+        let children = breed(7);
+        assert_eq!(children.len(), population.len());
+        for child in &children {
+            assert_eq!(
+                child.score,
+                child.calculate_score(&priority_map, &drivers, &vehicles, &orders, &index, &config)
+            );
+        }
+        for (a, b) in children.iter().zip(&breed(7)) {
+            assert_eq!(a.fingerprint(), b.fingerprint());
+        }
+    }
+
+    #[test]
+    fn fingerprint_ignores_assignment_order_and_tracks_changes() {
+        let drivers = [driver("d1"), driver("d2")];
+        let vehicles = [vehicle("v1"), vehicle("v2")];
+        let orders = [
+            order("o1", at(8, 0), at(9, 0)),
+            order("o2", at(8, 0), at(9, 0)),
+        ];
+        let slot = (at(8, 0), at(9, 0));
+        let mut forward = SolverState::new(&drivers, &vehicles);
+        forward.assign_order(&orders[0], &drivers[0], &vehicles[0], slot);
+        forward.assign_order(&orders[1], &drivers[1], &vehicles[1], slot);
+        let mut backward = SolverState::new(&drivers, &vehicles);
+        backward.assign_order(&orders[1], &drivers[1], &vehicles[1], slot);
+        backward.assign_order(&orders[0], &drivers[0], &vehicles[0], slot);
+        assert_eq!(forward.fingerprint(), backward.fingerprint());
+        assert_eq!(SolverState::rank(&forward, &backward), Ordering::Equal);
+        // Pinned so a change to the hashing shows up as a change to tie-breaking
+        assert_eq!(forward.fingerprint(), 10070177905382749210);
+
+        let mut swapped = SolverState::new(&drivers, &vehicles);
+        swapped.assign_order(&orders[0], &drivers[1], &vehicles[1], slot);
+        swapped.assign_order(&orders[1], &drivers[0], &vehicles[0], slot);
+        assert_ne!(swapped.fingerprint(), forward.fingerprint());
+        let expected = SolverState::rank(&forward, &swapped);
+        assert_ne!(expected, Ordering::Equal);
+        assert_eq!(SolverState::rank(&swapped, &forward), expected.reverse());
+
+        let before = forward.fingerprint();
+        assert_eq!(forward.unassign(1).order_id, "o2");
+        assert_ne!(forward.fingerprint(), before);
+        forward.assign_order(&orders[1], &drivers[1], &vehicles[1], slot);
+        assert_eq!(forward.fingerprint(), before);
+    }
+
+    // Children may only hold assignments that replay cleanly, and each order at most once per
+    // piece or crew member
+    fn assert_feasible_child(
+        child: &SolverState,
+        drivers: &[Driver],
+        vehicles: &[Vehicle],
+        orders: &[Order],
+        index: &ProblemIndex,
+        config: &SolverConfig,
+    ) {
+        assert_eq!(
+            first_infeasible_assignment(child, drivers, vehicles, index, config),
+            None
+        );
+        for order in orders.iter().filter(|o| !o.splittable) {
+            let count = child
+                .assignments
+                .iter()
+                .filter(|a| a.order_id == order.id)
+                .count();
+            assert!(
+                count <= order.crew_size(),
+                "{} assigned {} times",
+                order.id,
+                count
+            );
+        }
+    }
+
+    #[test]
+    fn crossover_children_stay_feasible_on_random_instances() {
+        for seed in 0..20 {
+            let (drivers, vehicles, orders) = random_instance(30, 5, 4, seed);
+            let index = ProblemIndex::new(&drivers, &vehicles, &orders);
+            for strategy in [
+                CrossoverStrategy::OnePoint,
+                CrossoverStrategy::TwoPoint,
+                CrossoverStrategy::Uniform,
+            ] {
+                for crossover_repair in [false, true] {
+                    let config = SolverConfig {
+                        crossover: strategy,
+                        crossover_repair,
+                        ..SolverConfig::default()
+                    };
+                    let priority_map = build_priority_map(&orders, &config);
+                    let mut rng = StdRng::seed_from_u64(seed);
+                    let mut parent = || {
+                        initialize_random_state(
+                            &drivers,
+                            &vehicles,
+                            &orders,
+                            &index,
+                            &priority_map,
+                            &config,
+                            InitOrder::Shuffle,
+                            &mut rng,
+                        )
+                    };
+                    let (parent1, parent2) = (parent(), parent());
+                    let mut rng = StdRng::seed_from_u64(seed + 1000);
+                    let child = crossover(
+                        &parent1,
+                        &parent2,
+                        &drivers,
+                        &vehicles,
+                        &orders,
+                        &index,
+                        &priority_map,
+                        &config,
+                        &mut rng,
+                    );
+                    assert_feasible_child(&child, &drivers, &vehicles, &orders, &index, &config);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn crossover_does_not_combine_conflicting_genes() {
+        // Each parent puts a different overlapping order on d1, the child can only keep one there
+        let drivers = [driver("d1"), driver("d2")];
+        let vehicles = [vehicle("v1"), vehicle("v2")];
+        let orders = [
+            order("o1", at(8, 0), at(9, 0)),
+            order("o2", at(8, 30), at(9, 30)),
+        ];
+        let index = ProblemIndex::new(&drivers, &vehicles, &orders);
+        let config = SolverConfig {
+            crossover: CrossoverStrategy::Uniform,
+            ..SolverConfig::default()
+        };
+        let priority_map = build_priority_map(&orders, &config);
+        let mut parent1 = SolverState::new(&drivers, &vehicles);
+        parent1.assign_order(&orders[0], &drivers[0], &vehicles[0], (at(8, 0), at(9, 0)));
+        let mut parent2 = SolverState::new(&drivers, &vehicles);
+        parent2.assign_order(
+            &orders[1],
+            &drivers[0],
+            &vehicles[0],
+            (at(8, 30), at(9, 30)),
+        );
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let child = crossover(
+            &parent1,
+            &parent2,
+            &drivers,
+            &vehicles,
+            &orders,
+            &index,
+            &priority_map,
+            &config,
+            &mut rng,
+        );
+        assert_feasible_child(&child, &drivers, &vehicles, &orders, &index, &config);
+        // Both orders fit once the second one moves to the other pair
+        assert_eq!(child.assignments.len(), 2);
+        let on_d1: Vec<&Assignment> = child
+            .assignments
+            .iter()
+            .filter(|a| a.driver_id == "d1")
+            .collect();
+        assert_eq!(on_d1.len(), 1);
+        assert!(
+            on_d1[0] == &assignment("o1", "d1", "v1", (at(8, 0), at(9, 0)))
+                || on_d1[0] == &assignment("o2", "d1", "v1", (at(8, 30), at(9, 30)))
+        );
+    }
+
+    #[test]
+    fn concurrent_solves_count_only_their_own_feasibility_checks() {
+        let (drivers, vehicles, orders) = random_instance(30, 5, 4, 3);
+        let config = SolverConfig {
+            seed: Some(9),
+            population_size: 8,
+            generations: 5,
+            ignore_time_budget: true,
+            ..SolverConfig::default()
+        };
+        let alone = solve(&drivers, &vehicles, &orders, &config).metrics;
+        assert!(alone.feasibility_checks > 0);
+        let concurrent: Vec<u64> = std::thread::scope(|scope| {
+            let solves: Vec<_> = (0..3)
+                .map(|_| scope.spawn(|| solve(&drivers, &vehicles, &orders, &config)))
+                .collect();
+            solves
+                .into_iter()
+                .map(|solve| solve.join().unwrap().metrics.feasibility_checks)
+                .collect()
+        });
+        assert_eq!(concurrent, vec![alone.feasibility_checks; 3]);
+    }
+
+    #[test]
+    fn two_phase_init_relaxes_and_collects_rejections() {
+        let drivers = [driver("d1")];
+        let vehicles = [vehicle("v1")];
+        // o2 overlaps o1 and only ever counts a rejection, o3 fits only with overflow
+        let orders = [
+            order("o1", at(8, 0), at(9, 0)),
+            order("o2", at(8, 30), at(9, 30)),
+            Order {
+                weight: 110.0,
+                ..order("o3", at(11, 0), at(12, 0))
+            },
+        ];
+        let index = ProblemIndex::new(&drivers, &vehicles, &orders);
+        let config = SolverConfig {
+            two_phase: true,
+            collect_rejection_stats: true,
+            relaxation: Relaxation {
+                break_slack_minutes: 0,
+                allow_capacity_overflow_pct: 20.0,
+            },
+            ..SolverConfig::default()
+        };
+        let priority_map = build_priority_map(&orders, &config);
+        let state = initialize_two_phase_state(
+            &drivers,
+            &vehicles,
+            &orders,
+            &index,
+            &priority_map,
+            &config,
+            &mut StdRng::seed_from_u64(1),
+        );
+        let relaxed: Vec<&str> = state
+            .assignments
+            .iter()
+            .filter(|a| a.relaxed)
+            .map(|a| a.order_id.as_str())
+            .collect();
+        assert_eq!(relaxed, vec!["o3"]);
+        assert!(state.rejection_counts.get(&Rejection::Weight) >= Some(&1));
+        assert!(state.rejection_counts.get(&Rejection::Overlap) >= Some(&1));
+    }
+
+    #[test]
+    fn touching_slots_do_not_overlap() {
+        assert!(!orders_overlap((at(8, 0), at(9, 0)), (at(9, 0), at(10, 0))));
+        assert!(!orders_overlap((at(9, 0), at(10, 0)), (at(8, 0), at(9, 0))));
+        assert!(!orders_overlap(
+            (at(8, 0), at(9, 0)),
+            (at(9, 30), at(10, 0))
+        ));
+    }
+
+    #[test]
+    fn contained_and_identical_slots_overlap() {
+        assert!(orders_overlap((at(8, 0), at(12, 0)), (at(9, 0), at(10, 0))));
+        assert!(orders_overlap((at(9, 0), at(10, 0)), (at(8, 0), at(12, 0))));
+        assert!(orders_overlap((at(8, 0), at(9, 0)), (at(8, 0), at(9, 0))));
+        assert!(orders_overlap((at(8, 0), at(9, 0)), (at(8, 59), at(10, 0))));
+    }
+
+    #[test]
+    fn a_gap_equal_to_the_mandatory_break_is_sufficient() {
+        let mandatory_break = ChronoDuration::minutes(30);
+        let first = (at(8, 0), at(9, 0));
+        assert!(!insufficient_break(
+            first,
+            (at(9, 30), at(10, 0)),
+            mandatory_break
+        ));
+        assert!(!insufficient_break(
+            (at(9, 30), at(10, 0)),
+            first,
+            mandatory_break
+        ));
+        assert!(insufficient_break(
+            first,
+            (at(9, 29), at(10, 0)),
+            mandatory_break
+        ));
+        assert!(insufficient_break(
+            (at(9, 29), at(10, 0)),
+            first,
+            mandatory_break
+        ));
+        // Touching slots leave no gap at all
+        assert!(insufficient_break(
+            first,
+            (at(9, 0), at(10, 0)),
+            mandatory_break
+        ));
+        assert!(!insufficient_break(
+            first,
+            (at(9, 0), at(10, 0)),
+            ChronoDuration::zero()
+        ));
+    }
+
+    #[test]
+    fn overlapping_slots_are_not_an_insufficient_break() {
+        let mandatory_break = ChronoDuration::minutes(30);
+        assert!(!insufficient_break(
+            (at(8, 0), at(12, 0)),
+            (at(9, 0), at(10, 0)),
+            mandatory_break
+        ));
+        assert!(!insufficient_break(
+            (at(8, 0), at(9, 0)),
+            (at(8, 0), at(9, 0)),
+            mandatory_break
+        ));
+    }
+
+    #[test]
+    fn back_to_back_orders_fit_without_a_mandatory_break() {
+        let drivers = [driver("d1")];
+        let vehicles = [vehicle("v1")];
+        let orders = [
+            order("o1", at(8, 0), at(9, 0)),
+            order("o2", at(9, 0), at(10, 0)),
+        ];
+        let mut state = SolverState::new(&drivers, &vehicles);
+        state.assign_order(&orders[0], &drivers[0], &vehicles[0], (at(8, 0), at(9, 0)));
+
+        let with_break = SolverConfig::default();
+        assert_eq!(
+            slot_in(&state, &orders[1], &drivers[0], &vehicles[0], &with_break),
+            None
+        );
+        let no_break = SolverConfig {
+            mandatory_break_minutes: 0,
+            ..SolverConfig::default()
+        };
+        assert_eq!(
+            slot_in(&state, &orders[1], &drivers[0], &vehicles[0], &no_break),
+            Some((at(9, 0), at(10, 0)))
+        );
+
+        let response = solve(&drivers, &vehicles, &orders, &quick(no_break));
+        assert_eq!(assigned_ids(&response), vec!["o1", "o2"]);
+    }
+
+    #[test]
+    fn drivers_need_every_tag_of_the_order_as_a_skill() {
+        let tagged = Order {
+            tags: Some(vec!["cold".to_string(), "hazmat".to_string()]),
+            ..order("o1", at(8, 0), at(9, 0))
+        };
+        let skilled = |skills: &[&str]| Driver {
+            skills: Some(skills.iter().map(|s| s.to_string()).collect()),
+            ..driver("d1")
+        };
+        let slot = (at(8, 0), at(9, 0));
+        let spacing = Spacing::from_config(&SolverConfig::default());
+
+        let missing_one = skilled(&["cold"]);
+        assert_eq!(
+            driver_rejection(&tagged, slot, &missing_one, &[], spacing),
+            Some(Rejection::MissingSkill)
+        );
+        let superset = skilled(&["cold", "hazmat", "oversized"]);
+        assert_eq!(
+            driver_rejection(&tagged, slot, &superset, &[], spacing),
+            None
+        );
+    }
+
+    #[test]
+    fn a_driver_never_exceeds_max_orders() {
+        let drivers = [Driver {
+            max_orders: Some(2),
+            ..driver("d1")
+        }];
+        let vehicles = [vehicle("v1")];
+        let orders = [
+            order("o1", at(8, 0), at(9, 0)),
+            order("o2", at(10, 0), at(11, 0)),
+            order("o3", at(12, 0), at(13, 0)),
+        ];
+        let response = solve(
+            &drivers,
+            &vehicles,
+            &orders,
+            &quick(SolverConfig::default()),
+        );
+        assert_eq!(response.assignments.len(), 2);
+        assert_eq!(response.unassigned_order_ids.len(), 1);
+    }
+
+    #[test]
+    fn a_driver_only_takes_orders_on_allowed_vehicles() {
+        let tagged = |tag: &str| Some(vec![tag.to_string()]);
+        let drivers = [Driver {
+            allowed_vehicle_ids: Some(vec!["v1".to_string()]),
+            ..driver("d1")
+        }];
+        let vehicles = [
+            Vehicle {
+                tags: tagged("a"),
+                ..vehicle("v1")
+            },
+            Vehicle {
+                tags: tagged("b"),
+                ..vehicle("v2")
+            },
+        ];
+        // Each order fits the driver's schedule but only one of the vehicles
+        let orders = [
+            Order {
+                tags: tagged("a"),
+                ..order("o1", at(8, 0), at(9, 0))
+            },
+            Order {
+                tags: tagged("b"),
+                ..order("o2", at(10, 0), at(11, 0))
+            },
+        ];
+        assert!(can_assign(
+            &orders[1],
+            &drivers[0],
+            &vehicles[1],
+            &[],
+            &[],
+            Spacing::from_config(&SolverConfig::default())
+        )
+        .is_none());
+
+        let response = solve(
+            &drivers,
+            &vehicles,
+            &orders,
+            &quick(SolverConfig::default()),
+        );
+        assert_eq!(
+            response.assignments,
+            vec![assignment("o1", "d1", "v1", (at(8, 0), at(9, 0)))]
+        );
+        assert_eq!(response.unassigned_order_ids, vec!["o2"]);
+    }
+
+    #[test]
+    fn a_dependent_order_is_never_placed_before_its_predecessor() {
+        let drivers = [driver("d1"), driver("d2")];
+        let vehicles = [vehicle("v1"), vehicle("v2")];
+        let flexible = |id: &str| Order {
+            duration_minutes: Some(60),
+            latest_end: Some(at(12, 0)),
+            ..order(id, at(8, 0), at(9, 0))
+        };
+        let orders = [
+            Order {
+                must_follow: Some(vec!["o1".to_string()]),
+                ..flexible("o2")
+            },
+            flexible("o1"),
+        ];
+        let mut dependent_placed = false;
+        for seed in 0..10 {
+            let config = SolverConfig {
+                seed: Some(seed),
+                ..quick(SolverConfig::default())
+            };
+            let response = solve(&drivers, &vehicles, &orders, &config);
+            let find = |id| response.assignments.iter().find(|a| a.order_id == id);
+            if let Some(dependent) = find("o2") {
+                let predecessor = find("o1").expect("o2 assigned without o1");
+                assert_eq!(predecessor.driver_id, dependent.driver_id);
+                assert!(predecessor.end_time <= dependent.start_time);
+                dependent_placed = true;
+            }
+        }
+        assert!(dependent_placed);
+    }
+
+    #[test]
+    fn orders_must_fit_inside_one_shift() {
+        let shifted = Driver {
+            shifts: Some(vec![(at(8, 0), at(12, 0)), (at(13, 0), at(17, 0))]),
+            ..driver("d1")
+        };
+        let spacing = Spacing::from_config(&SolverConfig::default());
+        let rejection = |start, end| {
+            driver_rejection(
+                &order("o1", start, end),
+                (start, end),
+                &shifted,
+                &[],
+                spacing,
+            )
+        };
+        assert_eq!(
+            rejection(at(11, 30), at(12, 30)),
+            Some(Rejection::OutsideShift)
+        );
+        // Spanning the gap between two shifts is no better
+        assert_eq!(
+            rejection(at(11, 0), at(14, 0)),
+            Some(Rejection::OutsideShift)
+        );
+        assert_eq!(rejection(at(9, 0), at(10, 0)), None);
+        assert_eq!(rejection(at(13, 0), at(17, 0)), None);
+        // Without shifts the driver is always available
+        assert_eq!(
+            driver_rejection(
+                &order("o1", at(11, 30), at(12, 30)),
+                (at(11, 30), at(12, 30)),
+                &driver("d2"),
+                &[],
+                spacing
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn an_order_running_into_a_break_is_rejected() {
+        let on_break = Driver {
+            breaks: Some(vec![Break {
+                from: at(12, 0),
+                to: at(12, 30),
+            }]),
+            ..driver("d1")
+        };
+        let spacing = Spacing::from_config(&SolverConfig::default());
+        // Starts before the break and overlaps into it
+        let straddling = order("o1", at(11, 45), at(12, 15));
+        assert_eq!(
+            driver_rejection(
+                &straddling,
+                (at(11, 45), at(12, 15)),
+                &on_break,
+                &[],
+                spacing
+            ),
+            Some(Rejection::DriverBreak)
+        );
+        assert!(can_assign(&straddling, &on_break, &vehicle("v1"), &[], &[], spacing).is_none());
+        // Ending as the break begins is fine
+        let before = order("o2", at(11, 0), at(12, 0));
+        assert_eq!(
+            driver_rejection(&before, (at(11, 0), at(12, 0)), &on_break, &[], spacing),
+            None
+        );
+    }
+
+    #[test]
+    fn a_vehicle_in_maintenance_takes_no_overlapping_order() {
+        let in_maintenance = Vehicle {
+            unavailable: Some(vec![Break {
+                from: at(9, 0),
+                to: at(11, 0),
+            }]),
+            ..vehicle("v1")
+        };
+        let spacing = Spacing::from_config(&SolverConfig::default());
+        let during = order("o1", at(10, 0), at(10, 30));
+        let after = order("o2", at(11, 30), at(12, 0));
+        assert_eq!(
+            vehicle_rejection(
+                &during,
+                (at(10, 0), at(10, 30)),
+                &in_maintenance,
+                &[],
+                spacing
+            ),
+            Some(Rejection::VehicleUnavailable)
+        );
+        assert_eq!(
+            can_assign(&during, &driver("d1"), &in_maintenance, &[], &[], spacing),
+            None
+        );
+        assert_eq!(
+            can_assign(&after, &driver("d1"), &in_maintenance, &[], &[], spacing),
+            Some((at(11, 30), at(12, 0)))
+        );
+    }
+
+    #[test]
+    fn an_oversized_splittable_order_is_spread_over_two_vehicles() {
+        let drivers = [driver("d1"), driver("d2")];
+        let vehicles = [
+            Vehicle {
+                max_weight: 50.0,
+                ..vehicle("v1")
+            },
+            Vehicle {
+                max_weight: 50.0,
+                ..vehicle("v2")
+            },
+        ];
+        let orders = [Order {
+            weight: 100.0,
+            splittable: true,
+            ..order("o1", at(8, 0), at(9, 0))
+        }];
+        let response = solve(
+            &drivers,
+            &vehicles,
+            &orders,
+            &quick(SolverConfig::default()),
+        );
+        assert_eq!(response.assignments.len(), 2);
+        let mut used: Vec<&str> = response
+            .assignments
+            .iter()
+            .map(|a| a.vehicle_id.as_str())
+            .collect();
+        used.sort();
+        assert_eq!(used, vec!["v1", "v2"]);
+        let weights: Vec<f64> = response
+            .assignments
+            .iter()
+            .map(|a| a.weight.unwrap())
+            .collect();
+        assert!(weights.iter().all(|&w| w <= 50.0));
+        assert!((weights.iter().sum::<f64>() - 100.0).abs() < 1e-9);
+        // Fully covered, so the whole priority is credited once
+        assert!((response.score - 1.0).abs() < 1e-9);
+        assert!(response.unassigned_order_ids.is_empty());
+    }
+
+    #[test]
+    fn overtime_ranks_an_otherwise_equal_schedule_lower() {
+        let drivers = [
+            Driver {
+                max_work_minutes: Some(60),
+                ..driver("d1")
+            },
+            driver("d2"),
+        ];
+        let vehicles = [vehicle("v1")];
+        let orders = [
+            order("o1", at(8, 0), at(9, 0)),
+            order("o2", at(10, 0), at(11, 0)),
+        ];
+        let index = ProblemIndex::new(&drivers, &vehicles, &orders);
+        let config = SolverConfig {
+            overtime_penalty: 0.01,
+            ..SolverConfig::default()
+        };
+        let priority_map = build_priority_map(&orders, &config);
+        let on = |d: usize| {
+            let mut state = SolverState::new(&drivers, &vehicles);
+            for order in &orders {
+                state.assign_order(
+                    order,
+                    &drivers[d],
+                    &vehicles[0],
+                    (order.start_time, order.end_time),
+                );
+            }
+            state.score =
+                state.calculate_score(&priority_map, &drivers, &vehicles, &orders, &index, &config);
+            state
+        };
+        let within_budget = on(1);
+        let overtime = on(0);
+        assert!((within_budget.score - 2.0).abs() < 1e-9);
+        assert!((overtime.score - (2.0 - 60.0 * 0.01)).abs() < 1e-9);
+        assert_eq!(SolverState::rank(&within_budget, &overtime), Ordering::Less);
+    }
+
+    #[test]
+    fn a_vehicle_without_max_volume_is_unknown_or_unlimited() {
+        let bulky = Order {
+            volume: Some(5.0),
+            ..order("o1", at(8, 0), at(9, 0))
+        };
+        let slot = (at(8, 0), at(9, 0));
+        let spacing = Spacing::from_config(&SolverConfig::default());
+        let flatbed = |volume_unlimited| Vehicle {
+            volume_unlimited,
+            ..vehicle("v1")
+        };
+        for unknown in [flatbed(None), flatbed(Some(false))] {
+            assert_eq!(
+                vehicle_rejection(&bulky, slot, &unknown, &[], spacing),
+                Some(Rejection::Volume)
+            );
+        }
+        assert_eq!(
+            vehicle_rejection(&bulky, slot, &flatbed(Some(true)), &[], spacing),
+            None
+        );
+        // Orders without a volume fit either way
+        let plain = order("o2", at(8, 0), at(9, 0));
+        assert_eq!(
+            vehicle_rejection(&plain, slot, &flatbed(None), &[], spacing),
+            None
+        );
+    }
+
+    #[test]
+    fn an_infeasible_required_order_is_reported() {
+        let drivers = [driver("d1")];
+        let vehicles = [vehicle("v1")];
+        let orders = [
+            order("o1", at(8, 0), at(9, 0)),
+            // Heavier than the only vehicle
+            Order {
+                weight: 500.0,
+                required: Some(true),
+                ..order("o2", at(10, 0), at(11, 0))
+            },
+            Order {
+                required: Some(true),
+                ..order("o3", at(12, 0), at(13, 0))
+            },
+        ];
+        let response = solve(
+            &drivers,
+            &vehicles,
+            &orders,
+            &quick(SolverConfig::default()),
+        );
+        assert_eq!(response.required_unassigned, vec!["o2"]);
+        assert_eq!(assigned_ids(&response), vec!["o1", "o3"]);
+    }
+
+    #[test]
+    fn assign_order_leaves_the_score_to_calculate_score() {
+        let drivers = [driver("d1")];
+        let vehicles = [vehicle("v1")];
+        let orders = [Order {
+            priority: Some(3.0),
+            ..order("o1", at(8, 0), at(9, 0))
+        }];
+        let index = ProblemIndex::new(&drivers, &vehicles, &orders);
+        let config = SolverConfig::default();
+        let priority_map = build_priority_map(&orders, &config);
+        let mut state = SolverState::new(&drivers, &vehicles);
+        state.assign_order(&orders[0], &drivers[0], &vehicles[0], (at(8, 0), at(9, 0)));
+        assert_eq!(state.score, 0.0);
+        state.score =
+            state.calculate_score(&priority_map, &drivers, &vehicles, &orders, &index, &config);
+        assert_eq!(state.score, 3.0);
+        state.unassign(0);
+        assert_eq!(state.score, 3.0);
+    }
+
+    #[test]
+    fn far_apart_orders_close_in_time_are_infeasible() {
+        let at_location = |id: &str, start, end, (lat, lon)| Order {
+            lat: Some(lat),
+            lon: Some(lon),
+            ..order(id, start, end)
+        };
+        let berlin = (52.52, 13.40);
+        let munich = (48.14, 11.58);
+        let first = at_location("o1", at(8, 0), at(9, 0), berlin);
+        let drivers = [driver("d1")];
+        let vehicles = [vehicle("v1")];
+        let config = SolverConfig::default();
+        let mut state = SolverState::new(&drivers, &vehicles);
+        state.assign_order(&first, &drivers[0], &vehicles[0], (at(8, 0), at(9, 0)));
+
+        // The break alone would be satisfied, the 500 km in between are not
+        let far = at_location("o2", at(9, 40), at(10, 40), munich);
+        assert_eq!(
+            slot_in(&state, &far, &drivers[0], &vehicles[0], &config),
+            None
+        );
+        let near = at_location("o3", at(9, 40), at(10, 40), (52.53, 13.41));
+        assert!(slot_in(&state, &near, &drivers[0], &vehicles[0], &config).is_some());
+        let without_coordinates = order("o4", at(9, 40), at(10, 40));
+        assert!(slot_in(
+            &state,
+            &without_coordinates,
+            &drivers[0],
+            &vehicles[0],
+            &config
+        )
+        .is_some());
+    }
+
+    // Records the best score of every progress update
+    struct BestScores(Vec<f64>);
+
+    impl SolveObserver for BestScores {
+        fn on_progress(&mut self, progress: &ProgressUpdate) {
+            self.0.push(progress.best_score);
+        }
+    }
+
+    #[test]
+    fn best_score_never_decreases_across_generations() {
+        let (drivers, vehicles, orders) = random_instance(40, 5, 4, 11);
+        for mutation_rate in [0.1, 0.9] {
+            let config = quick(SolverConfig {
+                mutation_rate,
+                max_mutation_rate: 1.0,
+                generations: 60,
+                ..SolverConfig::default()
+            });
+            let mut observer = BestScores(Vec::new());
+            let response =
+                solve_with_observer(&drivers, &vehicles, &orders, &config, &mut observer);
+            assert!(observer.0.len() > 1);
+            for pair in observer.0.windows(2) {
+                assert!(pair[1] >= pair[0], "{:?}", observer.0);
+            }
+            assert!(response.score >= *observer.0.last().unwrap());
+        }
+    }
+
+    #[test]
+    fn a_group_goes_to_one_driver_or_stays_unassigned() {
+        let drivers = [driver("d1"), driver("d2")];
+        let vehicles = [vehicle("v1"), vehicle("v2")];
+        let grouped = |id: &str, start, end| Order {
+            group_id: Some("route".to_string()),
+            ..order(id, start, end)
+        };
+        let orders = [
+            grouped("o1", at(8, 0), at(9, 0)),
+            grouped("o2", at(9, 30), at(10, 30)),
+            grouped("o3", at(11, 0), at(12, 0)),
+            // Competes with the group for both drivers
+            order("o4", at(9, 45), at(11, 15)),
+            order("o5", at(8, 15), at(9, 15)),
+        ];
+        let on_one_driver_or_none = |assignments: &[Assignment]| {
+            let members: Vec<&Assignment> = assignments
+                .iter()
+                .filter(|a| ["o1", "o2", "o3"].contains(&a.order_id.as_str()))
+                .collect();
+            members.is_empty()
+                || (members.len() == 3
+                    && members.iter().all(|a| a.driver_id == members[0].driver_id))
+        };
+
+        // A group split over two drivers is cut back to nothing
+        let mut split = SolverState::new(&drivers, &vehicles);
+        split.assign_order(&orders[0], &drivers[0], &vehicles[0], (at(8, 0), at(9, 0)));
+        split.assign_order(
+            &orders[1],
+            &drivers[0],
+            &vehicles[0],
+            (at(9, 30), at(10, 30)),
+        );
+        split.assign_order(
+            &orders[2],
+            &drivers[1],
+            &vehicles[1],
+            (at(11, 0), at(12, 0)),
+        );
+        repair_constraints(&mut split, &orders);
+        assert!(split.assignments.is_empty());
+
+        for seed in 0..10 {
+            let config = SolverConfig {
+                seed: Some(seed),
+                ..quick(SolverConfig::default())
+            };
+            let response = solve(&drivers, &vehicles, &orders, &config);
+            assert!(
+                on_one_driver_or_none(&response.assignments),
+                "{:?}",
+                response.assignments
+            );
+        }
+    }
+
+    #[test]
+    fn fractional_priorities_are_told_apart() {
+        let drivers = [driver("d1")];
+        let vehicles = [vehicle("v1")];
+        // Only one of the two overlapping orders fits
+        let orders = [
+            Order {
+                priority: Some(1.5),
+                ..order("o1", at(8, 0), at(9, 0))
+            },
+            Order {
+                priority: Some(1.6),
+                ..order("o2", at(8, 30), at(9, 30))
+            },
+        ];
+        let priority_map = build_priority_map(&orders, &SolverConfig::default());
+        assert_eq!(priority_map["o1"], 1.5);
+        assert_eq!(priority_map["o2"], 1.6);
+        let response = solve(
+            &drivers,
+            &vehicles,
+            &orders,
+            &quick(SolverConfig::default()),
+        );
+        assert_eq!(assigned_ids(&response), vec!["o2"]);
+        assert!((response.score - 1.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn seeded_output_is_unchanged_by_the_id_lookups() {
+        let (drivers, vehicles, orders) = random_instance(40, 6, 4, 5);
+        let config = quick(SolverConfig {
+            group_bonus: 0.5,
+            min_fill_penalty: 0.5,
+            explain: true,
+            ..SolverConfig::default()
+        });
+        let first = solve(&drivers, &vehicles, &orders, &config);
+        let second = solve(&drivers, &vehicles, &orders, &config);
+        assert_eq!(first.assignments, second.assignments);
+        // Recorded while scoring still scanned the order and driver lists, and again once each
+        // child got its own generator, which changed the search but not the scoring
+        assert_eq!(first.assignments.len(), 24);
+        assert!((first.score - 75.1).abs() < 1e-6, "{}", first.score);
+    }
+
+    #[test]
+    fn break_slack_places_an_order_with_a_short_break() {
+        let drivers = [driver("d1")];
+        let vehicles = [vehicle("v1")];
+        // 20 minutes apart, 10 short of the mandatory break
+        let orders = [
+            order("o1", at(8, 0), at(9, 0)),
+            order("o2", at(9, 20), at(10, 0)),
+        ];
+        let strict = solve(
+            &drivers,
+            &vehicles,
+            &orders,
+            &quick(SolverConfig::default()),
+        );
+        assert_eq!(strict.assignments.len(), 1);
+
+        let config = quick(SolverConfig {
+            relaxation: Relaxation {
+                break_slack_minutes: 10,
+                allow_capacity_overflow_pct: 0.0,
+            },
+            ..SolverConfig::default()
+        });
+        let relaxed = solve(&drivers, &vehicles, &orders, &config);
+        assert_eq!(assigned_ids(&relaxed), vec!["o1", "o2"]);
+        assert_eq!(relaxed.relaxed_assignments.len(), 1);
+        // 5 minutes of slack are not enough
+        let config = quick(SolverConfig {
+            relaxation: Relaxation {
+                break_slack_minutes: 5,
+                allow_capacity_overflow_pct: 0.0,
+            },
+            ..SolverConfig::default()
+        });
+        assert_eq!(
+            solve(&drivers, &vehicles, &orders, &config)
+                .assignments
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn capacity_overflow_places_a_slightly_heavy_order() {
+        let drivers = [driver("d1")];
+        let vehicles = [vehicle("v1")];
+        let orders = [Order {
+            weight: 110.0,
+            ..order("o1", at(8, 0), at(9, 0))
+        }];
+        let overflow = |pct| {
+            quick(SolverConfig {
+                relaxation: Relaxation {
+                    break_slack_minutes: 0,
+                    allow_capacity_overflow_pct: pct,
+                },
+                ..SolverConfig::default()
+            })
+        };
+        let relaxed = solve(&drivers, &vehicles, &orders, &overflow(20.0));
+        assert_eq!(relaxed.relaxed_assignments, vec!["o1"]);
+        assert!(solve(&drivers, &vehicles, &orders, &overflow(5.0))
+            .assignments
+            .is_empty());
+
+        // Tags stay hard whatever the relaxation
+        let tagged = [Order {
+            tags: Some(vec!["cold".to_string()]),
+            ..orders[0].clone()
+        }];
+        assert!(solve(&drivers, &vehicles, &tagged, &overflow(20.0))
+            .assignments
+            .is_empty());
+    }
+
+    #[test]
+    fn a_daily_break_applies_on_every_date() {
+        let lunch = Driver {
+            recurring_breaks: Some(vec![DailyBreak {
+                start_minute_of_day: 12 * 60,
+                duration_minutes: 30,
+            }]),
+            breaks: Some(vec![Break {
+                from: on(2, 9, 0),
+                to: on(2, 10, 0),
+            }]),
+            ..driver("d1")
+        };
+        let on_break = |start, end| is_driver_on_break(&lunch, (start, end));
+        assert!(on_break(on(1, 11, 45), on(1, 12, 15)));
+        assert!(on_break(on(3, 12, 10), on(3, 12, 40)));
+        assert!(!on_break(on(1, 12, 30), on(1, 13, 0)));
+        assert!(!on_break(on(3, 11, 0), on(3, 12, 0)));
+        // The explicit break still counts next to the daily one
+        assert!(on_break(on(2, 9, 30), on(2, 10, 30)));
+        assert!(!on_break(on(3, 9, 30), on(3, 10, 30)));
+    }
+
+    #[test]
+    fn a_chain_past_max_continuous_minutes_needs_a_long_break() {
+        let drivers = [driver("d1")];
+        let vehicles = [vehicle("v1")];
+        let config = SolverConfig {
+            mandatory_break_minutes: 0,
+            max_continuous_minutes: 240,
+            long_break_minutes: 45,
+            ..SolverConfig::default()
+        };
+        let mut state = SolverState::new(&drivers, &vehicles);
+        state.assign_order(
+            &order("o1", at(8, 0), at(10, 0)),
+            &drivers[0],
+            &vehicles[0],
+            (at(8, 0), at(10, 0)),
+        );
+        state.assign_order(
+            &order("o2", at(10, 0), at(12, 0)),
+            &drivers[0],
+            &vehicles[0],
+            (at(10, 0), at(12, 0)),
+        );
+
+        // A 10 minute gap does not end the four hour stretch
+        let too_soon = order("o3", at(12, 10), at(13, 0));
+        assert_eq!(
+            driver_rejection(
+                &too_soon,
+                (at(12, 10), at(13, 0)),
+                &drivers[0],
+                &state.driver_schedules["d1"],
+                Spacing::from_config(&config)
+            ),
+            Some(Rejection::ContinuousWork)
+        );
+        let after_long_break = order("o4", at(12, 45), at(13, 30));
+        assert!(slot_in(
+            &state,
+            &after_long_break,
+            &drivers[0],
+            &vehicles[0],
+            &config
+        )
+        .is_some());
+        // Without the rule the chain may go on
+        let unlimited = SolverConfig {
+            max_continuous_minutes: 0,
+            ..config
+        };
+        assert!(slot_in(&state, &too_soon, &drivers[0], &vehicles[0], &unlimited).is_some());
+    }
+
+    #[test]
+    fn rank_priorities_let_several_small_orders_beat_one_huge_one() {
+        let drivers = [driver("d1")];
+        let vehicles = [vehicle("v1")];
+        // The huge order blocks the three small ones, which all fit together
+        let orders = [
+            Order {
+                priority: Some(1000.0),
+                ..order("big", at(8, 0), at(12, 0))
+            },
+            order("s1", at(8, 0), at(9, 0)),
+            order("s2", at(9, 30), at(10, 30)),
+            order("s3", at(11, 0), at(12, 0)),
+        ];
+        let raw = SolverConfig::default();
+        let rank = SolverConfig {
+            priority_transform: PriorityTransform::Rank,
+            ..SolverConfig::default()
+        };
+        let spread = |config: &SolverConfig| {
+            let map = build_priority_map(&orders, config);
+            map["big"] - map["s1"]
+        };
+        assert_eq!(spread(&raw), 999.0);
+        assert_eq!(spread(&rank), 1.0);
+
+        assert_eq!(
+            assigned_ids(&solve(&drivers, &vehicles, &orders, &quick(raw))),
+            vec!["big"]
+        );
+        assert_eq!(
+            assigned_ids(&solve(&drivers, &vehicles, &orders, &quick(rank))),
+            vec!["s1", "s2", "s3"]
+        );
+    }
+
+    #[test]
+    fn service_time_pushes_the_next_order_past_the_break() {
+        let drivers = [driver("d1")];
+        let vehicles = [vehicle("v1")];
+        let config = SolverConfig::default();
+        let next = order("o2", at(9, 30), at(10, 0));
+        let with_service = |service_minutes| {
+            let first = Order {
+                service_minutes,
+                ..order("o1", at(8, 0), at(9, 0))
+            };
+            let mut state = SolverState::new(&drivers, &vehicles);
+            state.assign_order(&first, &drivers[0], &vehicles[0], (at(8, 0), at(9, 0)));
+            state
+        };
+        // 09:00 plus the 30 minute break is just in time
+        let plain = with_service(None);
+        assert!(slot_in(&plain, &next, &drivers[0], &vehicles[0], &config).is_some());
+        // Unloading until 09:15 moves the earliest next start to 09:45
+        let serviced = with_service(Some(15));
+        assert_eq!(
+            slot_in(&serviced, &next, &drivers[0], &vehicles[0], &config),
+            None
+        );
+        // The order itself still reports its own window
+        assert_eq!(serviced.assignments[0].end_time, at(9, 0));
+    }
+
+    #[test]
+    fn min_fill_prefers_a_consolidated_schedule() {
+        let drivers = [driver("d1")];
+        let half_full = |id: &str| Vehicle {
+            min_fill_pct: Some(0.5),
+            ..vehicle(id)
+        };
+        let vehicles = [half_full("v1"), half_full("v2")];
+        let load = |id: &str, start, end| Order {
+            weight: 30.0,
+            ..order(id, start, end)
+        };
+        let orders = [
+            load("o1", at(8, 0), at(9, 0)),
+            load("o2", at(10, 0), at(11, 0)),
+        ];
+        let index = ProblemIndex::new(&drivers, &vehicles, &orders);
+        let config = SolverConfig {
+            min_fill_penalty: 1.0,
+            ..SolverConfig::default()
+        };
+        let priority_map = build_priority_map(&orders, &config);
+        let score = |second_vehicle: usize| {
+            let mut state = SolverState::new(&drivers, &vehicles);
+            state.assign_order(&orders[0], &drivers[0], &vehicles[0], (at(8, 0), at(9, 0)));
+            state.assign_order(
+                &orders[1],
+                &drivers[0],
+                &vehicles[second_vehicle],
+                (at(10, 0), at(11, 0)),
+            );
+            state.calculate_score(&priority_map, &drivers, &vehicles, &orders, &index, &config)
+        };
+        // 60 of 100 on one vehicle, the unused one does not count
+        let consolidated = score(0);
+        // 30 of 100 on both, each 0.2 short of half full
+        let scattered = score(1);
+        assert!((consolidated - 2.0).abs() < 1e-9);
+        assert!((scattered - 1.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn the_earlier_of_two_placements_earns_more_earliness_bonus() {
+        let drivers = [driver("d1")];
+        let vehicles = [vehicle("v1")];
+        // 60 minutes anywhere in 08:00-12:00, latest start 11:00
+        let orders = [Order {
+            duration_minutes: Some(60),
+            latest_end: Some(at(12, 0)),
+            ..order("o1", at(8, 0), at(9, 0))
+        }];
+        let index = ProblemIndex::new(&drivers, &vehicles, &orders);
+        let config = SolverConfig {
+            earliness_bonus: 1.0,
+            ..SolverConfig::default()
+        };
+        let priority_map = build_priority_map(&orders, &config);
+        let score = |start: NaiveDateTime| {
+            let mut state = SolverState::new(&drivers, &vehicles);
+            state.assign_order(
+                &orders[0],
+                &drivers[0],
+                &vehicles[0],
+                (start, start + ChronoDuration::hours(1)),
+            );
+            state.calculate_score(&priority_map, &drivers, &vehicles, &orders, &index, &config)
+        };
+        let early = score(at(8, 0));
+        let later = score(at(10, 0));
+        assert!((early - 2.0).abs() < 1e-9);
+        assert!((later - (1.0 + 1.0 / 3.0)).abs() < 1e-9);
+        assert!(early > later);
+        // Without the bonus both placements are worth the same
+        let no_bonus = SolverConfig::default();
+        let mut state = SolverState::new(&drivers, &vehicles);
+        state.assign_order(
+            &orders[0],
+            &drivers[0],
+            &vehicles[0],
+            (at(10, 0), at(11, 0)),
+        );
+        assert_eq!(
+            state.calculate_score(
+                &priority_map,
+                &drivers,
+                &vehicles,
+                &orders,
+                &index,
+                &no_bonus
+            ),
+            1.0
+        );
+    }
+
+    #[test]
+    fn max_vehicles_used_caps_the_fleet_of_the_plan() {
+        let drivers: Vec<Driver> = (1..=4).map(|i| driver(&format!("d{}", i))).collect();
+        let vehicles: Vec<Vehicle> = (1..=5).map(|i| vehicle(&format!("v{}", i))).collect();
+        // Two pairs of simultaneous orders, two vehicles are enough for all four
+        let orders = [
+            order("o1", at(8, 0), at(9, 0)),
+            order("o2", at(8, 0), at(9, 0)),
+            order("o3", at(10, 0), at(11, 0)),
+            order("o4", at(10, 0), at(11, 0)),
+        ];
+        let config = quick(SolverConfig {
+            max_vehicles_used: Some(2),
+            ..SolverConfig::default()
+        });
+        let response = solve(&drivers, &vehicles, &orders, &config);
+        assert_eq!(assigned_ids(&response), ["o1", "o2", "o3", "o4"]);
+        let used: HashSet<&str> = response
+            .assignments
+            .iter()
+            .map(|a| a.vehicle_id.as_str())
+            .collect();
+        assert_eq!(used.len(), 2);
+
+        // Once two vehicles carry an order only those two can take a third one
+        let mut state = SolverState::new(&drivers, &vehicles);
+        state.assign_order(&orders[0], &drivers[0], &vehicles[0], (at(8, 0), at(9, 0)));
+        state.assign_order(&orders[1], &drivers[1], &vehicles[1], (at(8, 0), at(9, 0)));
+        let spacing = Spacing::from_config(&config);
+        assert!(state.fleet_allows(&vehicles[0], spacing));
+        assert!(state.fleet_allows(&vehicles[1], spacing));
+        assert!(vehicles[2..]
+            .iter()
+            .all(|v| !state.fleet_allows(v, spacing)));
+        assert!(state.fleet_allows(&vehicles[2], Spacing::from_config(&SolverConfig::default())));
+    }
+
+    #[test]
+    fn roomier_gaps_score_higher_under_gap_bonus() {
+        let drivers = [driver("d1")];
+        let vehicles = [vehicle("v1")];
+        let config = SolverConfig {
+            preferred_gap_minutes: 60,
+            gap_bonus: 0.5,
+            ..SolverConfig::default()
+        };
+        let score = |second_start: NaiveDateTime, config: &SolverConfig| {
+            let orders = [
+                order("o1", at(8, 0), at(9, 0)),
+                order("o2", second_start, second_start + ChronoDuration::hours(1)),
+            ];
+            let index = ProblemIndex::new(&drivers, &vehicles, &orders);
+            let priority_map = build_priority_map(&orders, config);
+            let mut state = SolverState::new(&drivers, &vehicles);
+            for o in &orders {
+                state.assign_order(o, &drivers[0], &vehicles[0], (o.start_time, o.end_time));
+            }
+            state.calculate_score(&priority_map, &drivers, &vehicles, &orders, &index, config)
+        };
+        // A 30 minute gap meets the mandatory break only, a 60 minute gap earns the bonus
+        let tight = score(at(9, 30), &config);
+        let roomy = score(at(10, 0), &config);
+        assert_eq!(tight, 2.0);
+        assert_eq!(roomy, 2.5);
+
+        let capped = SolverConfig {
+            gap_bonus_cap: Some(0.2),
+            ..config
+        };
+        assert_eq!(score(at(10, 0), &capped), 2.2);
+    }
+
+    #[test]
+    fn a_driver_change_on_a_vehicle_needs_the_handoff_gap() {
+        let drivers = [driver("d1"), driver("d2")];
+        let vehicles = [vehicle("v1")];
+        let first = order("o1", at(8, 0), at(9, 0));
+        let second = order("o2", at(9, 30), at(10, 30));
+        let config = SolverConfig {
+            vehicle_handoff_minutes: 45,
+            ..SolverConfig::default()
+        };
+        let mut state = SolverState::new(&drivers, &vehicles);
+        state.assign_order(&first, &drivers[0], &vehicles[0], (at(8, 0), at(9, 0)));
+
+        // 30 minutes are enough for the same driver but too short to hand the vehicle over
+        assert!(slot_in(&state, &second, &drivers[0], &vehicles[0], &config).is_some());
+        assert_eq!(
+            slot_in(&state, &second, &drivers[1], &vehicles[0], &config),
+            None
+        );
+        assert!(slot_in(
+            &state,
+            &second,
+            &drivers[1],
+            &vehicles[0],
+            &SolverConfig::default()
+        )
+        .is_some());
+        // The handoff also holds when the other driver's order comes first
+        let earlier = order("o0", at(6, 30), at(7, 30));
+        assert_eq!(
+            slot_in(&state, &earlier, &drivers[1], &vehicles[0], &config),
+            None
+        );
+    }
+
+    #[test]
+    fn hard_preference_holds_a_driver_to_the_open_preferred_vehicle() {
+        let drivers = [
+            Driver {
+                preference: Some("v1".to_string()),
+                ..driver("d1")
+            },
+            driver("d2"),
+        ];
+        let vehicles = [vehicle("v1"), vehicle("v2")];
+        let orders = [
+            order("o1", at(8, 0), at(9, 0)),
+            order("o2", at(8, 0), at(9, 0)),
+        ];
+        let index = ProblemIndex::new(&drivers, &vehicles, &orders);
+        let soft = SolverConfig::default();
+        let hard = SolverConfig {
+            preference_mode: PreferenceMode::HardWhenAvailable,
+            ..SolverConfig::default()
+        };
+        let pairs = |state: &SolverState, config: &SolverConfig| -> Vec<(String, String)> {
+            feasible_placements(
+                state,
+                &orders[0],
+                &drivers,
+                &vehicles,
+                &index,
+                Spacing::from_config(config),
+            )
+            .into_iter()
+            .filter(|(d, _, _)| d.id == "d1")
+            .map(|(d, v, _)| (d.id.clone(), v.id.clone()))
+            .collect()
+        };
+        let both = vec![
+            ("d1".to_string(), "v1".to_string()),
+            ("d1".to_string(), "v2".to_string()),
+        ];
+
+        // Soft only rewards the preferred vehicle, hard rules out the other one while v1 is free
+        let mut state = SolverState::new(&drivers, &vehicles);
+        assert_eq!(pairs(&state, &soft), both);
+        assert_eq!(pairs(&state, &hard), [("d1".to_string(), "v1".to_string())]);
+        let score = |vehicle: &Vehicle| {
+            let mut state = SolverState::new(&drivers, &vehicles);
+            state.assign_order(&orders[0], &drivers[0], vehicle, (at(8, 0), at(9, 0)));
+            let priority_map = build_priority_map(&orders, &soft);
+            state.calculate_score(&priority_map, &drivers, &vehicles, &orders, &index, &soft)
+        };
+        assert!(score(&vehicles[0]) > score(&vehicles[1]));
+
+        // Once v1 is taken at that time d1 may fall back to v2
+        state.assign_order(&orders[1], &drivers[1], &vehicles[0], (at(8, 0), at(9, 0)));
+        assert_eq!(pairs(&state, &hard), [("d1".to_string(), "v2".to_string())]);
+    }
+
+    #[test]
+    fn the_repaired_warm_start_is_part_of_generation_zero() {
+        let drivers = [driver("d1"), driver("d2")];
+        let vehicles = [vehicle("v1"), vehicle("v2")];
+        // Random placement books o1 at its earliest start, only the warm start has it at 10:00
+        let orders = [
+            Order {
+                duration_minutes: Some(60),
+                latest_end: Some(at(12, 0)),
+                ..order("o1", at(8, 0), at(9, 0))
+            },
+            order("o2", at(10, 30), at(11, 30)),
+        ];
+        // o2 overlaps o1 on the same driver and vehicle, o3 no longer exists
+        let previous = response(vec![
+            assignment("o1", "d1", "v1", (at(10, 0), at(11, 0))),
+            assignment("o2", "d1", "v1", (at(10, 30), at(11, 30))),
+            assignment("o3", "d2", "v2", (at(8, 0), at(9, 0))),
+        ]);
+        let config = SolverConfig {
+            generations: 0,
+            return_population: true,
+            warm_start: Some(previous),
+            ..quick(SolverConfig::default())
+        };
+        let response = solve(&drivers, &vehicles, &orders, &config);
+        let population = response.population.unwrap();
+        assert_eq!(population.len(), config.population_size);
+        let warm: Vec<&SchedulingResponse> = population
+            .iter()
+            .filter(|individual| {
+                individual
+                    .assignments
+                    .iter()
+                    .any(|a| a.order_id == "o1" && a.start_time == at(10, 0))
+            })
+            .collect();
+        assert_eq!(warm.len(), 1);
+
+        // o1 keeps its slot, o2 moves to the other driver and vehicle, o3 is dropped
+        let mut placed: Vec<(&str, &str, &str, NaiveDateTime)> = warm[0]
+            .assignments
+            .iter()
+            .map(|a| {
+                (
+                    a.order_id.as_str(),
+                    a.driver_id.as_str(),
+                    a.vehicle_id.as_str(),
+                    a.start_time,
+                )
+            })
+            .collect();
+        placed.sort();
+        assert_eq!(
+            placed,
+            [
+                ("o1", "d1", "v1", at(10, 0)),
+                ("o2", "d2", "v2", at(10, 30))
+            ]
+        );
+    }
+
+    #[test]
+    fn allowed_driver_ids_pins_an_order_to_one_of_several_feasible_drivers() {
+        let drivers = [driver("d1"), driver("d2"), driver("d3")];
+        let vehicles = [Vehicle {
+            tags: Some(vec!["cold".to_string()]),
+            ..vehicle("v1")
+        }];
+        let open = Order {
+            tags: Some(vec!["cold".to_string()]),
+            ..order("o1", at(8, 0), at(9, 0))
+        };
+        let listed = Order {
+            allowed_driver_ids: Some(vec!["d3".to_string()]),
+            ..open.clone()
+        };
+        let spacing = Spacing::from_config(&SolverConfig::default());
+        let slot = (at(8, 0), at(9, 0));
+
+        // Every driver can take the order by its tags, the list narrows that to d3
+        let orders = [open.clone()];
+        let index = ProblemIndex::new(&drivers, &vehicles, &orders);
+        let state = SolverState::new(&drivers, &vehicles);
+        let placements = feasible_placements(&state, &open, &drivers, &vehicles, &index, spacing);
+        assert_eq!(placements.len(), 3);
+        for d in &drivers[..2] {
+            assert_eq!(
+                driver_rejection(&listed, slot, d, &[], spacing),
+                Some(Rejection::DriverNotListed)
+            );
+        }
+        assert_eq!(
+            driver_rejection(&listed, slot, &drivers[2], &[], spacing),
+            None
+        );
+
+        let orders = [listed];
+        let response = solve(
+            &drivers,
+            &vehicles,
+            &orders,
+            &quick(SolverConfig::default()),
+        );
+        assert_eq!(response.assignments.len(), 1);
+        assert_eq!(response.assignments[0].driver_id, "d3");
+    }
+
+    #[test]
+    fn tournament_of_one_selects_uniformly_at_random() {
+        let population: Vec<SolverState> = [4.0, 3.0, 2.0, 1.0]
+            .into_iter()
+            .map(|score| SolverState {
+                score,
+                ..SolverState::new(&[], &[])
+            })
+            .collect();
+        let strategy = SelectionStrategy::Tournament { size: 1 };
+        // With one contender the score never matters, whatever the win probability
+        for win_prob in [1.0, 0.5] {
+            let mut rng = StdRng::seed_from_u64(7);
+            let mut counts = [0; 4];
+            for _ in 0..8000 {
+                let parent = select_parent(&population, &strategy, win_prob, &mut rng);
+                counts[population
+                    .iter()
+                    .position(|s| std::ptr::eq(s, parent))
+                    .unwrap()] += 1;
+            }
+            for count in counts {
+                assert!((1800..2200).contains(&count), "{:?}", counts);
+            }
+        }
+
+        // A larger tournament favours the best, as a contrast
+        let mut rng = StdRng::seed_from_u64(7);
+        let best = (0..8000)
+            .filter(|_| {
+                let strategy = SelectionStrategy::Tournament { size: 3 };
+                std::ptr::eq(
+                    select_parent(&population, &strategy, 1.0, &mut rng),
+                    &population[0],
+                )
+            })
+            .count();
+        assert!(best > 4000);
+    }
+
+    #[test]
+    fn a_filler_is_only_assigned_where_it_displaces_nothing() {
+        let drivers = [driver("d1")];
+        let vehicles = [vehicle("v1")];
+        let filler = |id: &str, start: NaiveDateTime| Order {
+            priority: Some(-1.0),
+            ..order(id, start, start + ChronoDuration::hours(1))
+        };
+        // f1 overlaps the regular order, f2 has the afternoon to itself
+        let orders = [
+            Order {
+                priority: Some(0.5),
+                ..order("o1", at(8, 0), at(9, 0))
+            },
+            filler("f1", at(8, 30)),
+            filler("f2", at(14, 0)),
+        ];
+        for seed in 0..5 {
+            let config = SolverConfig {
+                seed: Some(seed),
+                ..quick(SolverConfig::default())
+            };
+            let response = solve(&drivers, &vehicles, &orders, &config);
+            assert_eq!(assigned_ids(&response), ["f2", "o1"], "seed {}", seed);
+        }
+
+        // Fillers earn something but less than any regular order, even together
+        let index = ProblemIndex::new(&drivers, &vehicles, &orders);
+        let config = SolverConfig::default();
+        let priority_map = build_priority_map(&orders, &config);
+        let score = |ids: &[&str]| {
+            let mut state = SolverState::new(&drivers, &vehicles);
+            for o in orders.iter().filter(|o| ids.contains(&o.id.as_str())) {
+                state.assign_order(o, &drivers[0], &vehicles[0], (o.start_time, o.end_time));
+            }
+            state.calculate_score(&priority_map, &drivers, &vehicles, &orders, &index, &config)
+        };
+        assert!(score(&["f2"]) > 0.0);
+        assert!(score(&["o1"]) > score(&["f1", "f2"]));
+        assert!(score(&["o1", "f2"]) > score(&["o1"]));
+    }
+
+    #[test]
+    fn a_stall_restarts_the_non_elite_and_keeps_the_elite() {
+        let (drivers, vehicles, orders) = random_instance(12, 3, 2, 5);
+        let index = ProblemIndex::new(&drivers, &vehicles, &orders);
+        let config = SolverConfig {
+            restart_fraction: 0.5,
+            ..SolverConfig::default()
+        };
+        let priority_map = build_priority_map(&orders, &config);
+        // Empty plans stand in for the population, every fresh individual assigns something
+        let mut population: Vec<SolverState> = (0..6)
+            .map(|i| SolverState {
+                score: -(i as f64),
+                ..SolverState::new(&drivers, &vehicles)
+            })
+            .collect();
+        let mut rng = StdRng::seed_from_u64(1);
+        restart_population(
+            &mut population,
+            2,
+            &drivers,
+            &vehicles,
+            &orders,
+            &index,
+            &priority_map,
+            &config,
+            &mut rng,
+        );
+        let kept: Vec<f64> = population
+            .iter()
+            .filter(|s| s.assignments.is_empty())
+            .map(|s| s.score)
+            .collect();
+        // Half of the four non-elite, the worst two, are replaced
+        assert_eq!(kept, [0.0, -1.0, -2.0, -3.0]);
+        assert!(population[4..].iter().all(|s| !s.assignments.is_empty()));
+
+        // A solve that stalls quickly restarts instead of stopping, and the best never drops
+        let config = SolverConfig {
+            max_generations_without_improvement: 3,
+            max_restarts: 2,
+            restart_fraction: 0.5,
+            ..quick(SolverConfig::default())
+        };
+        let mut observer = BestScores(Vec::new());
+        let response = solve_with_observer(&drivers, &vehicles, &orders, &config, &mut observer);
+        assert_eq!(response.metrics.restarts, 2);
+        assert!(observer.0.windows(2).all(|pair| pair[1] >= pair[0]));
+        assert_eq!(response.score, *observer.0.last().unwrap());
+    }
+
+    #[test]
+    fn each_vehicle_enforces_its_own_break_between_the_same_orders() {
+        let drivers = [driver("d1"), driver("d2")];
+        let vehicles = [
+            Vehicle {
+                min_break_minutes: Some(60),
+                ..vehicle("truck")
+            },
+            vehicle("van"),
+        ];
+        let first = order("o1", at(8, 0), at(9, 0));
+        let second = order("o2", at(9, 30), at(10, 30));
+        let later = order("o3", at(10, 0), at(11, 0));
+        let config = SolverConfig::default();
+
+        // Different drivers, so only the vehicle's break separates the orders
+        for (vehicle, fits) in [(&vehicles[0], false), (&vehicles[1], true)] {
+            let mut state = SolverState::new(&drivers, &vehicles);
+            state.assign_order(&first, &drivers[0], vehicle, (at(8, 0), at(9, 0)));
+            assert_eq!(
+                slot_in(&state, &second, &drivers[1], vehicle, &config).is_some(),
+                fits,
+                "{}",
+                vehicle.id
+            );
+            assert!(slot_in(&state, &later, &drivers[1], vehicle, &config).is_some());
+        }
+
+        // The driver keeps the global break on the truck
+        let mut state = SolverState::new(&drivers, &vehicles);
+        state.assign_order(&first, &drivers[0], &vehicles[1], (at(8, 0), at(9, 0)));
+        assert!(slot_in(&state, &second, &drivers[0], &vehicles[0], &config).is_some());
+    }
+
+    #[test]
+    fn pinned_assignments_are_unchanged_after_a_hundred_generations() {
+        let (drivers, vehicles, orders) = random_instance(20, 4, 3, 9);
+        let unpinned = solve(
+            &drivers,
+            &vehicles,
+            &orders,
+            &quick(SolverConfig::default()),
+        );
+        // Pin two assignments of an earlier plan, with their weight cleared as a client sends them
+        let pinned: Vec<Assignment> = unpinned.assignments[..2]
+            .iter()
+            .map(|a| Assignment {
+                weight: None,
+                ..a.clone()
+            })
+            .collect();
+
+        struct Snapshots(Vec<SchedulingResponse>);
+        impl SolveObserver for Snapshots {
+            fn on_progress(&mut self, progress: &ProgressUpdate) {
+                self.0.extend(progress.snapshot.clone());
+            }
+        }
+        let config = SolverConfig {
+            generations: 100,
+            max_generations_without_improvement: 100,
+            snapshot_interval: 10,
+            return_population: true,
+            pinned: pinned.iter().map(PlannedAssignment::from).collect(),
+            ..quick(SolverConfig::default())
+        };
+        let mut snapshots = Snapshots(Vec::new());
+        let response = solve_with_observer(&drivers, &vehicles, &orders, &config, &mut snapshots);
+        assert_eq!(response.metrics.generations, 100);
+        assert_eq!(snapshots.0.len(), 10);
+
+        let plans = snapshots
+            .0
+            .iter()
+            .chain(response.population.iter().flatten())
+            .chain([&response]);
+        for plan in plans {
+            for pin in &pinned {
+                let kept: Vec<&Assignment> = plan
+                    .assignments
+                    .iter()
+                    .filter(|a| a.order_id == pin.order_id)
+                    .collect();
+                assert_eq!(kept.len(), 1, "{}", pin.order_id);
+                let a = kept[0];
+                assert!(a.pinned);
+                assert_eq!(
+                    (&a.driver_id, &a.vehicle_id, a.start_time, a.end_time),
+                    (
+                        &pin.driver_id,
+                        &pin.vehicle_id,
+                        pin.start_time,
+                        pin.end_time
+                    )
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn scores_do_not_depend_on_the_order_of_drivers_and_vehicles() {
+        let (drivers, vehicles, orders) = random_instance(25, 4, 3, 2);
+        let config = SolverConfig {
+            unassigned_penalty: 0.5,
+            ..SolverConfig::default()
+        };
+        let priority_map = build_priority_map(&orders, &config);
+        let index = ProblemIndex::new(&drivers, &vehicles, &orders);
+        let mut rng = StdRng::seed_from_u64(3);
+        let state = initialize_random_state(
+            &drivers,
+            &vehicles,
+            &orders,
+            &index,
+            &priority_map,
+            &config,
+            InitOrder::Shuffle,
+            &mut rng,
+        );
+        let score =
+            state.calculate_score(&priority_map, &drivers, &vehicles, &orders, &index, &config);
+        // The same plan scored 25.5 while the schedules were still keyed by HashMap
+        assert!((score - 25.5).abs() < 1e-9);
+        assert!(state.driver_schedules.keys().is_sorted());
+        assert!(state.vehicle_schedules.keys().is_sorted());
+
+        // The same plan replayed with the inputs listed in reverse
+        let reversed_drivers: Vec<Driver> = drivers.iter().rev().cloned().collect();
+        let reversed_vehicles: Vec<Vehicle> = vehicles.iter().rev().cloned().collect();
+        let reversed_index = ProblemIndex::new(&reversed_drivers, &reversed_vehicles, &orders);
+        let mut replayed = SolverState::new(&reversed_drivers, &reversed_vehicles);
+        for a in &state.assignments {
+            let order = index.orders[a.order_id.as_str()];
+            replayed.assign_order(
+                &assigned_piece(order, a),
+                reversed_index.drivers[a.driver_id.as_str()],
+                reversed_index.vehicles[a.vehicle_id.as_str()],
+                (a.start_time, a.end_time),
+            );
+        }
+        assert_eq!(
+            replayed.driver_schedules.keys().collect::<Vec<_>>(),
+            state.driver_schedules.keys().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            replayed.calculate_score(
+                &priority_map,
+                &reversed_drivers,
+                &reversed_vehicles,
+                &orders,
+                &reversed_index,
+                &config
+            ),
+            score
+        );
+    }
+
+    #[test]
+    fn a_vehicle_group_never_lands_on_two_vehicles() {
+        let leg = |id: &str, start: NaiveDateTime| Order {
+            same_vehicle_group: Some("cargo".to_string()),
+            ..order(id, start, start + ChronoDuration::hours(1))
+        };
+        let drivers = [driver("d1"), driver("d2")];
+        let vehicles = [vehicle("v1"), vehicle("v2")];
+        let orders = [leg("g1", at(8, 0)), leg("g2", at(10, 0))];
+
+        // Repair drops the leg that left the vehicle chosen for the group
+        let mut state = SolverState::new(&drivers, &vehicles);
+        state.assign_order(&orders[0], &drivers[0], &vehicles[0], (at(8, 0), at(9, 0)));
+        state.assign_order(
+            &orders[1],
+            &drivers[1],
+            &vehicles[1],
+            (at(10, 0), at(11, 0)),
+        );
+        repair_constraints(&mut state, &orders);
+        assert_eq!(state.assignments.len(), 1);
+
+        // Plenty of other orders compete for both vehicles, the legs still share one
+        let (drivers, vehicles, mut orders) = random_instance(16, 3, 3, 4);
+        orders.iter_mut().take(2).for_each(|o| {
+            o.same_vehicle_group = Some("cargo".to_string());
+            o.tags = None;
+        });
+        let mut both_assigned = 0;
+        for seed in 0..5 {
+            let config = SolverConfig {
+                seed: Some(seed),
+                ..quick(SolverConfig::default())
+            };
+            let response = solve(&drivers, &vehicles, &orders, &config);
+            let legs: Vec<&Assignment> = response
+                .assignments
+                .iter()
+                .filter(|a| a.order_id == "o0" || a.order_id == "o1")
+                .collect();
+            let used: HashSet<&str> = legs.iter().map(|a| a.vehicle_id.as_str()).collect();
+            assert!(used.len() <= 1, "seed {}: {:?}", seed, used);
+            both_assigned += usize::from(legs.len() == 2);
+        }
+        assert!(both_assigned > 0);
+    }
+
+    #[test]
+    fn an_imminent_order_outranks_a_later_one_under_urgency() {
+        // The driver takes one order only, so the two compete
+        let drivers = [Driver {
+            max_orders: Some(1),
+            ..driver("d1")
+        }];
+        let vehicles = [vehicle("v1")];
+        let orders = [
+            order("soon", at(7, 0), at(8, 0)),
+            Order {
+                priority: Some(2.0),
+                ..order("later", at(17, 0), at(18, 0))
+            },
+        ];
+        let calm = quick(SolverConfig {
+            reference_time: Some(at(7, 0)),
+            ..SolverConfig::default()
+        });
+        let urgent = SolverConfig {
+            priority_urgency_factor: 10.0,
+            ..calm.clone()
+        };
+        let map = build_priority_map(&orders, &calm);
+        assert_eq!((map["soon"], map["later"]), (1.0, 2.0));
+        let map = build_priority_map(&orders, &urgent);
+        assert!(map["soon"] > map["later"], "{:?}", map);
+
+        assert_eq!(
+            assigned_ids(&solve(&drivers, &vehicles, &orders, &calm)),
+            ["later"]
+        );
+        assert_eq!(
+            assigned_ids(&solve(&drivers, &vehicles, &orders, &urgent)),
+            ["soon"]
+        );
+    }
+
+    #[test]
+    fn slots_across_midnight_overlap_and_space_like_any_other() {
+        // 23:00-01:00 runs into the next day
+        let overnight = (on(1, 23, 0), on(2, 1, 0));
+        assert!(orders_overlap(overnight, (on(2, 0, 30), on(2, 2, 0))));
+        assert!(orders_overlap(overnight, (on(1, 22, 0), on(1, 23, 30))));
+        // Same times of day, a day apart
+        assert!(!orders_overlap(overnight, (on(2, 23, 0), on(3, 1, 0))));
+        assert!(!orders_overlap(overnight, (on(2, 1, 0), on(2, 2, 0))));
+
+        let break_30 = ChronoDuration::minutes(30);
+        assert!(insufficient_break(
+            overnight,
+            (on(2, 1, 15), on(2, 2, 0)),
+            break_30
+        ));
+        assert!(!insufficient_break(
+            overnight,
+            (on(2, 1, 30), on(2, 2, 0)),
+            break_30
+        ));
+        assert!(insufficient_break(
+            (on(1, 22, 0), on(1, 22, 45)),
+            overnight,
+            break_30
+        ));
+        assert!(!insufficient_break(
+            overnight,
+            (on(2, 23, 0), on(3, 1, 0)),
+            break_30
+        ));
+    }
+
+    #[test]
+    fn breaks_across_midnight_block_both_days() {
+        let fixed = Driver {
+            breaks: Some(vec![Break {
+                from: on(1, 23, 30),
+                to: on(2, 0, 30),
+            }]),
+            ..driver("d1")
+        };
+        // Every night 23:30-00:30
+        let nightly = Driver {
+            recurring_breaks: Some(vec![DailyBreak {
+                start_minute_of_day: 23 * 60 + 30,
+                duration_minutes: 60,
+            }]),
+            ..driver("d2")
+        };
+        for d in [&fixed, &nightly] {
+            assert!(
+                is_driver_on_break(d, (on(2, 0, 0), on(2, 1, 0))),
+                "{}",
+                d.id
+            );
+            assert!(
+                is_driver_on_break(d, (on(1, 23, 0), on(1, 23, 45))),
+                "{}",
+                d.id
+            );
+            assert!(
+                !is_driver_on_break(d, (on(2, 0, 30), on(2, 1, 30))),
+                "{}",
+                d.id
+            );
+            assert!(
+                !is_driver_on_break(d, (on(1, 22, 0), on(1, 23, 30))),
+                "{}",
+                d.id
+            );
+        }
+        // The break begun the evening before is found for a slot early on the 3rd
+        assert!(is_driver_on_break(&nightly, (on(3, 0, 0), on(3, 0, 15))));
+        assert!(!is_driver_on_break(&fixed, (on(3, 0, 0), on(3, 0, 15))));
+
+        // A slot within one day still sees that day's break and the one from the night before
+        let windows: Vec<_> =
+            recurring_break_windows(&nightly, (on(2, 6, 0), on(2, 7, 0))).collect();
+        assert_eq!(
+            windows,
+            [(on(1, 23, 30), on(2, 0, 30)), (on(2, 23, 30), on(3, 0, 30))]
+        );
+        // An overnight order touches the nights before, of and after it
+        assert_eq!(
+            recurring_break_windows(&nightly, (on(2, 23, 0), on(3, 1, 0))).count(),
+            3
+        );
+    }
+
+    #[test]
+    fn the_historical_driver_wins_among_equal_options() {
+        let drivers = [driver("d1"), driver("d2"), driver("d3")];
+        let vehicles = [vehicle("v1"), vehicle("v2"), vehicle("v3")];
+        let orders = [
+            order("o1", at(8, 0), at(9, 0)),
+            order("o2", at(8, 0), at(9, 0)),
+        ];
+        let base = SolverConfig {
+            historical_assignments: vec![
+                PlannedAssignment::from(&assignment("o1", "d2", "v1", (at(8, 0), at(9, 0)))),
+                PlannedAssignment::from(&assignment("o2", "d3", "v1", (at(8, 0), at(9, 0)))),
+            ],
+            continuity_bonus: 0.5,
+            ..SolverConfig::default()
+        };
+        for seed in 0..5 {
+            let config = SolverConfig {
+                seed: Some(seed),
+                ..quick(base.clone())
+            };
+            let response = solve(&drivers, &vehicles, &orders, &config);
+            let mut drivers_used: Vec<(&str, &str)> = response
+                .assignments
+                .iter()
+                .map(|a| (a.order_id.as_str(), a.driver_id.as_str()))
+                .collect();
+            drivers_used.sort();
+            assert_eq!(drivers_used, [("o1", "d2"), ("o2", "d3")], "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn a_concentrated_schedule_beats_a_spread_one_under_driver_count_penalty() {
+        let drivers = [driver("d1"), driver("d2")];
+        let vehicles = [vehicle("v1"), vehicle("v2")];
+        let orders = [
+            order("o1", at(8, 0), at(9, 0)),
+            order("o2", at(10, 0), at(11, 0)),
+        ];
+        let index = ProblemIndex::new(&drivers, &vehicles, &orders);
+        let config = SolverConfig {
+            driver_count_penalty: 0.5,
+            ..SolverConfig::default()
+        };
+        let priority_map = build_priority_map(&orders, &config);
+        let score = |second_driver: &Driver, config: &SolverConfig| {
+            let mut state = SolverState::new(&drivers, &vehicles);
+            state.assign_order(&orders[0], &drivers[0], &vehicles[0], (at(8, 0), at(9, 0)));
+            state.assign_order(
+                &orders[1],
+                second_driver,
+                &vehicles[1],
+                (at(10, 0), at(11, 0)),
+            );
+            state.calculate_score(&priority_map, &drivers, &vehicles, &orders, &index, config)
+        };
+        let concentrated = score(&drivers[0], &config);
+        let spread = score(&drivers[1], &config);
+        assert_eq!((concentrated, spread), (1.5, 1.0));
+        // Without the penalty neither is preferred
+        let neutral = SolverConfig::default();
+        assert_eq!(score(&drivers[0], &neutral), score(&drivers[1], &neutral));
+
+        let response = solve(&drivers, &vehicles, &orders, &quick(config));
+        let used: HashSet<&str> = response
+            .assignments
+            .iter()
+            .map(|a| a.driver_id.as_str())
+            .collect();
+        assert_eq!((response.assignments.len(), used.len()), (2, 1));
+    }
+
+    #[test]
+    fn touching_allowed_decides_back_to_back_orders_at_the_boundary() {
+        let drivers = [driver("d1"), driver("d2")];
+        let vehicles = [vehicle("v1"), vehicle("v2")];
+        let first = order("o1", at(8, 0), at(9, 0));
+        let next = order("o2", at(9, 0), at(10, 0));
+        let half_open = SolverConfig {
+            mandatory_break_minutes: 0,
+            ..SolverConfig::default()
+        };
+        let closed = SolverConfig {
+            touching_allowed: false,
+            ..half_open.clone()
+        };
+        assert!(orders_touch((at(8, 0), at(9, 0)), (at(9, 0), at(10, 0))));
+        assert!(!orders_overlap((at(8, 0), at(9, 0)), (at(9, 0), at(10, 0))));
+
+        let mut state = SolverState::new(&drivers, &vehicles);
+        state.assign_order(&first, &drivers[0], &vehicles[0], (at(8, 0), at(9, 0)));
+        // Same driver, or the same vehicle under another driver
+        for (d, v) in [(&drivers[0], &vehicles[1]), (&drivers[1], &vehicles[0])] {
+            assert!(slot_in(&state, &next, d, v, &half_open).is_some());
+            assert_eq!(slot_in(&state, &next, d, v, &closed), None);
+        }
+        // One minute apart fits either way
+        let later = order("o3", at(9, 1), at(10, 0));
+        assert!(slot_in(&state, &later, &drivers[0], &vehicles[0], &closed).is_some());
+
+        // Breaks stay half-open, an order may start as a break ends
+        let resting = Driver {
+            breaks: Some(vec![Break {
+                from: at(8, 0),
+                to: at(9, 0),
+            }]),
+            ..driver("d3")
+        };
+        assert!(!is_driver_on_break(&resting, (at(9, 0), at(10, 0))));
+        let state = SolverState::new(std::slice::from_ref(&resting), &vehicles);
+        assert!(slot_in(&state, &next, &resting, &vehicles[0], &closed).is_some());
+    }
+
+    #[test]
+    fn a_two_person_order_gets_the_only_free_pair() {
+        let drivers = [
+            Driver {
+                breaks: Some(vec![Break {
+                    from: at(10, 0),
+                    to: at(10, 30),
+                }]),
+                ..driver("d1")
+            },
+            Driver {
+                shifts: Some(vec![(at(6, 0), at(10, 0))]),
+                ..driver("d2")
+            },
+            driver("d3"),
+            Driver {
+                allowed_vehicle_ids: Some(vec!["v2".to_string()]),
+                ..driver("d4")
+            },
+            driver("d5"),
+        ];
+        // v2 is in the workshop, so d4 cannot join either
+        let vehicles = [
+            vehicle("v1"),
+            Vehicle {
+                unavailable: Some(vec![Break {
+                    from: at(9, 0),
+                    to: at(12, 0),
+                }]),
+                ..vehicle("v2")
+            },
+        ];
+        let orders = [Order {
+            required_drivers: Some(2),
+            ..order("lift", at(10, 0), at(11, 0))
+        }];
+        let spacing = Spacing::from_config(&SolverConfig::default());
+        let state = SolverState::new(&drivers, &vehicles);
+        let slot = (at(10, 0), at(11, 0));
+        let crew = state
+            .crew_for(
+                &orders[0],
+                &drivers[2],
+                &vehicles[0],
+                slot,
+                &drivers,
+                spacing,
+            )
+            .unwrap();
+        assert_eq!(
+            crew.iter().map(|d| d.id.as_str()).collect::<Vec<_>>(),
+            ["d3", "d5"]
+        );
+
+        for seed in 0..3 {
+            let config = SolverConfig {
+                seed: Some(seed),
+                ..quick(SolverConfig::default())
+            };
+            let response = solve(&drivers, &vehicles, &orders, &config);
+            let mut crew: Vec<(&str, &str)> = response
+                .assignments
+                .iter()
+                .map(|a| (a.driver_id.as_str(), a.vehicle_id.as_str()))
+                .collect();
+            crew.sort();
+            assert_eq!(crew, [("d3", "v1"), ("d5", "v1")], "seed {}", seed);
+            // Credited once although two rows carry the order
+            assert_eq!(response.score, 1.0);
+        }
+    }
+
+    #[test]
+    fn priority_desc_always_assigns_the_top_order_when_it_fits() {
+        let init = |drivers: &[Driver],
+                    vehicles: &[Vehicle],
+                    orders: &[Order],
+                    init_order: InitOrder,
+                    seed: u64| {
+            let config = SolverConfig::default();
+            let index = ProblemIndex::new(drivers, vehicles, orders);
+            let priority_map = build_priority_map(orders, &config);
+            let mut rng = StdRng::seed_from_u64(seed);
+            initialize_random_state(
+                drivers,
+                vehicles,
+                orders,
+                &index,
+                &priority_map,
+                &config,
+                init_order,
+                &mut rng,
+            )
+        };
+        let has =
+            |state: &SolverState, id: &str| state.assignments.iter().any(|a| a.order_id == id);
+
+        // Ten orders at once for one driver, only one can be placed
+        let drivers = [driver("d1")];
+        let vehicles = [vehicle("v1")];
+        let mut orders: Vec<Order> = (0..10)
+            .map(|i| order(&format!("o{}", i), at(8, 0), at(9, 0)))
+            .collect();
+        orders[7].priority = Some(5.0);
+        let mut shuffled_hits = 0;
+        for seed in 0..20 {
+            assert!(has(
+                &init(&drivers, &vehicles, &orders, InitOrder::PriorityDesc, seed),
+                "o7"
+            ));
+            shuffled_hits += usize::from(has(
+                &init(&drivers, &vehicles, &orders, InitOrder::Shuffle, seed),
+                "o7",
+            ));
+        }
+        assert!(shuffled_hits < 20);
+
+        // On mixed instances too, whenever the top order fits an empty plan
+        for seed in 0..20 {
+            let (drivers, vehicles, mut orders) = random_instance(30, 3, 2, seed);
+            let top = seed as usize % orders.len();
+            orders[top].priority = Some(100.0);
+            let index = ProblemIndex::new(&drivers, &vehicles, &orders);
+            let empty = SolverState::new(&drivers, &vehicles);
+            let spacing = Spacing::from_config(&SolverConfig::default());
+            let fits =
+                !feasible_placements(&empty, &orders[top], &drivers, &vehicles, &index, spacing)
+                    .is_empty();
+            let state = init(&drivers, &vehicles, &orders, InitOrder::PriorityDesc, seed);
+            assert_eq!(has(&state, &orders[top].id), fits, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn a_distant_order_past_max_distance_km_is_rejected() {
+        let located = |id: &str, start: NaiveDateTime, (lat, lon): (f64, f64)| Order {
+            lat: Some(lat),
+            lon: Some(lon),
+            ..order(id, start, start + ChronoDuration::hours(1))
+        };
+        let drivers = [driver("d1")];
+        let vehicles = [Vehicle {
+            max_distance_km: Some(50.0),
+            ..vehicle("v1")
+        }];
+        // Two stops in Berlin, about 1.3 km apart, and one in Hamburg, about 255 km away. The
+        // afternoon start leaves time for the drive, so only the range stands in the way.
+        let first = located("o1", at(8, 0), (52.52, 13.40));
+        let nearby = located("o2", at(10, 0), (52.53, 13.41));
+        let distant = located("o3", at(18, 0), (53.55, 10.00));
+        let config = SolverConfig::default();
+        let spacing = Spacing::from_config(&config);
+
+        let mut state = SolverState::new(&drivers, &vehicles);
+        state.assign_order(&first, &drivers[0], &vehicles[0], (at(8, 0), at(9, 0)));
+        let schedule = &state.vehicle_schedules["v1"];
+        let rejection = |o: &Order, vehicle: &Vehicle| {
+            vehicle_rejection(o, (o.start_time, o.end_time), vehicle, schedule, spacing)
+        };
+        assert_eq!(rejection(&nearby, &vehicles[0]), None);
+        assert_eq!(rejection(&distant, &vehicles[0]), Some(Rejection::Range));
+        assert_eq!(rejection(&distant, &vehicle("v1")), None);
+        assert_eq!(
+            slot_in(&state, &distant, &drivers[0], &vehicles[0], &config),
+            None
+        );
+
+        let orders = [first, nearby, distant];
+        let response = solve(&drivers, &vehicles, &orders, &quick(config));
+        assert_eq!(assigned_ids(&response), ["o1", "o2"]);
+    }
+
+    #[test]
+    fn a_pin_with_only_a_start_time_takes_the_duration_from_the_order() {
+        let orders = [Order {
+            duration_minutes: Some(60),
+            ..order("o1", at(8, 0), at(12, 0))
+        }];
+        let config = SolverConfig {
+            pinned: vec![PlannedAssignment {
+                order_id: "o1".to_string(),
+                driver_id: "d1".to_string(),
+                vehicle_id: "v1".to_string(),
+                start_time: Some(at(10, 0)),
+                end_time: None,
+                weight: None,
+            }],
+            ..quick(SolverConfig::default())
+        };
+        let response = solve(&[driver("d1")], &[vehicle("v1")], &orders, &config);
+        assert_eq!(response.assignments.len(), 1);
+        let a = &response.assignments[0];
+        assert!(a.pinned);
+        assert_eq!((a.start_time, a.end_time), (at(10, 0), at(11, 0)));
+    }
+
+    #[test]
+    fn best_of_seeds_wrap_at_two_to_the_53() {
+        assert_eq!(run_seed(7, 2), 9);
+        assert_eq!(run_seed(MAX_SAFE_INTEGER, 0), MAX_SAFE_INTEGER);
+        assert_eq!(run_seed(MAX_SAFE_INTEGER, 1), 0);
+        assert_eq!(run_seed(MAX_SAFE_INTEGER - 1, 3), 1);
+    }
 }
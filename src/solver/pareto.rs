@@ -0,0 +1,120 @@
+use super::{Driver, Order, ProblemIndex, SchedulingResponse, SolverConfig, SolverState, Vehicle};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// The two objectives the front trades off, both to be maximized
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ParetoObjectives {
+    // Summed order terms of the score breakdown, before the priority weight
+    pub priority: f64,
+    // Negative variance of the number of orders per vehicle
+    pub balance: f64,
+}
+
+impl ParetoObjectives {
+    fn of(
+        state: &SolverState,
+        priority_map: &HashMap<String, f64>,
+        index: &ProblemIndex,
+        orders: &[Order],
+        config: &SolverConfig,
+    ) -> ParetoObjectives {
+        let priority = state
+            .score_breakdown(priority_map, index, orders, config)
+            .iter()
+            .map(|s| {
+                s.base_priority + s.preference_bonus + s.earliness_bonus + s.continuity_bonus
+                    - s.unassigned_penalty
+            })
+            .sum();
+        ParetoObjectives {
+            priority,
+            balance: -state.vehicle_load_variance(),
+        }
+    }
+
+    fn dominates(&self, other: &ParetoObjectives) -> bool {
+        self.priority >= other.priority
+            && self.balance >= other.balance
+            && (self.priority > other.priority || self.balance > other.balance)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ParetoSolution {
+    pub objectives: ParetoObjectives,
+    pub response: SchedulingResponse,
+}
+
+// Non-dominated individuals seen during a run. Past the limit the front is thinned to evenly
+// spaced members along the priority axis, both extremes kept.
+pub(super) struct ParetoArchive {
+    limit: usize,
+    members: Vec<(ParetoObjectives, SolverState)>,
+}
+
+impl ParetoArchive {
+    pub(super) fn new(limit: usize) -> ParetoArchive {
+        ParetoArchive {
+            limit,
+            members: Vec::new(),
+        }
+    }
+
+    pub(super) fn offer(
+        &mut self,
+        state: &SolverState,
+        priority_map: &HashMap<String, f64>,
+        index: &ProblemIndex,
+        orders: &[Order],
+        config: &SolverConfig,
+    ) {
+        let objectives = ParetoObjectives::of(state, priority_map, index, orders, config);
+        // Equal objectives count as covered, the first individual to reach them stays
+        if self
+            .members
+            .iter()
+            .any(|(kept, _)| kept.dominates(&objectives) || *kept == objectives)
+        {
+            return;
+        }
+        self.members.retain(|(kept, _)| !objectives.dominates(kept));
+        self.members.push((objectives, state.clone()));
+
+        if self.members.len() > self.limit {
+            self.members
+                .sort_by(|a, b| b.0.priority.total_cmp(&a.0.priority));
+            let last = self.members.len() - 1;
+            let keep: Vec<usize> = (0..self.limit)
+                .map(|i| i * last / (self.limit - 1).max(1))
+                .collect();
+            self.members = std::mem::take(&mut self.members)
+                .into_iter()
+                .enumerate()
+                .filter(|(i, _)| keep.contains(i))
+                .map(|(_, member)| member)
+                .collect();
+        }
+    }
+
+    // Highest priority first
+    pub(super) fn into_solutions(
+        mut self,
+        priority_map: &HashMap<String, f64>,
+        drivers: &[Driver],
+        vehicles: &[Vehicle],
+        orders: &[Order],
+        index: &ProblemIndex,
+        config: &SolverConfig,
+    ) -> Vec<ParetoSolution> {
+        self.members
+            .sort_by(|a, b| b.0.priority.total_cmp(&a.0.priority));
+        self.members
+            .into_iter()
+            .map(|(objectives, state)| ParetoSolution {
+                objectives,
+                response: state.to_response(priority_map, drivers, vehicles, orders, index, config),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,240 @@
+use super::{
+    assigned_piece, feasible_placements, first_infeasible_assignment, repair_constraints, Driver,
+    Order, ProblemIndex, SolverConfig, SolverState, Spacing, Vehicle,
+};
+use std::collections::{HashMap, HashSet};
+
+// Moves assignments from the busiest drivers to ones with at least two fewer until the spread
+// of assignment counts is within config.max_assignment_imbalance, or nothing can move. Each
+// move keeps the vehicle and the original slot when the new driver allows it. Orders tied to
+// others through groups or precedence stay with their driver. When the repair after the moves
+// would drop an order the plan is left as it was, so an unreachable cap is left to the
+// imbalance penalty.
+pub fn rebalance_drivers(
+    state: &mut SolverState,
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    index: &ProblemIndex,
+    priority_map: &HashMap<String, f64>,
+    config: &SolverConfig,
+) {
+    let Some(cap) = config.max_assignment_imbalance else {
+        return;
+    };
+    let spacing = Spacing::from_config(config);
+    let predecessors: HashSet<&str> = orders
+        .iter()
+        .flat_map(|o| o.must_follow.iter().flatten())
+        .map(String::as_str)
+        .collect();
+    let before = state.clone();
+    // Every move lowers the sum of squared counts, so the loop ends
+    while state.assignment_imbalance() > cap {
+        let Some(moved) = overload_move(state, drivers, vehicles, index, &predecessors, spacing)
+        else {
+            break;
+        };
+        *state = moved;
+    }
+
+    repair_constraints(state, orders);
+    let kept: HashSet<&str> = state
+        .assignments
+        .iter()
+        .map(|a| a.order_id.as_str())
+        .collect();
+    if before
+        .assignments
+        .iter()
+        .any(|a| !kept.contains(a.order_id.as_str()))
+    {
+        *state = before;
+    }
+    state.score = state.calculate_score(priority_map, drivers, vehicles, orders, index, config);
+    debug_assert_eq!(
+        first_infeasible_assignment(state, drivers, vehicles, index, config),
+        None
+    );
+}
+
+// The plan with one assignment of a busiest driver handed to a driver holding at least two
+// fewer, the least busy first, or None when no such handover is feasible. predecessors holds
+// the ids named in some order's must_follow.
+fn overload_move(
+    state: &SolverState,
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    index: &ProblemIndex,
+    predecessors: &HashSet<&str>,
+    spacing: Spacing,
+) -> Option<SolverState> {
+    let load = |driver_id: &str| state.driver_schedules[driver_id].len();
+    let busiest = state.driver_schedules.values().map(Vec::len).max()?;
+    let mut targets: Vec<&Driver> = drivers
+        .iter()
+        .filter(|d| load(&d.id) + 2 <= busiest)
+        .collect();
+    targets.sort_by_key(|d| load(&d.id));
+
+    for (i, assignment) in state.assignments.iter().enumerate() {
+        // Relaxed assignments only fit because they bend the limits, pinned ones are fixed, a
+        // crew member cannot move without the others and neither can a group or precedence
+        // member without its partners
+        let order = index.orders[assignment.order_id.as_str()];
+        if assignment.relaxed
+            || assignment.pinned
+            || order.crew_size() > 1
+            || order.group_id.is_some()
+            || order.same_vehicle_group.is_some()
+            || order
+                .must_follow
+                .as_ref()
+                .is_some_and(|preds| !preds.is_empty())
+            || predecessors.contains(order.id.as_str())
+            || load(&assignment.driver_id) < busiest
+        {
+            continue;
+        }
+        let mut candidate = state.clone();
+        let removed = candidate.unassign(i);
+        let piece = assigned_piece(order, &removed);
+        let placements = feasible_placements(&candidate, &piece, drivers, vehicles, index, spacing);
+        for target in &targets {
+            let mut on_target = placements
+                .iter()
+                .filter(|(d, v, _)| d.id == target.id && v.id == removed.vehicle_id);
+            let first = on_target.clone().next();
+            let keeps_slot =
+                on_target.find(|(_, _, slot)| *slot == (removed.start_time, removed.end_time));
+            if let Some(&(driver, vehicle, slot)) = keeps_slot.or(first) {
+                candidate.assign_order(&piece, driver, vehicle, slot);
+                return Some(candidate);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fixtures::{at, driver, order, vehicle};
+    use super::super::{build_priority_map, solve};
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+
+    // Six one-hour orders, 30 minutes apart, that one driver and one vehicle could do in a row
+    fn symmetric_orders() -> Vec<Order> {
+        (0..6)
+            .map(|i| {
+                let start = at(8, 0) + ChronoDuration::minutes(90 * i);
+                order(&format!("o{}", i), start, start + ChronoDuration::hours(1))
+            })
+            .collect()
+    }
+
+    fn counts(drivers: &[Driver], driver_ids: Vec<&str>) -> Vec<usize> {
+        drivers
+            .iter()
+            .map(|d| driver_ids.iter().filter(|id| **id == d.id).count())
+            .collect()
+    }
+
+    #[test]
+    fn six_symmetric_orders_spread_evenly_over_three_drivers() {
+        let drivers = [driver("d1"), driver("d2"), driver("d3")];
+        let vehicles = [vehicle("v1")];
+        let orders = symmetric_orders();
+        let index = ProblemIndex::new(&drivers, &vehicles, &orders);
+        let config = SolverConfig {
+            max_assignment_imbalance: Some(0),
+            imbalance_penalty: 0.5,
+            ..SolverConfig::default()
+        };
+        let priority_map = build_priority_map(&orders, &config);
+
+        // Everything on d1 is feasible, rebalancing hands two orders to each driver
+        let mut state = SolverState::new(&drivers, &vehicles);
+        for o in &orders {
+            state.assign_order(o, &drivers[0], &vehicles[0], (o.start_time, o.end_time));
+        }
+        rebalance_drivers(
+            &mut state,
+            &drivers,
+            &vehicles,
+            &orders,
+            &index,
+            &priority_map,
+            &config,
+        );
+        assert_eq!(state.assignments.len(), 6);
+        let ids = state
+            .assignments
+            .iter()
+            .map(|a| a.driver_id.as_str())
+            .collect();
+        assert_eq!(counts(&drivers, ids), [2, 2, 2]);
+
+        for seed in 0..5 {
+            let config = SolverConfig {
+                seed: Some(seed),
+                population_size: 16,
+                generations: 30,
+                ignore_time_budget: true,
+                ..config.clone()
+            };
+            let response = solve(&drivers, &vehicles, &orders, &config);
+            assert_eq!(response.assignments.len(), 6);
+            let ids = response
+                .assignments
+                .iter()
+                .map(|a| a.driver_id.as_str())
+                .collect();
+            assert_eq!(counts(&drivers, ids), [2, 2, 2], "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn grouped_and_chained_orders_stay_with_their_driver() {
+        let drivers = [driver("d1"), driver("d2"), driver("d3")];
+        let vehicles = [vehicle("v1")];
+        // o0 and o1 share a group, o3 has to follow o2, only o4 and o5 are free to move
+        let mut orders = symmetric_orders();
+        orders[0].group_id = Some("g".to_string());
+        orders[1].group_id = Some("g".to_string());
+        orders[3].must_follow = Some(vec!["o2".to_string()]);
+        let index = ProblemIndex::new(&drivers, &vehicles, &orders);
+        let config = SolverConfig {
+            max_assignment_imbalance: Some(0),
+            imbalance_penalty: 0.5,
+            ..SolverConfig::default()
+        };
+        let priority_map = build_priority_map(&orders, &config);
+
+        let mut state = SolverState::new(&drivers, &vehicles);
+        for o in &orders {
+            state.assign_order(o, &drivers[0], &vehicles[0], (o.start_time, o.end_time));
+        }
+        rebalance_drivers(
+            &mut state,
+            &drivers,
+            &vehicles,
+            &orders,
+            &index,
+            &priority_map,
+            &config,
+        );
+        assert_eq!(state.assignments.len(), 6);
+        for a in &state.assignments {
+            if ["o0", "o1", "o2", "o3"].contains(&a.order_id.as_str()) {
+                assert_eq!(a.driver_id, "d1", "{}", a.order_id);
+            }
+        }
+        let ids = state
+            .assignments
+            .iter()
+            .map(|a| a.driver_id.as_str())
+            .collect();
+        assert_eq!(counts(&drivers, ids), [4, 1, 1]);
+    }
+}
@@ -0,0 +1,257 @@
+use super::{
+    assigned_piece, build_priority_map, fill_unassigned, mutate, place_pinned, repair_constraints,
+    seed_or_random, Assignment, Driver, Order, ProblemIndex, SchedulingResponse, SolverConfig,
+    SolverState, Spacing, Vehicle,
+};
+use rand::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ResolveResponse {
+    pub response: SchedulingResponse,
+    // Remaining orders whose assignments differ from the previous response
+    pub changed_order_ids: Vec<String>,
+}
+
+// Drops the removed order from an earlier solution and runs a short local search to reuse the
+// freed capacity, instead of solving from scratch. orders may still contain the removed order.
+// config.pinned is booked first, as in solve, and the earlier assignments that no longer fit
+// around the pins and the current inputs are dropped before the search.
+pub fn resolve_after_removal(
+    previous_response: &SchedulingResponse,
+    removed_order_id: &str,
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    config: &SolverConfig,
+) -> ResolveResponse {
+    let orders: Vec<Order> = orders
+        .iter()
+        .filter(|o| o.id != removed_order_id)
+        .cloned()
+        .collect();
+    let orders = &orders[..];
+    let priority_map = build_priority_map(orders, config);
+    let spacing = Spacing::from_config(config);
+    let mut index = ProblemIndex::new(drivers, vehicles, orders);
+    // Pins are expected to have passed check_pinned, one that fails here is left out together
+    // with the pins after it
+    let mut pinned = SolverState::new(drivers, vehicles);
+    let _ = place_pinned(&mut pinned, &index, config);
+    index.pin(pinned.assignments);
+    let seed_used = seed_or_random(config);
+    let mut rng = StdRng::seed_from_u64(seed_used);
+
+    // Replay the previous plan at its scheduled times, in start order so predecessors come
+    // first. Relaxed assignments broke the limits on purpose and are kept without the check.
+    let mut state = SolverState::with_pinned(drivers, vehicles, &index);
+    let mut replay: Vec<&Assignment> = previous_response.assignments.iter().collect();
+    replay.sort_by_key(|a| a.start_time);
+    for assignment in replay {
+        let (Some(&order), Some(&driver), Some(&vehicle)) = (
+            index.orders.get(assignment.order_id.as_str()),
+            index.drivers.get(assignment.driver_id.as_str()),
+            index.vehicles.get(assignment.vehicle_id.as_str()),
+        ) else {
+            continue;
+        };
+        let placed = state
+            .assignments
+            .iter()
+            .filter(|a| a.order_id == order.id)
+            .count();
+        if index.pinned_order_ids.contains(&order.id)
+            || (!order.splittable && placed >= order.crew_size())
+        {
+            continue;
+        }
+        let piece = assigned_piece(order, assignment);
+        let slot = (assignment.start_time, assignment.end_time);
+        let relaxed = previous_response
+            .relaxed_assignments
+            .contains(&assignment.order_id);
+        if relaxed || state.slot_fits(&piece, driver, vehicle, slot, spacing) {
+            state.assign_order(&piece, driver, vehicle, slot);
+            if let Some(replayed) = state.assignments.last_mut() {
+                replayed.relaxed = relaxed;
+            }
+        }
+    }
+
+    fill_unassigned(
+        &mut state, drivers, vehicles, orders, &index, config, &mut rng,
+    );
+    repair_constraints(&mut state, orders);
+    state.score = state.calculate_score(&priority_map, drivers, vehicles, orders, &index, config);
+
+    // Hill climb: keep a mutated copy only when it scores strictly better
+    for _ in 0..config.local_search_iterations {
+        let mut candidate = state.clone();
+        mutate(
+            &mut candidate,
+            drivers,
+            vehicles,
+            orders,
+            &index,
+            &priority_map,
+            config,
+            &mut rng,
+        );
+        fill_unassigned(
+            &mut candidate,
+            drivers,
+            vehicles,
+            orders,
+            &index,
+            config,
+            &mut rng,
+        );
+        repair_constraints(&mut candidate, orders);
+        candidate.score =
+            candidate.calculate_score(&priority_map, drivers, vehicles, orders, &index, config);
+        if candidate.score > state.score {
+            state = candidate;
+        }
+    }
+
+    let previous = assignments_by_order(&previous_response.assignments);
+    let current = assignments_by_order(&state.assignments);
+    let changed_order_ids = orders
+        .iter()
+        .filter(|o| previous.get(&o.id) != current.get(&o.id))
+        .map(|o| o.id.clone())
+        .collect();
+
+    let mut response = state.to_response(&priority_map, drivers, vehicles, orders, &index, config);
+    response.seed_used = seed_used;
+    ResolveResponse {
+        response,
+        changed_order_ids,
+    }
+}
+
+type AssignmentKey = (String, String, i64, i64, Option<u64>);
+
+fn assignments_by_order(assignments: &[Assignment]) -> HashMap<String, BTreeSet<AssignmentKey>> {
+    let mut by_order: HashMap<String, BTreeSet<AssignmentKey>> = HashMap::new();
+    for assignment in assignments {
+        by_order
+            .entry(assignment.order_id.clone())
+            .or_default()
+            .insert((
+                assignment.driver_id.clone(),
+                assignment.vehicle_id.clone(),
+                assignment.start_time.and_utc().timestamp_millis(),
+                assignment.end_time.and_utc().timestamp_millis(),
+                assignment.weight.map(f64::to_bits),
+            ));
+    }
+    by_order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fixtures::{assignment, at, driver, order, response, vehicle};
+    use super::super::{evaluate_schedule, PlannedAssignment};
+    use super::*;
+
+    fn config() -> SolverConfig {
+        SolverConfig {
+            seed: Some(3),
+            local_search_iterations: 50,
+            ..SolverConfig::default()
+        }
+    }
+
+    #[test]
+    fn reuses_the_capacity_of_the_removed_order() {
+        let orders = [
+            order("o1", at(9, 0), at(10, 0)),
+            order("o2", at(9, 30), at(10, 30)),
+        ];
+        let previous = response(vec![assignment("o1", "d1", "v1", (at(9, 0), at(10, 0)))]);
+        let resolved = resolve_after_removal(
+            &previous,
+            "o1",
+            &[driver("d1")],
+            &[vehicle("v1")],
+            &orders,
+            &config(),
+        );
+        assert_eq!(
+            resolved.response.assignments,
+            vec![assignment("o2", "d1", "v1", (at(9, 30), at(10, 30)))]
+        );
+        assert_eq!(resolved.changed_order_ids, vec!["o2"]);
+    }
+
+    #[test]
+    fn keeps_pinned_assignments_in_place() {
+        let drivers = [driver("d1"), driver("d2")];
+        let vehicles = [vehicle("v1"), vehicle("v2")];
+        let orders = [
+            order("o1", at(9, 0), at(10, 0)),
+            order("o2", at(11, 0), at(12, 0)),
+            order("o3", at(13, 0), at(14, 0)),
+        ];
+        let pin = assignment("o2", "d2", "v2", (at(11, 0), at(12, 0)));
+        let config = SolverConfig {
+            pinned: vec![PlannedAssignment::from(&pin)],
+            ..config()
+        };
+        // The earlier plan had o2 elsewhere, the pin wins over it
+        let previous = response(vec![
+            assignment("o1", "d1", "v1", (at(9, 0), at(10, 0))),
+            assignment("o2", "d1", "v1", (at(11, 0), at(12, 0))),
+            assignment("o3", "d1", "v1", (at(13, 0), at(14, 0))),
+        ]);
+        let resolved =
+            resolve_after_removal(&previous, "o1", &drivers, &vehicles, &orders, &config);
+        let o2: Vec<&Assignment> = resolved
+            .response
+            .assignments
+            .iter()
+            .filter(|a| a.order_id == "o2")
+            .collect();
+        assert_eq!(o2.len(), 1);
+        assert_eq!(
+            (&o2[0].driver_id, &o2[0].vehicle_id),
+            (&pin.driver_id, &pin.vehicle_id)
+        );
+        assert!(o2[0].pinned);
+    }
+
+    #[test]
+    fn drops_earlier_assignments_that_no_longer_fit() {
+        let drivers = [driver("d1")];
+        let vehicles = [vehicle("v1"), vehicle("v2")];
+        let orders = [
+            order("o1", at(8, 0), at(9, 0)),
+            order("o2", at(9, 0), at(10, 0)),
+            order("o3", at(9, 15), at(10, 15)),
+        ];
+        // o3 overlaps o2 on the only driver, the earlier plan must have been made for other inputs
+        let previous = response(vec![
+            assignment("o1", "d1", "v1", (at(8, 0), at(9, 0))),
+            assignment("o2", "d1", "v1", (at(9, 0), at(10, 0))),
+            assignment("o3", "d1", "v2", (at(9, 15), at(10, 15))),
+        ]);
+        let config = SolverConfig {
+            mandatory_break_minutes: 0,
+            ..config()
+        };
+        let resolved =
+            resolve_after_removal(&previous, "o1", &drivers, &vehicles, &orders, &config);
+        let plan = &resolved.response.assignments;
+        let plan: Vec<PlannedAssignment> = plan.iter().map(PlannedAssignment::from).collect();
+        let evaluation = evaluate_schedule(&drivers, &vehicles, &orders[1..], &plan, &config);
+        assert!(
+            evaluation.violations.is_empty(),
+            "{:?}",
+            evaluation.violations
+        );
+        assert_eq!(plan.len(), 1);
+        assert_eq!(resolved.response.unassigned_order_ids.len(), 1);
+    }
+}
@@ -0,0 +1,105 @@
+use chrono::{DateTime, NaiveDateTime};
+use serde::de::{self, Visitor};
+use serde::{Deserializer, Serializer};
+use serde_with::{DeserializeAs, SerializeAs, TimestampMilliSeconds};
+use std::fmt;
+
+// Reads a timestamp given either as milliseconds since the epoch or as an RFC 3339 string,
+// string offsets are converted to UTC. Always written back as milliseconds.
+pub struct FlexibleTimestamp;
+
+impl SerializeAs<NaiveDateTime> for FlexibleTimestamp {
+    fn serialize_as<S: Serializer>(
+        source: &NaiveDateTime,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        TimestampMilliSeconds::<i64>::serialize_as(source, serializer)
+    }
+}
+
+impl<'de> DeserializeAs<'de, NaiveDateTime> for FlexibleTimestamp {
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<NaiveDateTime, D::Error> {
+        deserializer.deserialize_any(FlexibleTimestampVisitor)
+    }
+}
+
+struct FlexibleTimestampVisitor;
+
+impl Visitor<'_> for FlexibleTimestampVisitor {
+    type Value = NaiveDateTime;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("milliseconds since the epoch or an RFC 3339 string")
+    }
+
+    fn visit_i64<E: de::Error>(self, millis: i64) -> Result<NaiveDateTime, E> {
+        DateTime::from_timestamp_millis(millis)
+            .map(|t| t.naive_utc())
+            .ok_or_else(|| E::custom(format!("timestamp {} is out of range", millis)))
+    }
+
+    fn visit_u64<E: de::Error>(self, millis: u64) -> Result<NaiveDateTime, E> {
+        let millis = i64::try_from(millis)
+            .map_err(|_| E::custom(format!("timestamp {} is out of range", millis)))?;
+        self.visit_i64(millis)
+    }
+
+    // JS numbers beyond the safe integer range arrive as floats
+    fn visit_f64<E: de::Error>(self, millis: f64) -> Result<NaiveDateTime, E> {
+        if millis.fract() != 0.0 || !millis.is_finite() {
+            return Err(E::custom(format!(
+                "timestamp {} is not a whole number of milliseconds",
+                millis
+            )));
+        }
+        self.visit_i64(millis as i64)
+    }
+
+    fn visit_str<E: de::Error>(self, text: &str) -> Result<NaiveDateTime, E> {
+        DateTime::parse_from_rfc3339(text)
+            .map(|t| t.naive_utc())
+            .map_err(|e| E::custom(format!("invalid RFC 3339 timestamp {:?}: {}", text, e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fixtures::at;
+    use super::*;
+    use serde::de::value::{Error, F64Deserializer, I64Deserializer, StrDeserializer};
+
+    fn from_millis(millis: i64) -> Result<NaiveDateTime, Error> {
+        FlexibleTimestamp::deserialize_as(I64Deserializer::<Error>::new(millis))
+    }
+
+    fn from_text(text: &str) -> Result<NaiveDateTime, Error> {
+        FlexibleTimestamp::deserialize_as(StrDeserializer::<Error>::new(text))
+    }
+
+    #[test]
+    fn millis_and_rfc3339_read_the_same_time() {
+        let expected = at(9, 30);
+        let millis = expected.and_utc().timestamp_millis();
+        assert_eq!(from_millis(millis).unwrap(), expected);
+        assert_eq!(
+            from_text(&expected.and_utc().to_rfc3339()).unwrap(),
+            expected
+        );
+        assert_eq!(from_text("2024-01-01T09:30:00Z").unwrap(), expected);
+        // Offsets are converted to UTC
+        assert_eq!(from_text("2024-01-01T11:30:00+02:00").unwrap(), expected);
+        let whole_float = F64Deserializer::<Error>::new(millis as f64);
+        assert_eq!(
+            FlexibleTimestamp::deserialize_as(whole_float).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_timestamps() {
+        assert!(from_text("2024-01-01 09:30").is_err());
+        assert!(from_millis(i64::MAX).is_err());
+        let fractional = F64Deserializer::<Error>::new(1.5);
+        assert!(FlexibleTimestamp::deserialize_as(fractional).is_err());
+    }
+}
@@ -0,0 +1,343 @@
+use super::{Assignment, Driver, Order, SolverConfig, Vehicle};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+// Problems with the input payload that would make the solver output meaningless
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "kind")]
+pub enum ValidationError {
+    EmptyDrivers,
+    EmptyVehicles,
+    EmptyOrders,
+    DuplicateOrderId { order_id: String },
+    InvalidTimeRange { order_id: String },
+    // A duration_minutes of zero or below
+    InvalidDuration { order_id: String },
+    // earliest_start after latest_end, either bound falling back to start_time or end_time
+    InvalidWindow { order_id: String },
+    NegativeServiceTime { order_id: String },
+    NegativeWeight { order_id: String },
+    UnknownTag { order_id: String, tag: String },
+    // A break ending before it starts, usually times of day given for a break across midnight
+    InvalidBreak { driver_id: String },
+    // A daily break starting outside the day or lasting longer than one
+    InvalidDailyBreak { driver_id: String },
+    // required_drivers of zero, or above one on a splittable order
+    InvalidRequiredDrivers { order_id: String },
+    // A negative or non-finite max_distance_km
+    InvalidMaxDistance { vehicle_id: String },
+}
+
+// Inputs the solver accepts but that usually point to a data bug, such as weights given in
+// grams against capacities in kilograms. unit echoes config.weight_unit or volume_unit.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum ValidationWarning {
+    // More than the largest vehicle carries, or than the whole fleet for a splittable order
+    WeightExceedsFleet {
+        order_id: String,
+        weight: f64,
+        capacity: f64,
+        unit: Option<String>,
+    },
+    VolumeExceedsFleet {
+        order_id: String,
+        volume: f64,
+        capacity: f64,
+        unit: Option<String>,
+    },
+    // Solved as the identical earlier order under config.dedupe and given its assignments
+    DuplicateOrder {
+        order_id: String,
+        duplicate_of: String,
+    },
+}
+
+pub fn validate_inputs(
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+
+    if drivers.is_empty() {
+        errors.push(ValidationError::EmptyDrivers);
+    }
+    if vehicles.is_empty() {
+        errors.push(ValidationError::EmptyVehicles);
+    }
+    if orders.is_empty() {
+        errors.push(ValidationError::EmptyOrders);
+    }
+
+    const MINUTES_PER_DAY: u32 = 24 * 60;
+    for driver in drivers {
+        if driver.breaks.iter().flatten().any(|b| b.to <= b.from) {
+            errors.push(ValidationError::InvalidBreak {
+                driver_id: driver.id.clone(),
+            });
+        }
+        if driver.recurring_breaks.iter().flatten().any(|b| {
+            b.start_minute_of_day >= MINUTES_PER_DAY || b.duration_minutes > MINUTES_PER_DAY
+        }) {
+            errors.push(ValidationError::InvalidDailyBreak {
+                driver_id: driver.id.clone(),
+            });
+        }
+    }
+
+    for vehicle in vehicles {
+        if vehicle
+            .max_distance_km
+            .is_some_and(|km| !km.is_finite() || km < 0.0)
+        {
+            errors.push(ValidationError::InvalidMaxDistance {
+                vehicle_id: vehicle.id.clone(),
+            });
+        }
+    }
+
+    let vehicle_tags: HashSet<&String> = vehicles
+        .iter()
+        .flat_map(|v| v.tags.iter().flatten())
+        .collect();
+    let mut seen_order_ids = HashSet::new();
+    for order in orders {
+        if !seen_order_ids.insert(&order.id) {
+            errors.push(ValidationError::DuplicateOrderId {
+                order_id: order.id.clone(),
+            });
+        }
+        if order.end_time <= order.start_time {
+            errors.push(ValidationError::InvalidTimeRange {
+                order_id: order.id.clone(),
+            });
+        }
+        if order.duration_minutes.is_some_and(|minutes| minutes <= 0) {
+            errors.push(ValidationError::InvalidDuration {
+                order_id: order.id.clone(),
+            });
+        }
+        let (window_start, window_end) = order.window();
+        if window_start > window_end {
+            errors.push(ValidationError::InvalidWindow {
+                order_id: order.id.clone(),
+            });
+        }
+        if order.service_minutes.is_some_and(|minutes| minutes < 0) {
+            errors.push(ValidationError::NegativeServiceTime {
+                order_id: order.id.clone(),
+            });
+        }
+        if order.weight < 0.0 {
+            errors.push(ValidationError::NegativeWeight {
+                order_id: order.id.clone(),
+            });
+        }
+        if order.required_drivers == Some(0)
+            || (order.splittable && order.required_drivers.unwrap_or(1) > 1)
+        {
+            errors.push(ValidationError::InvalidRequiredDrivers {
+                order_id: order.id.clone(),
+            });
+        }
+        for tag in order.tags.iter().flatten() {
+            if !vehicle_tags.contains(tag) {
+                errors.push(ValidationError::UnknownTag {
+                    order_id: order.id.clone(),
+                    tag: tag.clone(),
+                });
+            }
+        }
+    }
+
+    errors
+}
+
+// Orders no vehicle could carry even when empty. Volumes are only compared when every vehicle
+// has a max_volume.
+pub fn input_warnings(
+    vehicles: &[Vehicle],
+    orders: &[Order],
+    config: &SolverConfig,
+) -> Vec<ValidationWarning> {
+    // An empty fleet is already a validation error
+    if vehicles.is_empty() {
+        return Vec::new();
+    }
+    let largest_weight = vehicles.iter().map(|v| v.max_weight).fold(0.0, f64::max);
+    let fleet_weight: f64 = vehicles.iter().map(|v| v.max_weight).sum();
+    let volumes: Option<Vec<f64>> = vehicles.iter().map(|v| v.max_volume).collect();
+    let mut warnings = Vec::new();
+
+    for order in orders {
+        let weight_capacity = if order.splittable {
+            fleet_weight
+        } else {
+            largest_weight
+        };
+        if order.weight > weight_capacity {
+            warnings.push(ValidationWarning::WeightExceedsFleet {
+                order_id: order.id.clone(),
+                weight: order.weight,
+                capacity: weight_capacity,
+                unit: config.weight_unit.clone(),
+            });
+        }
+        if let (Some(volume), Some(volumes)) = (order.volume, &volumes) {
+            let volume_capacity = if order.splittable {
+                volumes.iter().sum()
+            } else {
+                volumes.iter().copied().fold(0.0, f64::max)
+            };
+            if volume > volume_capacity {
+                warnings.push(ValidationWarning::VolumeExceedsFleet {
+                    order_id: order.id.clone(),
+                    volume,
+                    capacity: volume_capacity,
+                    unit: config.volume_unit.clone(),
+                });
+            }
+        }
+    }
+
+    warnings
+}
+
+// Id-level checks of a plan, without replaying any schedule: every referenced order, driver and
+// vehicle exists, no assignment is listed twice and only splittable orders, or crew orders up to
+// their crew size, appear more than once.
+// Each message starts with the offending id.
+pub fn validate_assignment_structure(
+    assignments: &[Assignment],
+    drivers: &[Driver],
+    vehicles: &[Vehicle],
+    orders: &[Order],
+) -> Vec<String> {
+    let driver_ids: HashSet<&str> = drivers.iter().map(|d| d.id.as_str()).collect();
+    let vehicle_ids: HashSet<&str> = vehicles.iter().map(|v| v.id.as_str()).collect();
+    let orders_by_id: HashMap<&str, &Order> = orders.iter().map(|o| (o.id.as_str(), o)).collect();
+    let mut messages = Vec::new();
+    let mut seen = HashSet::new();
+    let mut assignment_counts: HashMap<&str, usize> = HashMap::new();
+
+    for assignment in assignments {
+        let order_id = assignment.order_id.as_str();
+        if !orders_by_id.contains_key(order_id) {
+            messages.push(format!("order {}: unknown order id", order_id));
+        }
+        if !driver_ids.contains(assignment.driver_id.as_str()) {
+            messages.push(format!(
+                "driver {}: unknown driver id in the assignment of order {}",
+                assignment.driver_id, order_id
+            ));
+        }
+        if !vehicle_ids.contains(assignment.vehicle_id.as_str()) {
+            messages.push(format!(
+                "vehicle {}: unknown vehicle id in the assignment of order {}",
+                assignment.vehicle_id, order_id
+            ));
+        }
+        let key = (
+            order_id,
+            assignment.driver_id.as_str(),
+            assignment.vehicle_id.as_str(),
+            assignment.start_time,
+            assignment.end_time,
+            assignment.weight.map(f64::to_bits),
+        );
+        if !seen.insert(key) {
+            messages.push(format!(
+                "order {}: the same assignment is listed twice",
+                order_id
+            ));
+            continue;
+        }
+        *assignment_counts.entry(order_id).or_insert(0) += 1;
+    }
+
+    // Reported once per order, in input order
+    for order in orders {
+        let count = assignment_counts
+            .get(order.id.as_str())
+            .copied()
+            .unwrap_or(0);
+        if count > order.crew_size() && !order.splittable {
+            messages.push(format!("order {}: assigned {} times", order.id, count));
+        }
+    }
+
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::fixtures::{at, driver, order, vehicle};
+    use super::*;
+
+    fn order_errors(order: Order) -> Vec<ValidationError> {
+        validate_inputs(&[driver("d1")], &[vehicle("v1")], &[order])
+    }
+
+    #[test]
+    fn accepts_a_plain_order() {
+        assert!(order_errors(order("o1", at(9, 0), at(10, 0))).is_empty());
+    }
+
+    #[test]
+    fn rejects_a_non_positive_duration() {
+        for minutes in [0, -15] {
+            let errors = order_errors(Order {
+                duration_minutes: Some(minutes),
+                ..order("o1", at(9, 0), at(10, 0))
+            });
+            assert!(
+                matches!(&errors[..], [ValidationError::InvalidDuration { order_id }] if order_id == "o1"),
+                "{:?}",
+                errors
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_a_window_ending_before_it_starts() {
+        let errors = order_errors(Order {
+            earliest_start: Some(at(11, 0)),
+            latest_end: Some(at(10, 30)),
+            ..order("o1", at(9, 0), at(10, 0))
+        });
+        assert!(
+            matches!(&errors[..], [ValidationError::InvalidWindow { order_id }] if order_id == "o1"),
+            "{:?}",
+            errors
+        );
+
+        // A missing latest_end falls back to end_time
+        let errors = order_errors(Order {
+            earliest_start: Some(at(10, 30)),
+            ..order("o1", at(9, 0), at(10, 0))
+        });
+        assert!(matches!(
+            &errors[..],
+            [ValidationError::InvalidWindow { .. }]
+        ));
+    }
+
+    #[test]
+    fn rejects_negative_service_minutes() {
+        let errors = order_errors(Order {
+            service_minutes: Some(-5),
+            ..order("o1", at(9, 0), at(10, 0))
+        });
+        assert!(
+            matches!(&errors[..], [ValidationError::NegativeServiceTime { order_id }] if order_id == "o1"),
+            "{:?}",
+            errors
+        );
+        let errors = order_errors(Order {
+            service_minutes: Some(0),
+            ..order("o1", at(9, 0), at(10, 0))
+        });
+        assert!(errors.is_empty());
+    }
+}